@@ -0,0 +1,96 @@
+use futures_util::future::join_all;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::Semaphore;
+
+use crate::{Error, ProviderKind, Result, TranslationRequest, Translator};
+
+/// A line of a JSON Lines batch: `{ "id", "text", "to"?, "from"? }`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchRequest {
+    /// Any JSON value identifying the request, echoed in its result.
+    pub id: Value,
+    pub text: String,
+    /// The target language. Defaults to the one given on the command line.
+    #[serde(default)]
+    pub to: Option<String>,
+    #[serde(default)]
+    pub from: Option<String>,
+}
+
+/// The result of a [`BatchRequest`], written as a line of JSON.
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchResult {
+    pub id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    pub to: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detected_source: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<ProviderKind>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Parse a JSON Lines batch, skipping blank lines.
+pub fn parse_batch(input: &str) -> Result<Vec<BatchRequest>> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).map_err(|e| {
+                Error::Custom(format!(
+                    "Invalid batch request on line {}: {}",
+                    index + 1,
+                    e
+                ))
+            })
+        })
+        .collect()
+}
+
+/// Translate every request of a batch, running at most `jobs` translations at a time.
+///
+/// A failed request does not stop the batch: its result carries the error instead of a text.
+/// Results are returned in the order of the requests.
+pub async fn translate_batch(
+    requests: &[BatchRequest],
+    translator: &dyn Translator,
+    default_to: &str,
+    default_from: Option<&str>,
+    jobs: usize,
+) -> Vec<BatchResult> {
+    let semaphore = Semaphore::new(jobs.max(1));
+
+    join_all(requests.iter().map(|request| async {
+        let to = request.to.as_deref().unwrap_or(default_to).to_string();
+        let from = request.from.as_deref().or(default_from).map(String::from);
+        let translation_request = TranslationRequest::new(request.text.clone(), from, to.clone());
+
+        let _permit = semaphore
+            .acquire()
+            .await
+            .expect("semaphore is never closed");
+        match translator.translate(&translation_request).await {
+            Ok(translation) => BatchResult {
+                id: request.id.clone(),
+                text: Some(translation.text),
+                to,
+                detected_source: translation.detected_source,
+                provider: translation.provider,
+                error: None,
+            },
+            Err(e) => BatchResult {
+                id: request.id.clone(),
+                text: None,
+                to,
+                detected_source: None,
+                provider: None,
+                error: Some(e.to_string()),
+            },
+        }
+    }))
+    .await
+}
@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Config, Error, Model, OnChunk, Result, Translation, TranslationRequest, Translator, UsageLog,
+    UsageMeter,
+};
+
+/// The `[budget]` section of the config file: caps on the estimated cost of the runs, in US
+/// dollars.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct BudgetConfig {
+    /// The most a single run may cost.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub per_run: Option<f64>,
+    /// The most the runs of a calendar month may cost together.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub per_month: Option<f64>,
+}
+
+impl BudgetConfig {
+    /// How much the next run may cost, given what the runs of this month already cost, or
+    /// `None` when there is no cap.
+    pub fn remaining(&self, log: &UsageLog) -> Result<Option<f64>> {
+        let monthly = match self.per_month {
+            Some(per_month) => Some(per_month - log.month_cost()?),
+            None => None,
+        };
+        Ok(match (self.per_run, monthly) {
+            (Some(per_run), Some(monthly)) => Some(per_run.min(monthly)),
+            (per_run, monthly) => per_run.or(monthly),
+        })
+    }
+}
+
+/// Translator that refuses to send more requests once the tokens used in the run cost as much
+/// as the budget allows.
+pub struct BudgetTranslator {
+    inner: Box<dyn Translator>,
+    meter: Arc<UsageMeter>,
+    config: Config,
+    limit: f64,
+}
+
+impl BudgetTranslator {
+    pub fn new(
+        inner: Box<dyn Translator>,
+        meter: Arc<UsageMeter>,
+        config: Config,
+        limit: f64,
+    ) -> Self {
+        Self {
+            inner,
+            meter,
+            config,
+            limit,
+        }
+    }
+
+    fn check(&self) -> Result<()> {
+        match self.meter.cost(&self.config) {
+            Some(cost) if cost >= self.limit => Err(Error::Custom(format!(
+                "The budget of ${:.2} for this run is used up; run again with --yes to go over it",
+                self.limit.max(0.0)
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl Translator for BudgetTranslator {
+    async fn translate(&self, request: &TranslationRequest) -> Result<Translation> {
+        self.check()?;
+        self.inner.translate(request).await
+    }
+
+    async fn translate_stream(
+        &self,
+        request: &TranslationRequest,
+        on_chunk: OnChunk<'_>,
+    ) -> Result<Translation> {
+        self.check()?;
+        self.inner.translate_stream(request, on_chunk).await
+    }
+
+    async fn models(&self) -> Result<Vec<Model>> {
+        self.inner.models().await
+    }
+}
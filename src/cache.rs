@@ -0,0 +1,204 @@
+use std::{fs, path::PathBuf};
+
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+use tracing::{debug, warn};
+
+use crate::{
+    Config, GenerationParams, OnChunk, ProviderKind, Result, Translation, TranslationRequest,
+    Translator,
+};
+
+/// A local cache of translations, stored as one JSON file per entry.
+pub struct Cache {
+    dir: PathBuf,
+}
+
+/// The number of entries in the cache and their total size in bytes.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CacheStats {
+    pub entries: u64,
+    pub size: u64,
+}
+
+impl Cache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// The cache in the user cache directory, e.g. `$HOME/.cache/trlt`.
+    pub fn open() -> Self {
+        Self::new(Self::default_dir())
+    }
+
+    pub fn default_dir() -> PathBuf {
+        dirs::cache_dir()
+            .expect("Failed to get cache directory")
+            .join("trlt")
+    }
+
+    pub fn dir(&self) -> &PathBuf {
+        &self.dir
+    }
+
+    /// The cache key of a request translated by the given provider and model, sent to
+    /// `endpoint` with the `generation` parameters.
+    pub fn key(
+        provider: ProviderKind,
+        model: Option<&str>,
+        endpoint: Option<&str>,
+        generation: &GenerationParams,
+        request: &TranslationRequest,
+    ) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(format!(
+            "{:?}\0{}\0{}",
+            provider,
+            model.unwrap_or_default(),
+            request.fingerprint()
+        ));
+        // Left out when unset, so that the entries cached before they were part of the key
+        // are still found.
+        if let Some(endpoint) = endpoint {
+            hasher.update(format!("\0{}", endpoint));
+        }
+        if !generation.is_empty() {
+            hasher.update(format!("\0{:?}", generation));
+        }
+        hex::encode(hasher.finalize())
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        // Shard entries by prefix to keep directories small.
+        self.dir.join(&key[..2]).join(format!("{}.json", key))
+    }
+
+    pub fn get(&self, key: &str) -> Option<Translation> {
+        let contents = fs::read_to_string(self.path(key)).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    pub fn put(&self, key: &str, translation: &Translation) -> Result<()> {
+        let path = self.path(key);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let contents =
+            serde_json::to_string(translation).map_err(|e| crate::Error::Custom(e.to_string()))?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        if self.dir.exists() {
+            fs::remove_dir_all(&self.dir)?;
+        }
+        Ok(())
+    }
+
+    pub fn stats(&self) -> Result<CacheStats> {
+        let mut stats = CacheStats::default();
+        if !self.dir.exists() {
+            return Ok(stats);
+        }
+
+        for shard in fs::read_dir(&self.dir)? {
+            let shard = shard?.path();
+            if !shard.is_dir() {
+                continue;
+            }
+            for entry in fs::read_dir(shard)? {
+                stats.entries += 1;
+                stats.size += entry?.metadata()?.len();
+            }
+        }
+
+        Ok(stats)
+    }
+}
+
+/// Translator that answers from the local cache when possible and stores new results.
+pub struct CachedTranslator {
+    inner: Box<dyn Translator>,
+    cache: Cache,
+    provider: ProviderKind,
+    model: Option<String>,
+    endpoint: Option<String>,
+    generation: GenerationParams,
+}
+
+impl CachedTranslator {
+    /// Cache the translations of `provider`, with its model, endpoint and generation
+    /// parameters in `config`.
+    pub fn new(
+        inner: Box<dyn Translator>,
+        cache: Cache,
+        provider: ProviderKind,
+        config: &Config,
+    ) -> Self {
+        Self {
+            inner,
+            cache,
+            provider,
+            model: config.model_for(provider),
+            endpoint: config.endpoint_for(provider),
+            generation: config.generation,
+        }
+    }
+
+    fn key(&self, request: &TranslationRequest) -> String {
+        Cache::key(
+            self.provider,
+            self.model.as_deref(),
+            self.endpoint.as_deref(),
+            &self.generation,
+            request,
+        )
+    }
+
+    /// Store `translation`, recording the provider that made it, which is a fallback provider
+    /// when the requested one failed.
+    fn store(&self, key: &str, translation: &Translation) {
+        let translation = Translation {
+            provider: Some(translation.provider.unwrap_or(self.provider)),
+            ..translation.clone()
+        };
+        if let Err(e) = self.cache.put(key, &translation) {
+            warn!("Failed to write to the cache: {}", e);
+        }
+    }
+}
+
+#[async_trait]
+impl Translator for CachedTranslator {
+    async fn translate(&self, request: &TranslationRequest) -> Result<Translation> {
+        let key = self.key(request);
+        if let Some(translation) = self.cache.get(&key) {
+            debug!(key, "Cache hit");
+            return Ok(translation);
+        }
+        debug!(key, "Cache miss");
+
+        let translation = self.inner.translate(request).await?;
+        self.store(&key, &translation);
+        Ok(translation)
+    }
+
+    async fn translate_stream(
+        &self,
+        request: &TranslationRequest,
+        on_chunk: OnChunk<'_>,
+    ) -> Result<Translation> {
+        let key = self.key(request);
+        if let Some(translation) = self.cache.get(&key) {
+            debug!(key, "Cache hit");
+            on_chunk(&translation.text);
+            return Ok(translation);
+        }
+        debug!(key, "Cache miss");
+
+        let translation = self.inner.translate_stream(request, on_chunk).await?;
+        self.store(&key, &translation);
+        Ok(translation)
+    }
+}
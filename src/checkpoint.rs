@@ -0,0 +1,188 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::{BufRead, BufReader, Write},
+    path::PathBuf,
+    sync::Mutex,
+};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::{Error, Result, Task, Translation, TranslationRequest, Translator};
+
+/// A line of a checkpoint file.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+enum Entry {
+    /// The command line of the job and the directory it was run from, written first.
+    Command { args: Vec<String>, dir: PathBuf },
+    /// A segment translated by the job.
+    Segment { fingerprint: String, text: String },
+    /// An input translated in full.
+    Done { job: String },
+}
+
+/// The progress of a batch translation, recorded as it goes so that an interrupted run can be
+/// resumed without translating again what it already did.
+///
+/// Checkpoints are JSON Lines files in the user data directory, e.g.
+/// `$HOME/.local/share/trlt/jobs/<id>.jsonl`, appended to after every segment.
+pub struct Checkpoint {
+    id: String,
+    path: PathBuf,
+    args: Vec<String>,
+    dir: PathBuf,
+    completed: HashSet<String>,
+    segments: HashMap<String, String>,
+    file: Mutex<fs::File>,
+}
+
+impl Checkpoint {
+    pub fn default_dir() -> PathBuf {
+        dirs::data_dir()
+            .expect("Failed to get data directory")
+            .join("trlt")
+            .join("jobs")
+    }
+
+    /// Start recording a job run with the command-line arguments `args` from `dir`.
+    pub fn create(args: Vec<String>, dir: PathBuf) -> Result<Self> {
+        let id = format!(
+            "{}-{:x}",
+            Utc::now().format("%Y%m%d-%H%M%S"),
+            std::process::id()
+        );
+        let path = Self::default_dir().join(format!("{}.jsonl", id));
+        fs::create_dir_all(Self::default_dir())?;
+        let checkpoint = Self {
+            file: Mutex::new(fs::File::create(&path)?),
+            id,
+            path,
+            args: args.clone(),
+            dir: dir.clone(),
+            completed: HashSet::new(),
+            segments: HashMap::new(),
+        };
+        checkpoint.append(&Entry::Command { args, dir })?;
+        Ok(checkpoint)
+    }
+
+    /// Load the checkpoint of an interrupted job, to carry on recording into it.
+    pub fn open(id: &str) -> Result<Self> {
+        let path = Self::default_dir().join(format!("{}.jsonl", id));
+        let file = fs::File::open(&path)
+            .map_err(|_| Error::Custom(format!("There is no interrupted job `{}`", id)))?;
+
+        let mut command = None;
+        let mut completed = HashSet::new();
+        let mut segments = HashMap::new();
+        for line in BufReader::new(file).lines() {
+            // The last line is cut short when the job was killed while writing it.
+            let Ok(entry) = serde_json::from_str::<Entry>(&line?) else {
+                continue;
+            };
+            match entry {
+                Entry::Command { args, dir } => command = Some((args, dir)),
+                Entry::Segment { fingerprint, text } => {
+                    segments.insert(fingerprint, text);
+                }
+                Entry::Done { job } => {
+                    completed.insert(job);
+                }
+            }
+        }
+        let (args, dir) =
+            command.ok_or_else(|| Error::Custom(format!("The job `{}` is corrupted", id)))?;
+
+        Ok(Self {
+            file: Mutex::new(fs::OpenOptions::new().append(true).open(&path)?),
+            id: id.to_string(),
+            path,
+            args,
+            dir,
+            completed,
+            segments,
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The command-line arguments the job was started with.
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// The directory the job was started from, which relative paths are resolved against.
+    pub fn dir(&self) -> &PathBuf {
+        &self.dir
+    }
+
+    /// Whether `job` was translated in full before the interruption.
+    pub fn is_completed(&self, job: &str) -> bool {
+        self.completed.contains(job)
+    }
+
+    /// Record that `job` was translated in full.
+    pub fn complete(&self, job: &str) -> Result<()> {
+        self.append(&Entry::Done {
+            job: job.to_string(),
+        })
+    }
+
+    /// Delete the checkpoint once the job has finished.
+    pub fn remove(self) -> Result<()> {
+        fs::remove_file(&self.path)?;
+        Ok(())
+    }
+
+    fn append(&self, entry: &Entry) -> Result<()> {
+        let line = serde_json::to_string(entry)
+            .map_err(|e| Error::Custom(format!("Unable to write the checkpoint: {}", e)))?;
+        let mut file = self.file.lock().expect("checkpoint lock poisoned");
+        writeln!(file, "{}", line)?;
+        Ok(())
+    }
+}
+
+/// Translator that answers the segments recorded in a checkpoint and records the new ones.
+pub struct CheckpointTranslator<'a> {
+    inner: &'a dyn Translator,
+    checkpoint: &'a Checkpoint,
+}
+
+impl<'a> CheckpointTranslator<'a> {
+    pub fn new(inner: &'a dyn Translator, checkpoint: &'a Checkpoint) -> Self {
+        Self { inner, checkpoint }
+    }
+}
+
+#[async_trait]
+impl Translator for CheckpointTranslator<'_> {
+    async fn translate(&self, request: &TranslationRequest) -> Result<Translation> {
+        if request.task != Task::Translate {
+            return self.inner.translate(request).await;
+        }
+
+        let fingerprint = request.fingerprint();
+        if let Some(text) = self.checkpoint.segments.get(&fingerprint) {
+            return Ok(Translation {
+                text: text.clone(),
+                ..Default::default()
+            });
+        }
+
+        let translation = self.inner.translate(request).await?;
+        if let Err(e) = self.checkpoint.append(&Entry::Segment {
+            fingerprint,
+            text: translation.text.clone(),
+        }) {
+            warn!("Failed to write the checkpoint: {}", e);
+        }
+        Ok(translation)
+    }
+}
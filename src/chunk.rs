@@ -0,0 +1,248 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures_util::future::try_join_all;
+use tokio::sync::Semaphore;
+use tracing::info;
+
+use crate::{OnChunk, Progress, Result, Task, Translation, TranslationRequest, Translator, Usage};
+
+/// The default maximum size of a chunk, in bytes.
+pub const DEFAULT_CHUNK_SIZE: usize = 8000;
+
+/// How much of the previous chunk is sent along as context, in bytes.
+const CONTEXT_SIZE: usize = 500;
+
+const SENTENCE_ENDS: &[&str] = &[". ", "! ", "? ", ".\n", "!\n", "?\n", "。", "！", "？"];
+
+/// Split `text` into contiguous pieces of at most `max_size` bytes.
+///
+/// Pieces break at paragraph boundaries when possible, then at sentence boundaries, then
+/// at whitespace. Concatenating the pieces gives back the original text.
+pub fn split_text(text: &str, max_size: usize) -> Vec<&str> {
+    let max_size = max_size.max(1);
+    let mut pieces = Vec::new();
+    let mut start = 0;
+
+    while text.len() - start > max_size {
+        let mut limit = start + max_size;
+        while !text.is_char_boundary(limit) {
+            limit -= 1;
+        }
+        let window = &text[start..limit];
+
+        let end = window
+            .rfind("\n\n")
+            .map(|i| i + 2)
+            .or_else(|| {
+                SENTENCE_ENDS
+                    .iter()
+                    .filter_map(|end| window.rfind(end).map(|i| i + end.len()))
+                    .max()
+            })
+            .or_else(|| window.rfind(char::is_whitespace).map(|i| i + 1))
+            .filter(|end| *end > 0)
+            .unwrap_or(window.len().max(1));
+
+        let mut end = start + end;
+        while !text.is_char_boundary(end) {
+            end += 1;
+        }
+        pieces.push(&text[start..end]);
+        start = end;
+    }
+
+    if start < text.len() {
+        pieces.push(&text[start..]);
+    }
+
+    pieces
+}
+
+/// The tail of `text`, at most [`CONTEXT_SIZE`] bytes, starting at a word boundary.
+fn context_of(text: &str) -> String {
+    let text = text.trim();
+    if text.len() <= CONTEXT_SIZE {
+        return text.to_string();
+    }
+
+    let mut start = text.len() - CONTEXT_SIZE;
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    let tail = &text[start..];
+    match tail.find(char::is_whitespace) {
+        Some(i) => tail[i..].trim_start().to_string(),
+        None => tail.to_string(),
+    }
+}
+
+/// Split a piece into its leading whitespace, content and trailing whitespace.
+fn trim_parts(piece: &str) -> (&str, &str, &str) {
+    let content = piece.trim();
+    let leading = &piece[..piece.len() - piece.trim_start().len()];
+    let trailing = &piece[piece.trim_end().len()..];
+    (leading, content, trailing)
+}
+
+/// Translator that splits long inputs into chunks, translates each one with the end of the
+/// previous chunk as context, and reassembles the results in order.
+pub struct ChunkedTranslator {
+    inner: Box<dyn Translator>,
+    chunk_size: usize,
+    jobs: usize,
+    progress: Option<Arc<Progress>>,
+}
+
+impl ChunkedTranslator {
+    pub fn new(inner: Box<dyn Translator>, chunk_size: usize, jobs: usize) -> Self {
+        Self {
+            inner,
+            chunk_size,
+            jobs: jobs.max(1),
+            progress: None,
+        }
+    }
+
+    /// Count the chunks translated, and the ones to come, in `progress`.
+    pub fn with_progress(mut self, progress: Arc<Progress>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    fn add_chunks(&self, count: usize) {
+        if let Some(progress) = &self.progress {
+            progress.add_chunks(count);
+        }
+    }
+
+    fn chunk_done(&self) {
+        if let Some(progress) = &self.progress {
+            progress.chunk_done();
+        }
+    }
+
+    /// The requests for each chunk of `request`, with the whitespace around each chunk.
+    fn chunk_requests<'a>(
+        &self,
+        request: &'a TranslationRequest,
+    ) -> Vec<(&'a str, TranslationRequest, &'a str)> {
+        let mut previous: Option<&str> = None;
+
+        split_text(&request.text, self.chunk_size)
+            .into_iter()
+            .map(|piece| {
+                let (leading, content, trailing) = trim_parts(piece);
+                let chunk_request = TranslationRequest {
+                    text: content.to_string(),
+                    preceding: previous.map(context_of),
+                    ..request.clone()
+                };
+                previous = Some(content);
+                (leading, chunk_request, trailing)
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Translator for ChunkedTranslator {
+    async fn translate(&self, request: &TranslationRequest) -> Result<Translation> {
+        // Only plain translations can be stitched back together from chunks.
+        if request.text.len() <= self.chunk_size || request.task != Task::Translate {
+            self.add_chunks(1);
+            let translation = self.inner.translate(request).await?;
+            self.chunk_done();
+            return Ok(translation);
+        }
+
+        let chunks = self.chunk_requests(request);
+        info!(
+            bytes = request.text.len(),
+            chunk_size = self.chunk_size,
+            "Split the input into {} chunks",
+            chunks.len()
+        );
+        self.add_chunks(chunks.len());
+        let semaphore = Semaphore::new(self.jobs);
+        let translations = try_join_all(chunks.iter().map(|(_, chunk, _)| async {
+            let _permit = semaphore
+                .acquire()
+                .await
+                .expect("semaphore is never closed");
+            let translation = match chunk.text.is_empty() {
+                true => Ok(Translation::default()),
+                false => self.inner.translate(chunk).await,
+            };
+            if translation.is_ok() {
+                self.chunk_done();
+            }
+            translation
+        }))
+        .await?;
+
+        let mut text = String::new();
+        for ((leading, _, trailing), translation) in chunks.iter().zip(&translations) {
+            text.push_str(leading);
+            text.push_str(&translation.text);
+            text.push_str(trailing);
+        }
+
+        Ok(Translation {
+            text,
+            detected_source: translations
+                .iter()
+                .find_map(|translation| translation.detected_source.clone()),
+            provider: translations
+                .iter()
+                .find_map(|translation| translation.provider),
+            usage: translations
+                .iter()
+                .filter_map(|translation| translation.usage.clone())
+                .reduce(|total, usage| Usage {
+                    prompt_tokens: total.prompt_tokens + usage.prompt_tokens,
+                    completion_tokens: total.completion_tokens + usage.completion_tokens,
+                    ..total
+                }),
+        })
+    }
+
+    async fn translate_stream(
+        &self,
+        request: &TranslationRequest,
+        on_chunk: OnChunk<'_>,
+    ) -> Result<Translation> {
+        if request.text.len() <= self.chunk_size {
+            self.add_chunks(1);
+            let translation = self.inner.translate_stream(request, on_chunk).await?;
+            self.chunk_done();
+            return Ok(translation);
+        }
+
+        // Streamed chunks must arrive in order, so they are translated one at a time.
+        let chunks = self.chunk_requests(request);
+        info!(
+            bytes = request.text.len(),
+            chunk_size = self.chunk_size,
+            "Split the input into {} chunks, streamed one at a time",
+            chunks.len()
+        );
+        self.add_chunks(chunks.len());
+        let mut result = Translation::default();
+        for (leading, chunk, trailing) in chunks {
+            on_chunk(leading);
+            result.text.push_str(leading);
+            if !chunk.text.is_empty() {
+                let translation = self.inner.translate_stream(&chunk, on_chunk).await?;
+                result.text.push_str(&translation.text);
+                result.detected_source = result.detected_source.or(translation.detected_source);
+                result.provider = result.provider.or(translation.provider);
+            }
+            self.chunk_done();
+            on_chunk(trailing);
+            result.text.push_str(trailing);
+        }
+
+        Ok(result)
+    }
+}
@@ -0,0 +1,101 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::{DIMMED_STYLE, HEADING_STYLE, INSERTED_STYLE};
+
+/// The narrowest terminal, in columns, that [`side_by_side`] lays out in two columns.
+pub const MIN_SIDE_BY_SIDE_WIDTH: usize = 80;
+
+/// What separates the two columns.
+const SEPARATOR: &str = " │ ";
+
+/// Lay out source/translation pairs, such as paragraphs, for a terminal `width` columns wide:
+/// side by side with each pair aligned, or one after the other when the terminal is narrower
+/// than [`MIN_SIDE_BY_SIDE_WIDTH`].
+pub fn side_by_side(pairs: &[(String, String)], width: usize) -> String {
+    if width < MIN_SIDE_BY_SIDE_WIDTH {
+        return interleaved(pairs, width);
+    }
+
+    let column = (width - SEPARATOR.width()) / 2;
+    let separator = format!("{DIMMED_STYLE}{}{DIMMED_STYLE:#}", SEPARATOR);
+    let mut view = format!(
+        "{HEADING_STYLE}{}{HEADING_STYLE:#}{}{HEADING_STYLE}{}{HEADING_STYLE:#}\n",
+        pad("Source", column),
+        separator,
+        "Translation"
+    );
+    for (index, (source, translation)) in pairs.iter().enumerate() {
+        if index > 0 {
+            view.push_str(&format!("{}{}\n", " ".repeat(column), separator));
+        }
+        let left = wrap(source, column);
+        let right = wrap(translation, column);
+        for line in 0..left.len().max(right.len()) {
+            let left = left.get(line).map(String::as_str).unwrap_or_default();
+            let right = right.get(line).map(String::as_str).unwrap_or_default();
+            view.push_str(&format!(
+                "{}{}{INSERTED_STYLE}{}{INSERTED_STYLE:#}\n",
+                pad(left, column),
+                separator,
+                right
+            ));
+        }
+    }
+    view
+}
+
+/// Each source followed by its translation, indented.
+fn interleaved(pairs: &[(String, String)], width: usize) -> String {
+    let width = width.max(20);
+    let mut view = String::new();
+    for (index, (source, translation)) in pairs.iter().enumerate() {
+        if index > 0 {
+            view.push('\n');
+        }
+        for line in wrap(source, width) {
+            view.push_str(&line);
+            view.push('\n');
+        }
+        for line in wrap(translation, width - 2) {
+            view.push_str(&format!("  {INSERTED_STYLE}{}{INSERTED_STYLE:#}\n", line));
+        }
+    }
+    view
+}
+
+/// `text` followed by spaces up to `width` columns.
+fn pad(text: &str, width: usize) -> String {
+    format!("{}{}", text, " ".repeat(width.saturating_sub(text.width())))
+}
+
+/// Break `text` into lines of at most `width` columns, at spaces when possible, keeping its
+/// own line breaks.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    for paragraph in text.trim().lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let separator = usize::from(!line.is_empty());
+            if line.width() + separator + word.width() <= width {
+                if separator == 1 {
+                    line.push(' ');
+                }
+                line.push_str(word);
+                continue;
+            }
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            // Words longer than a line, or scripts written without spaces, are broken anywhere.
+            for c in word.chars() {
+                if line.width() + c.width().unwrap_or(0) > width {
+                    lines.push(std::mem::take(&mut line));
+                }
+                line.push(c);
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
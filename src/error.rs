@@ -4,6 +4,22 @@ use derive_more::derive::From;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// The exit code of `trlt` for failures without a more specific code.
+pub const EXIT_FAILURE: u8 = 1;
+/// The exit code for invalid arguments or input, such as an unknown language or a missing
+/// input file. Also used for command-line usage errors.
+pub const EXIT_INVALID_INPUT: u8 = 2;
+/// The exit code when the config file or an API key is missing or invalid.
+pub const EXIT_CONFIG: u8 = 3;
+/// The exit code when the provider rejected the credentials.
+pub const EXIT_AUTH: u8 = 4;
+/// The exit code when the provider kept rejecting requests for exceeding its rate limit.
+pub const EXIT_RATE_LIMITED: u8 = 5;
+/// The exit code when the provider could not be reached, timed out or kept failing.
+pub const EXIT_NETWORK: u8 = 6;
+/// The exit code when some inputs of a batch failed to translate while others succeeded.
+pub const EXIT_PARTIAL_FAILURE: u8 = 7;
+
 #[derive(Debug, From)]
 pub enum Error {
     Custom(String),
@@ -11,12 +27,76 @@ pub enum Error {
     FailedToGetConfigDirectory,
     UnableToConvertToToml(toml::ser::Error),
     UnableToConvertFromToml(toml::de::Error),
+    RequestFailed(reqwest::Error),
+    Database(rusqlite::Error),
+    Archive(zip::result::ZipError),
+    #[from(ignore)]
+    Api(String),
+    #[from(ignore)]
+    MissingApiKey(crate::ProviderKind),
+    /// There is no config file; `trlt init` creates one.
+    MissingConfig,
+    /// The server rejected the credentials with this status and body.
+    #[from(ignore)]
+    Unauthorized(u16, String),
+    #[from(ignore)]
+    InvalidInput(String),
+    /// The server answered with a status worth retrying, e.g. 429 or 503, with this body and,
+    /// when it said so, how long to wait before retrying.
+    #[from(ignore)]
+    Transient(u16, String, Option<std::time::Duration>),
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+        match self {
+            Error::Custom(message) | Error::Api(message) | Error::InvalidInput(message) => {
+                write!(f, "{}", message)
+            }
+            Error::UnableToWriteConfigToFile(e) => write!(f, "{}", e),
+            Error::FailedToGetConfigDirectory => write!(f, "Unable to find the config directory"),
+            Error::UnableToConvertToToml(e) => write!(f, "Unable to write the config: {}", e),
+            Error::UnableToConvertFromToml(e) => write!(f, "Invalid config: {}", e.message()),
+            Error::RequestFailed(e) => match std::error::Error::source(e) {
+                Some(source) => write!(f, "{}: {}", e, source),
+                None => write!(f, "{}", e),
+            },
+            Error::Database(e) => write!(f, "Database error: {}", e),
+            Error::Archive(e) => write!(f, "Invalid archive: {}", e),
+            Error::MissingApiKey(provider) => write!(f, "Missing API key for {}", provider),
+            Error::MissingConfig => write!(f, "No config file found"),
+            Error::Unauthorized(status, body) => {
+                write!(
+                    f,
+                    "The provider rejected the credentials ({}): {}",
+                    status, body
+                )
+            }
+            Error::Transient(status, body, _) => {
+                write!(f, "The provider answered with status {}: {}", status, body)
+            }
+        }
     }
 }
 
 impl std::error::Error for Error {}
+
+impl Error {
+    /// The exit code of `trlt` when it fails with this error.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::MissingConfig
+            | Error::MissingApiKey(_)
+            | Error::FailedToGetConfigDirectory
+            | Error::UnableToConvertFromToml(_) => EXIT_CONFIG,
+            Error::Unauthorized(..) => EXIT_AUTH,
+            Error::InvalidInput(_) => EXIT_INVALID_INPUT,
+            Error::Transient(429, ..) => EXIT_RATE_LIMITED,
+            Error::Transient(..) => EXIT_NETWORK,
+            Error::RequestFailed(e) if e.is_connect() || e.is_timeout() || e.is_request() => {
+                EXIT_NETWORK
+            }
+            _ => EXIT_FAILURE,
+        }
+    }
+}
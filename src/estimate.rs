@@ -0,0 +1,54 @@
+use async_trait::async_trait;
+
+use crate::{Result, Translation, TranslationRequest, Translator, Usage};
+
+/// Count the tokens of `text` with the tokenizer of `model`. Models without a known tokenizer,
+/// such as those of other vendors, are counted with the one of recent OpenAI models, which
+/// gives a close enough estimate.
+pub fn count_tokens(model: &str, text: &str) -> u64 {
+    let bpe =
+        tiktoken_rs::bpe_for_model(model).unwrap_or_else(|_| tiktoken_rs::o200k_base_singleton());
+    bpe.encode_with_special_tokens(text).len() as u64
+}
+
+/// Translator that sends nothing: it counts the tokens each request would use, assuming the
+/// translation is about as long as the source, and gives the source text back. It lets a run
+/// be estimated without calling the provider.
+pub struct DryRunTranslator {
+    model: String,
+    language_model: bool,
+}
+
+impl DryRunTranslator {
+    /// `language_model` tells whether the prompts are sent along with the text, as they are to
+    /// language models but not to machine translation services.
+    pub fn new(model: String, language_model: bool) -> Self {
+        Self {
+            model,
+            language_model,
+        }
+    }
+}
+
+#[async_trait]
+impl Translator for DryRunTranslator {
+    async fn translate(&self, request: &TranslationRequest) -> Result<Translation> {
+        let text_tokens = count_tokens(&self.model, &request.text);
+        let prompt_tokens = if self.language_model {
+            count_tokens(&self.model, request.system())
+                + count_tokens(&self.model, &request.prompt())
+        } else {
+            text_tokens
+        };
+
+        Ok(Translation {
+            text: request.text.clone(),
+            usage: Some(Usage {
+                model: self.model.clone(),
+                prompt_tokens,
+                completion_tokens: text_tokens,
+            }),
+            ..Default::default()
+        })
+    }
+}
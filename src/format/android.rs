@@ -0,0 +1,192 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::LazyLock,
+};
+
+use regex::Regex;
+
+use crate::Document;
+
+/// The elements holding text: strings, and string arrays and plurals, whose items do. Comments
+/// are matched so they can be skipped, and strings and string arrays marked as not
+/// translatable so they can be left out of the translation.
+static ELEMENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"(?s)<!--.*?-->",
+        r#"|(?P<fixed><(?:string-array|string)\s[^>]*\btranslatable="false"[^>]*>.*?</(?:string-array|string)>)"#,
+        r"|<string(?:\s[^>]*)?>(?P<content>.*?)</string>",
+        r"|<(?:string-array|plurals)(?:\s[^>]*)?>(?P<items>.*?)</(?:string-array|plurals)>",
+    ))
+    .expect("element pattern is valid")
+});
+
+/// The items of a string array or of plurals, and comments between them.
+static ITEM: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)<!--.*?-->|<item(?:\s[^>]*)?>(?P<content>.*?)</item>")
+        .expect("item pattern is valid")
+});
+
+/// Markup within a string, which is kept as it is: `<xliff:g>` placeholders with their
+/// content, CDATA sections, other tags and character references.
+static INLINE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"(?s)<xliff:g\b[^>]*>.*?</xliff:g>|<!\[CDATA\[.*?\]\]>|<[^>]+>",
+        r"|&(?:#\d+|#x[0-9a-fA-F]+|[A-Za-z]+);",
+    ))
+    .expect("inline pattern is valid")
+});
+
+/// Whether `path` is an Android string resource, such as `res/values/strings.xml`, or
+/// `arrays.xml` and `plurals.xml` in a `values` directory. Other resources there, such as
+/// `styles.xml` and `dimens.xml`, have no text to translate.
+pub fn is_android_resource(path: &Path) -> bool {
+    let in_values = path
+        .parent()
+        .and_then(Path::file_name)
+        .is_some_and(|dir| dir.to_string_lossy().starts_with("values"));
+    let is_xml = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("xml"));
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let has_strings = ["strings", "arrays", "plurals"]
+        .iter()
+        .any(|prefix| name.starts_with(prefix));
+    is_xml && (name == "strings.xml" || (in_values && has_strings))
+}
+
+/// The resource directory for `lang`, e.g. `values-pt-rBR` for `pt-BR` or `values-b+zh+Hant`
+/// for `zh-Hant`.
+pub fn android_values_dir(lang: &str) -> String {
+    let parts = lang.split(['-', '_']).collect::<Vec<_>>();
+    match parts.as_slice() {
+        [language] => format!("values-{}", language.to_lowercase()),
+        [language, region]
+            if region.len() == 2
+                || (region.len() == 3 && region.chars().all(|c| c.is_ascii_digit())) =>
+        {
+            format!(
+                "values-{}-r{}",
+                language.to_lowercase(),
+                region.to_uppercase()
+            )
+        }
+        _ => format!("values-b+{}", parts.join("+")),
+    }
+}
+
+/// Where the translation of an Android resource goes: the same file in the sibling
+/// `values-xx` directory, e.g. `res/values-pt/strings.xml`.
+pub fn android_output_path(path: &Path, lang: &str) -> PathBuf {
+    path.parent()
+        .and_then(Path::parent)
+        .unwrap_or(Path::new(""))
+        .join(android_values_dir(lang))
+        .join(path.file_name().unwrap_or_default())
+}
+
+/// Split an Android string resource file so that the text of its strings, string arrays and
+/// plurals is translated. References to other resources are kept as they are, and strings
+/// marked `translatable="false"` are left out, as Android expects them in the default
+/// resources only.
+pub fn parse_android(input: &str) -> Document {
+    let mut document = Document::new();
+    let mut last = 0;
+
+    for element in ELEMENT.captures_iter(input) {
+        if let Some(fixed) = element.name("fixed") {
+            let before = input[last..fixed.start()].trim_end_matches([' ', '\t']);
+            document.push_verbatim(before.strip_suffix('\n').unwrap_or(before));
+            last = fixed.end();
+            continue;
+        }
+        if let Some(content) = element.name("content") {
+            push_text(
+                &mut document,
+                input,
+                &mut last,
+                content.start(),
+                content.end(),
+            );
+        } else if let Some(items) = element.name("items") {
+            for item in ITEM.captures_iter(items.as_str()) {
+                if let Some(content) = item.name("content") {
+                    let start = items.start() + content.start();
+                    push_text(
+                        &mut document,
+                        input,
+                        &mut last,
+                        start,
+                        start + content.len(),
+                    );
+                }
+            }
+        }
+    }
+    document.push_verbatim(&input[last..]);
+
+    document
+}
+
+/// Add the text at `start..end` of `input` to `document`, after what follows `last`, unless it
+/// is a reference to another resource.
+fn push_text(document: &mut Document, input: &str, last: &mut usize, start: usize, end: usize) {
+    let text = decode(&input[start..end]);
+    if text.starts_with(['@', '?']) {
+        return;
+    }
+    document.push_verbatim(&input[*last..start]);
+    document.push_protected_encoded(&text, &INLINE, Box::new(encode));
+    *last = end;
+}
+
+/// Undo the escaping Android requires for quotes, newlines and leading `@` or `?`, outside
+/// of markup.
+fn decode(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    let mut in_tag = false;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            '\\' if !in_tag => {
+                match chars.next() {
+                    Some('n') => decoded.push('\n'),
+                    Some('t') => decoded.push('\t'),
+                    Some(c) => decoded.push(c),
+                    None => {}
+                }
+                continue;
+            }
+            _ => {}
+        }
+        decoded.push(c);
+    }
+    decoded
+}
+
+/// Escape text the way Android requires, outside of markup.
+fn encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    let mut in_tag = false;
+
+    for (i, c) in text.char_indices() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if in_tag => {}
+            '\\' => encoded.push_str("\\\\"),
+            '\'' => encoded.push_str("\\'"),
+            '"' => encoded.push_str("\\\""),
+            '\n' => encoded.push_str("\\n"),
+            '\t' => encoded.push_str("\\t"),
+            '@' | '?' if i == 0 => encoded.push('\\'),
+            _ => {}
+        }
+        if in_tag || !matches!(c, '\\' | '\'' | '"' | '\n' | '\t') {
+            encoded.push(c);
+        }
+    }
+    encoded
+}
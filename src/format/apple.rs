@@ -0,0 +1,115 @@
+use std::sync::LazyLock;
+
+use quick_xml::escape::{escape, unescape};
+use regex::Regex;
+
+use crate::Document;
+
+/// Comments, which are kept for translators, and `"key" = "value";` pairs.
+static STRINGS_ENTRY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"(?s)/\*.*?\*/|//[^\n]*",
+        r#"|(?:"(?:[^"\\]|\\.)*"|[A-Za-z0-9_.$-]+)\s*=\s*"(?P<value>(?:[^"\\]|\\.)*)"\s*;"#,
+    ))
+    .expect("strings entry pattern is valid")
+});
+
+/// Comments and `<key>` elements followed by a `<string>` value in a property list.
+static PLIST_ENTRY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)<!--.*?-->|<key>(?P<key>[^<]*)</key>\s*<string>(?P<value>[^<]*)</string>")
+        .expect("plist entry pattern is valid")
+});
+
+/// Variables of a format string, such as `%#@files@`, which are kept as they are.
+static FORMAT_VARIABLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"%#@[A-Za-z0-9_]+@").expect("variable pattern is valid"));
+
+/// The keys of a stringsdict whose values are text: the format and the plural forms.
+const STRINGSDICT_KEYS: &[&str] = &[
+    "NSStringLocalizedFormatKey",
+    "zero",
+    "one",
+    "two",
+    "few",
+    "many",
+    "other",
+];
+
+/// Split an Apple `.strings` file so that only the values are translated. Keys and the
+/// comments left for translators are kept as they are.
+pub fn parse_strings(input: &str) -> Document {
+    let mut document = Document::new();
+    let mut last = 0;
+
+    for entry in STRINGS_ENTRY.captures_iter(input) {
+        let Some(value) = entry.name("value") else {
+            continue;
+        };
+        document.push_verbatim(&input[last..value.start()]);
+        document.push_encoded(&decode_strings(value.as_str()), Box::new(encode_strings));
+        last = value.end();
+    }
+    document.push_verbatim(&input[last..]);
+
+    document
+}
+
+/// Split an Apple `.stringsdict` file so that only the format strings and plural forms are
+/// translated. The plural rules and the rest of the property list are kept as they are.
+pub fn parse_stringsdict(input: &str) -> Document {
+    let mut document = Document::new();
+    let mut last = 0;
+
+    for entry in PLIST_ENTRY.captures_iter(input) {
+        let (Some(key), Some(value)) = (entry.name("key"), entry.name("value")) else {
+            continue;
+        };
+        if !STRINGSDICT_KEYS.contains(&key.as_str()) {
+            continue;
+        }
+        let Ok(text) = unescape(value.as_str()) else {
+            continue;
+        };
+
+        document.push_verbatim(&input[last..value.start()]);
+        document.push_protected_encoded(
+            &text,
+            &FORMAT_VARIABLE,
+            Box::new(|text| escape(text).into_owned()),
+        );
+        last = value.end();
+    }
+    document.push_verbatim(&input[last..]);
+
+    document
+}
+
+/// Undo the escaping of a `.strings` value.
+fn decode_strings(value: &str) -> String {
+    let mut decoded = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            decoded.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => decoded.push('\n'),
+            Some('t') => decoded.push('\t'),
+            Some('r') => decoded.push('\r'),
+            Some(c) => decoded.push(c),
+            None => {}
+        }
+    }
+    decoded
+}
+
+/// Escape text for use as a `.strings` value, without the surrounding quotes.
+fn encode_strings(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t")
+        .replace('\r', "\\r")
+}
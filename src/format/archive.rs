@@ -0,0 +1,63 @@
+use std::{
+    fs::File,
+    io::{Read, Write},
+    path::Path,
+};
+
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::{
+    translate_document, write_atomic_with, Document, Result, TranslationRequest, Translator,
+};
+
+/// Translate the text parts of a ZIP-based document, such as a DOCX or an EPUB, into
+/// `output`. Parts for which `is_part` is false, like images and styles, are copied as-is.
+pub(crate) async fn translate_archive(
+    input: &Path,
+    output: &Path,
+    is_part: impl Fn(&str) -> bool,
+    parse: impl Fn(&str) -> Result<Document>,
+    translator: &dyn Translator,
+    request: &TranslationRequest,
+    jobs: usize,
+) -> Result<()> {
+    let mut archive = ZipArchive::new(File::open(input)?)?;
+
+    let mut parts = Vec::new();
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index)?;
+        let name = entry.name()?.into_owned();
+        if is_part(&name) {
+            let mut xml = String::new();
+            entry.read_to_string(&mut xml)?;
+            parts.push((index, name, xml));
+        }
+    }
+
+    let mut translated = Vec::with_capacity(parts.len());
+    for (index, name, xml) in parts {
+        let document = parse(&xml)?;
+        translated.push((
+            index,
+            name,
+            translate_document(&document, translator, request, jobs).await?,
+        ));
+    }
+
+    write_atomic_with(output, |file| {
+        let mut writer = ZipWriter::new(file);
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        let mut translated = translated.into_iter().peekable();
+        for index in 0..archive.len() {
+            match translated.next_if(|(part, _, _)| *part == index) {
+                Some((_, name, xml)) => {
+                    writer.start_file(name, options)?;
+                    writer.write_all(xml.as_bytes())?;
+                }
+                None => writer.raw_copy_file(archive.by_index(index)?)?,
+            }
+        }
+        writer.finish()?;
+        Ok(())
+    })
+}
@@ -0,0 +1,130 @@
+use crate::{Document, Error, Result};
+
+/// A field of a CSV record, as written in the file.
+struct Field<'a> {
+    raw: &'a str,
+    quoted: bool,
+}
+
+/// Split a CSV or TSV file so that only one column is translated. The header line, the
+/// other columns and the quoting of every field are kept as they are.
+///
+/// `column` is a header name or a one-based index. The delimiter is a tab when the header
+/// has more tabs than commas, and a comma otherwise.
+pub fn parse_csv(input: &str, column: &str) -> Result<Document> {
+    let header_end = input.find('\n').unwrap_or(input.len());
+    let header = &input[..header_end];
+    let delimiter = if header.matches('\t').count() > header.matches(',').count() {
+        '\t'
+    } else {
+        ','
+    };
+
+    let mut records = records(input, delimiter);
+    let header = records
+        .next()
+        .ok_or_else(|| Error::Custom("The CSV file is empty".to_string()))?;
+    let index = match column.parse::<usize>() {
+        Ok(0) => return Err(Error::Custom("Column indexes start at 1".to_string())),
+        Ok(index) => index - 1,
+        Err(_) => header
+            .0
+            .iter()
+            .position(|field| unquote(field) == column)
+            .ok_or_else(|| {
+                Error::Custom(format!("No column named {} in the CSV header", column))
+            })?,
+    };
+
+    let mut document = Document::new();
+    document.push_verbatim(&input[..header.1]);
+    let mut last = header.1;
+
+    for (fields, _) in records {
+        let Some(field) = fields.get(index) else {
+            continue;
+        };
+        let start = field.raw.as_ptr() as usize - input.as_ptr() as usize;
+        document.push_verbatim(&input[last..start]);
+        let quoted = field.quoted;
+        document.push_encoded(
+            &unquote(field),
+            Box::new(move |text| quote(text, delimiter, quoted)),
+        );
+        last = start + field.raw.len();
+    }
+    document.push_verbatim(&input[last..]);
+
+    Ok(document)
+}
+
+/// Iterate over the records of a CSV file, with the position right after each one.
+fn records(input: &str, delimiter: char) -> impl Iterator<Item = (Vec<Field<'_>>, usize)> {
+    let mut position = 0;
+
+    std::iter::from_fn(move || {
+        if position >= input.len() {
+            return None;
+        }
+
+        let mut fields = Vec::new();
+        let mut start = position;
+        let mut in_quotes = false;
+        let mut chars = input[position..].char_indices().peekable();
+
+        loop {
+            let Some((offset, c)) = chars.next() else {
+                fields.push(field(&input[start..]));
+                position = input.len();
+                break;
+            };
+            let i = position + offset;
+            match c {
+                '"' if in_quotes && chars.peek().is_some_and(|(_, next)| *next == '"') => {
+                    chars.next();
+                }
+                '"' => in_quotes = !in_quotes,
+                _ if in_quotes => {}
+                '\n' => {
+                    fields.push(field(input[start..i].trim_end_matches('\r')));
+                    position = i + 1;
+                    break;
+                }
+                _ if c == delimiter => {
+                    fields.push(field(&input[start..i]));
+                    start = i + c.len_utf8();
+                }
+                _ => {}
+            }
+        }
+
+        Some((fields, position))
+    })
+}
+
+fn field(raw: &str) -> Field<'_> {
+    Field {
+        raw,
+        quoted: raw.starts_with('"'),
+    }
+}
+
+fn unquote(field: &Field) -> String {
+    match field
+        .raw
+        .strip_prefix('"')
+        .and_then(|raw| raw.strip_suffix('"'))
+    {
+        Some(inner) if field.quoted => inner.replace("\"\"", "\""),
+        _ => field.raw.to_string(),
+    }
+}
+
+/// Write a field, quoting it when it was quoted or when the translation requires it.
+fn quote(text: &str, delimiter: char, quoted: bool) -> String {
+    if quoted || text.contains([delimiter, '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
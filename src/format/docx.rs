@@ -0,0 +1,114 @@
+use std::{path::Path, sync::LazyLock};
+
+use regex::Regex;
+
+use crate::{translate_archive, Document, Result, TranslationRequest, Translator};
+
+/// The start of the text of a run, `<w:t>` or `<w:t xml:space="preserve">`.
+static TEXT_START: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<w:t(?:\s[^>]*)?>").expect("text pattern is valid"));
+
+/// Everything between the text of two runs, such as formatting, tabs and field codes, and
+/// character references, which are kept as they are.
+static BETWEEN_TEXTS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)</w:t>.*?<w:t(?:\s[^>]*)?>|&(?:#\d+|#x[0-9a-fA-F]+|[A-Za-z]+);")
+        .expect("between pattern is valid")
+});
+
+/// Whether an entry of a DOCX archive holds text: the body, headers, footers and notes.
+fn is_text_part(name: &str) -> bool {
+    name == "word/document.xml"
+        || name == "word/footnotes.xml"
+        || name == "word/endnotes.xml"
+        || (name.starts_with("word/header") || name.starts_with("word/footer"))
+            && name.ends_with(".xml")
+}
+
+/// Split a WordprocessingML part so that each paragraph is translated as a whole, keeping
+/// the runs, styles, tables and images around and within it.
+pub fn parse_docx_xml(xml: &str) -> Document {
+    // Leading and trailing spaces may move between runs, and Word drops them unless preserved.
+    let xml = xml.replace("<w:t>", "<w:t xml:space=\"preserve\">");
+    let mut document = Document::new();
+    let mut last = 0;
+
+    // The text of a paragraph spans from its first run text to its last.
+    let mut paragraph: Option<(usize, usize)> = None;
+    for start in TEXT_START.find_iter(&xml) {
+        let Some(end) = xml[start.end()..]
+            .find("</w:t>")
+            .map(|end| start.end() + end)
+        else {
+            break;
+        };
+        paragraph = match paragraph {
+            Some((first, previous)) if !ends_paragraph(&xml[previous..start.start()]) => {
+                Some((first, end))
+            }
+            Some((first, previous)) => {
+                push_paragraph(&mut document, &xml, &mut last, first, previous);
+                Some((start.end(), end))
+            }
+            None => Some((start.end(), end)),
+        };
+    }
+    if let Some((first, end)) = paragraph {
+        push_paragraph(&mut document, &xml, &mut last, first, end);
+    }
+    document.push_verbatim(&xml[last..]);
+
+    document
+}
+
+fn ends_paragraph(between: &str) -> bool {
+    between.contains("</w:p>") || between.contains("<w:p>") || between.contains("<w:p ")
+}
+
+fn push_paragraph(document: &mut Document, xml: &str, last: &mut usize, start: usize, end: usize) {
+    document.push_verbatim(&xml[*last..start]);
+    document.push_protected_encoded(&xml[start..end], &BETWEEN_TEXTS, Box::new(escape_text));
+    *last = end;
+}
+
+/// Escape the characters the provider may add that are not allowed in XML text.
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            '&' if !in_tag
+                && BETWEEN_TEXTS
+                    .find_at(text, i)
+                    .is_none_or(|m| m.start() != i) =>
+            {
+                escaped.push_str("&amp;");
+                continue;
+            }
+            _ => {}
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Translate a Word document into `output`, keeping its styles, tables and images.
+pub async fn translate_docx(
+    input: &Path,
+    output: &Path,
+    translator: &dyn Translator,
+    request: &TranslationRequest,
+    jobs: usize,
+) -> Result<()> {
+    translate_archive(
+        input,
+        output,
+        is_text_part,
+        |xml| Ok(parse_docx_xml(xml)),
+        translator,
+        request,
+        jobs,
+    )
+    .await
+}
@@ -0,0 +1,48 @@
+use std::{path::Path, sync::LazyLock};
+
+use regex::Regex;
+
+use crate::{parse_html, translate_archive, Document, Result, TranslationRequest, Translator};
+
+static LANGUAGE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(<dc:language\b[^>]*>)[^<]*(</dc:language>)").expect("language pattern is valid")
+});
+
+/// Whether an entry of an EPUB archive is translated: the XHTML content documents, and the
+/// package document, whose language is set to the target language.
+fn is_text_part(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    [".xhtml", ".html", ".htm", ".opf"]
+        .iter()
+        .any(|extension| name.ends_with(extension))
+}
+
+/// Translate an ebook into `output`, keeping its package structure, styles and images.
+pub async fn translate_epub(
+    input: &Path,
+    output: &Path,
+    translator: &dyn Translator,
+    request: &TranslationRequest,
+    jobs: usize,
+) -> Result<()> {
+    let to = request.to.as_str();
+    translate_archive(
+        input,
+        output,
+        is_text_part,
+        |xml| {
+            if xml.contains("<package") {
+                let mut document = Document::new();
+                let replacement = format!("${{1}}{}${{2}}", to);
+                document.push_verbatim(&LANGUAGE.replace_all(xml, replacement.as_str()));
+                Ok(document)
+            } else {
+                Ok(parse_html(xml, to))
+            }
+        },
+        translator,
+        request,
+        jobs,
+    )
+    .await
+}
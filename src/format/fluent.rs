@@ -0,0 +1,78 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::Document;
+
+/// A message or term, such as `hello = Hello` or `-brand = Firefox`.
+static MESSAGE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^-?[A-Za-z][A-Za-z0-9_-]*[ \t]*=[ \t]*").expect("message pattern is valid")
+});
+
+/// An attribute of a message, such as `    .title = Tooltip`.
+static ATTRIBUTE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^[ \t]+\.[A-Za-z][A-Za-z0-9_-]*[ \t]*=[ \t]*").expect("attribute pattern is valid")
+});
+
+/// A variant of a select expression, such as `   *[other] `.
+static VARIANT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^[ \t]*\*?\[[^\]]+\][ \t]*").expect("variant pattern is valid"));
+
+/// Placeables such as `{ $name }` or `{ -brand }`, which are kept as they are.
+static PLACEABLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{(?:[^{}]|\{[^{}]*\})*\}").expect("placeable pattern is valid"));
+
+/// Split a Fluent `.ftl` file so that only the text of messages, terms, attributes and
+/// variants is translated. Identifiers, variable references, selectors and comments are
+/// kept as they are, so the result still parses.
+pub fn parse_fluent(input: &str) -> Document {
+    let mut document = Document::new();
+
+    for line in input.split_inclusive('\n') {
+        let content = line.trim_end_matches(['\n', '\r']);
+        let trimmed = content.trim();
+
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with('}') {
+            document.push_verbatim(line);
+            continue;
+        }
+
+        let indented = content.starts_with([' ', '\t']);
+        let prefix = if indented {
+            ATTRIBUTE
+                .find(content)
+                .or_else(|| VARIANT.find(content))
+                .map_or(content.len() - content.trim_start().len(), |m| m.end())
+        } else if let Some(message) = MESSAGE.find(content) {
+            message.end()
+        } else {
+            document.push_verbatim(line);
+            continue;
+        };
+
+        document.push_verbatim(&content[..prefix]);
+        push_value(&mut document, &content[prefix..]);
+        document.push_verbatim(&line[content.len()..]);
+    }
+
+    document
+}
+
+/// Add the text of a pattern. A select expression opened on the line, as in
+/// `{ $count ->`, is kept as it is.
+fn push_value(document: &mut Document, value: &str) {
+    let mut open = Vec::new();
+    for (i, c) in value.char_indices() {
+        match c {
+            '{' => open.push(i),
+            '}' => {
+                open.pop();
+            }
+            _ => {}
+        }
+    }
+
+    let end = open.first().copied().unwrap_or(value.len());
+    document.push_protected(&value[..end], &PLACEABLE);
+    document.push_verbatim(&value[end..]);
+}
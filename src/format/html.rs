@@ -0,0 +1,182 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::Document;
+
+/// Elements that flow within a paragraph. Any other element ends the text being read, so
+/// each block is translated on its own.
+const INLINE_ELEMENTS: &[&str] = &[
+    "a", "abbr", "b", "bdi", "bdo", "br", "cite", "code", "data", "dfn", "em", "font", "i", "img",
+    "kbd", "label", "mark", "q", "s", "samp", "small", "span", "strong", "sub", "sup", "time", "u",
+    "var", "wbr",
+];
+
+/// Elements whose content is never translated: code, form values and templates. Code within a
+/// paragraph, such as `<code>`, is kept as it is by [`INLINE`].
+const RAW_ELEMENTS: &[&str] = &["script", "style", "pre", "textarea", "template"];
+
+/// The `translate="no"` attribute, which marks an element whose content is kept as it is.
+static NO_TRANSLATE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)\stranslate\s*=\s*(?:"no"|'no'|no\b)"#).expect("translate pattern is valid")
+});
+
+/// Tags, entities and code within a block of text, which are kept as they are.
+static INLINE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"(?is)<code\b[^>]*>.*?</code>|<kbd\b[^>]*>.*?</kbd>",
+        r"|<samp\b[^>]*>.*?</samp>|<var\b[^>]*>.*?</var>",
+        r"|<[^>]+>|&(?:#\d+|#x[0-9a-f]+|[a-z][a-z0-9]*);",
+    ))
+    .expect("inline pattern is valid")
+});
+
+static TAG_NAME: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^</?([A-Za-z][A-Za-z0-9-]*)").expect("tag pattern is valid"));
+
+/// The attributes whose values are translated, plus `lang`, which is set to the target language.
+static ATTRIBUTE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)\s(?:xml:)?(alt|title|lang)\s*=\s*(?:"([^"]*)"|'([^']*)')"#)
+        .expect("attribute pattern is valid")
+});
+
+/// Split an HTML document into its text and `alt`/`title` attributes, which are translated,
+/// and its markup, scripts, styles, code blocks and elements marked `translate="no"`, which
+/// are kept as they are.
+///
+/// The `lang` attribute of the `<html>` element is set to `to`.
+pub fn parse_html(input: &str, to: &str) -> Document {
+    let mut document = Document::new();
+    let mut text = String::new();
+    let mut rest = input;
+
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if rest.starts_with("<!--") {
+            let end = rest.find("-->").map_or(rest.len(), |end| end + 3);
+            flush(&mut document, &mut text);
+            document.push_verbatim(&rest[..end]);
+            rest = &rest[end..];
+            continue;
+        }
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            let end = rest.find('>').map_or(rest.len(), |end| end + 1);
+            flush(&mut document, &mut text);
+            document.push_verbatim(&rest[..end]);
+            rest = &rest[end..];
+            continue;
+        }
+        let Some(name) = TAG_NAME.captures(rest).map(|c| c[1].to_ascii_lowercase()) else {
+            text.push('<');
+            rest = &rest[1..];
+            continue;
+        };
+
+        let end = tag_end(rest);
+        let tag = &rest[..end];
+        rest = &rest[end..];
+        let closing = tag.starts_with("</");
+
+        let inline = INLINE_ELEMENTS.contains(&name.as_str());
+        let untranslated = !inline && NO_TRANSLATE.is_match(tag) && !tag.ends_with("/>");
+        let raw = RAW_ELEMENTS.contains(&name.as_str());
+        if !closing && (raw || untranslated) {
+            // Scripts and styles end at their first closing tag, whatever they contain.
+            let content_end = element_end(rest, &name, !raw);
+            flush(&mut document, &mut text);
+            document.push_verbatim(tag);
+            document.push_verbatim(&rest[..content_end]);
+            rest = &rest[content_end..];
+            continue;
+        }
+
+        if inline && (closing || !ATTRIBUTE.is_match(tag)) {
+            text.push_str(tag);
+        } else {
+            flush(&mut document, &mut text);
+            push_tag(&mut document, tag, &name, to);
+        }
+    }
+    text.push_str(rest);
+    flush(&mut document, &mut text);
+
+    document
+}
+
+/// The end of the tag closing the `name` element whose content starts `html`, counting the
+/// elements of the same name nested in it if `nested`, or the end of `html` when it is not
+/// closed.
+fn element_end(html: &str, name: &str, nested: bool) -> usize {
+    let lower = html.to_ascii_lowercase();
+    let (open, close) = (format!("<{}", name), format!("</{}", name));
+    let is_tag = |i: usize, prefix: &str| {
+        lower[i..].starts_with(prefix)
+            && !lower[i + prefix.len()..]
+                .starts_with(|c: char| c.is_ascii_alphanumeric() || c == '-')
+    };
+    let mut depth = 1;
+    for (i, _) in lower.match_indices('<') {
+        if is_tag(i, &close) {
+            depth -= 1;
+            if depth == 0 {
+                return i + tag_end(&html[i..]);
+            }
+        } else if nested && is_tag(i, &open) {
+            depth += 1;
+        }
+    }
+    html.len()
+}
+
+/// The length of the tag at the start of `html`, skipping `>` within quoted attribute values.
+fn tag_end(html: &str) -> usize {
+    let mut quote = None;
+    for (i, c) in html.char_indices() {
+        match (quote, c) {
+            (Some(q), _) if c == q => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return i + 1,
+            _ => {}
+        }
+    }
+    html.len()
+}
+
+/// Add a tag, translating its `alt` and `title` attributes.
+fn push_tag(document: &mut Document, tag: &str, name: &str, to: &str) {
+    let mut last = 0;
+
+    for captures in ATTRIBUTE.captures_iter(tag) {
+        let attribute = captures[1].to_ascii_lowercase();
+        let (value, quote) = match (captures.get(2), captures.get(3)) {
+            (Some(value), _) => (value, "&quot;"),
+            (_, Some(value)) => (value, "&#39;"),
+            _ => continue,
+        };
+        if attribute == "lang" && name != "html" {
+            continue;
+        }
+
+        document.push_verbatim(&tag[last..value.start()]);
+        if attribute == "lang" {
+            document.push_verbatim(to);
+        } else {
+            let delimiter = if quote == "&quot;" { '"' } else { '\'' };
+            document.push_encoded(
+                value.as_str(),
+                Box::new(move |text| text.replace(delimiter, quote)),
+            );
+        }
+        last = value.end();
+    }
+    document.push_verbatim(&tag[last..]);
+}
+
+fn flush(document: &mut Document, text: &mut String) {
+    if !text.is_empty() {
+        document.push_protected(text, &INLINE);
+        text.clear();
+    }
+}
@@ -0,0 +1,53 @@
+use crate::{Document, Error, Result};
+
+/// Split a JSON locale file into its string values, which are translated, and everything
+/// else (keys, numbers, structure and formatting), which is kept as it is.
+pub fn parse_json(input: &str) -> Result<Document> {
+    serde_json::from_str::<serde_json::Value>(input)
+        .map_err(|e| Error::Custom(format!("Invalid JSON: {}", e)))?;
+
+    let mut document = Document::new();
+    let bytes = input.as_bytes();
+    let mut last = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] != b'"' {
+            i += 1;
+            continue;
+        }
+
+        let end = json_string_end(bytes, i);
+        let is_key = input[end..].trim_start().starts_with(':');
+        if !is_key {
+            let value: String = serde_json::from_str(&input[i..end])
+                .map_err(|e| Error::Custom(format!("Invalid JSON string: {}", e)))?;
+            document.push_verbatim(&input[last..i + 1]);
+            document.push_encoded(&value, Box::new(encode_json_string));
+            last = end - 1;
+        }
+        i = end;
+    }
+    document.push_verbatim(&input[last..]);
+
+    Ok(document)
+}
+
+/// The index right after the closing quote of the string starting at `start`.
+pub(crate) fn json_string_end(bytes: &[u8], start: usize) -> usize {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Escape text for use inside a JSON string, without the surrounding quotes.
+pub(crate) fn encode_json_string(text: &str) -> String {
+    let quoted = serde_json::Value::from(text).to_string();
+    quoted[1..quoted.len() - 1].to_string()
+}
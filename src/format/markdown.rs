@@ -0,0 +1,294 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::{encode_json_string, json_string_end, push_yaml_scalar, Document};
+
+/// Inline spans that must reach the output untouched: code spans, images, link destinations,
+/// reference labels, autolinks, inline HTML and bare URLs.
+static INLINE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"``[^`]+``|`[^`\n]+`",
+        r"|!\[[^\]\n]*\]\([^)\n]*\)",
+        r"|\]\([^)\n]*\)",
+        r"|\]\[[^\]\n]*\]",
+        r"|<(?:https?|ftp|mailto):[^>\s]+>|<[^>\s@]+@[^>\s]+>",
+        r"|<!--.*?-->|</?[A-Za-z][A-Za-z0-9-]*(?:\s[^<>]*)?/?>",
+        r"|https?://[^\s<>()\[\]]+",
+    ))
+    .expect("inline pattern is valid")
+});
+
+static HEADING: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^#{1,6}(?:[ \t]+|\s*$)").expect("heading pattern is valid"));
+
+static LIST_ITEM: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?:[-*+]|\d{1,9}[.)])[ \t]+(?:\[[ xX]\][ \t]+)?")
+        .expect("list item pattern is valid")
+});
+
+static BLOCKQUOTE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:>[ \t]?)+").expect("blockquote pattern is valid"));
+
+static LINK_DEFINITION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\[[^\]]+\]:[ \t]").expect("link definition pattern is valid"));
+
+/// An autolink such as `<https://example.com>` or `<user@example.com>`, which starts a
+/// paragraph rather than an HTML block.
+static AUTOLINK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"^<(?:[A-Za-z][A-Za-z0-9+.-]{1,31}:[^\s<>]*",
+        r"|[A-Za-z0-9.!#$%&'*+/=?^_`{|}~-]+@[A-Za-z0-9](?:[A-Za-z0-9.-]*[A-Za-z0-9])?)>",
+    ))
+    .expect("autolink pattern is valid")
+});
+
+static TABLE_DELIMITER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\|?(?:[ \t]*:?-+:?[ \t]*\|)+(?:[ \t]*:?-+:?[ \t]*)?$")
+        .expect("table delimiter pattern is valid")
+});
+
+/// A top-level field of YAML (`title: ...`) or TOML (`title = ...`) front matter.
+static FRONT_MATTER_FIELD: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^([A-Za-z0-9_-]+)[ \t]*[:=][ \t]*").expect("field pattern is valid")
+});
+
+/// The front matter fields translated by default.
+pub const DEFAULT_FRONT_MATTER_FIELDS: &[&str] = &["title", "description"];
+
+/// Split a Markdown document into its prose, which is translated, and its syntax, code and
+/// URLs, which are kept as they are.
+///
+/// Of the YAML (`---`) or TOML (`+++`) front matter, only the top-level `fields` are
+/// translated, so static site generators still read the rest.
+pub fn parse_markdown(input: &str, fields: &[String]) -> Document {
+    let mut parser = MarkdownParser {
+        fields,
+        ..Default::default()
+    };
+    for (index, line) in input.split_inclusive('\n').enumerate() {
+        parser.line(index, line);
+    }
+    parser.finish()
+}
+
+#[derive(Default)]
+struct MarkdownParser<'a> {
+    document: Document,
+    /// The lines of the paragraph being read.
+    paragraph: String,
+    /// The fence that closes the code block being read.
+    fence: Option<&'a str>,
+    /// The line that closes the front matter being read.
+    front_matter: Option<&'static str>,
+    fields: &'a [String],
+    html: bool,
+    comment: bool,
+    previous_blank: bool,
+    in_list: bool,
+}
+
+impl<'a> MarkdownParser<'a> {
+    fn line(&mut self, index: usize, line: &'a str) {
+        let content = line.trim_end_matches(['\n', '\r']);
+        let trimmed = content.trim_start();
+        let indent = content.len() - trimmed.len();
+
+        if index == 0 && matches!(content, "---" | "+++") {
+            self.front_matter = Some(if content == "+++" { "+++" } else { "---" });
+            return self.verbatim(line);
+        }
+        if let Some(end) = self.front_matter {
+            if content == end || (end == "---" && content == "...") {
+                self.front_matter = None;
+                return self.verbatim(line);
+            }
+            return self.front_matter_line(line, content, end == "+++");
+        }
+        if let Some(fence) = self.fence {
+            if trimmed.starts_with(fence)
+                && trimmed.trim_start_matches(&fence[..1]).trim().is_empty()
+            {
+                self.fence = None;
+            }
+            return self.verbatim(line);
+        }
+        if self.comment {
+            self.comment = !content.contains("-->");
+            return self.verbatim(line);
+        }
+
+        if trimmed.is_empty() {
+            self.html = false;
+            self.previous_blank = true;
+            return self.verbatim(line);
+        }
+        if self.html {
+            return self.verbatim(line);
+        }
+
+        let blank_before = std::mem::replace(&mut self.previous_blank, false);
+        if indent >= 4 && blank_before && !self.in_list && self.paragraph.is_empty() {
+            return self.verbatim(line);
+        }
+
+        if let Some(fence) = fence(trimmed) {
+            self.fence = Some(fence);
+            return self.verbatim(line);
+        }
+        if trimmed.starts_with("<!--") {
+            self.comment = !trimmed.contains("-->");
+            return self.verbatim(line);
+        }
+        if trimmed.starts_with('<')
+            && trimmed[1..].starts_with(|c: char| c.is_ascii_alphabetic() || c == '/' || c == '!')
+            && !AUTOLINK.is_match(trimmed)
+            && self.paragraph.is_empty()
+        {
+            // Raw HTML blocks last until the next blank line.
+            self.html = true;
+            return self.verbatim(line);
+        }
+
+        self.block(line, line.trim_start());
+    }
+
+    /// Handle a line that may start a new block, after any container prefixes.
+    fn block(&mut self, line: &'a str, trimmed: &'a str) {
+        let prefix = &line[..line.len() - trimmed.len()];
+        let text = &line[prefix.len()..];
+
+        if is_thematic_break(trimmed) || LINK_DEFINITION.is_match(trimmed) {
+            self.in_list = false;
+            return self.verbatim(line);
+        }
+        if let Some(quote) = BLOCKQUOTE.find(trimmed) {
+            // Quoted lines are translated one by one, since each carries its own prefix.
+            self.flush();
+            self.verbatim(&line[..prefix.len() + quote.end()]);
+            self.segment(&text[quote.end()..]);
+            return;
+        }
+        if let Some(heading) = HEADING.find(trimmed) {
+            self.in_list = false;
+            self.flush();
+            self.verbatim(&line[..prefix.len() + heading.end()]);
+            self.segment(&text[heading.end()..]);
+            return;
+        }
+        if let Some(item) = LIST_ITEM.find(trimmed) {
+            self.in_list = true;
+            self.flush();
+            self.verbatim(&line[..prefix.len() + item.end()]);
+            self.paragraph.push_str(&text[item.end()..]);
+            return;
+        }
+        if trimmed.starts_with('|') {
+            self.in_list = false;
+            self.flush();
+            if TABLE_DELIMITER.is_match(trimmed.trim_end()) {
+                return self.verbatim(line);
+            }
+            self.verbatim(prefix);
+            return self.table_row(text);
+        }
+
+        if self.paragraph.is_empty() {
+            self.verbatim(prefix);
+            self.paragraph.push_str(text);
+        } else {
+            self.paragraph.push_str(line);
+        }
+    }
+
+    /// Translate the value of a front matter field if it is one of the selected fields.
+    fn front_matter_line(&mut self, line: &str, content: &str, toml: bool) {
+        let field = FRONT_MATTER_FIELD
+            .captures(content)
+            .filter(|captures| self.fields.iter().any(|field| *field == captures[1]));
+        let Some(prefix) = field.and_then(|captures| captures.get(0)) else {
+            return self.verbatim(line);
+        };
+        let value = &content[prefix.end()..];
+
+        self.verbatim(&content[..prefix.end()]);
+        match (toml, value.strip_prefix('"')) {
+            (false, _) => push_yaml_scalar(&mut self.document, value),
+            (true, Some(_)) if !value.starts_with("\"\"\"") => {
+                let end = json_string_end(value.as_bytes(), 0);
+                match serde_json::from_str::<String>(&value[..end]) {
+                    Ok(text) => {
+                        self.document.push_verbatim("\"");
+                        self.document
+                            .push_encoded(&text, Box::new(encode_json_string));
+                        self.document.push_verbatim(&value[end - 1..]);
+                    }
+                    Err(_) => self.document.push_verbatim(value),
+                }
+            }
+            (true, _) => self.document.push_verbatim(value),
+        }
+        self.document.push_verbatim(&line[content.len()..]);
+    }
+
+    /// Translate each cell of a table row on its own, keeping the pipes.
+    fn table_row(&mut self, row: &str) {
+        let mut code = false;
+        let mut escaped = false;
+        let mut start = 0;
+
+        for (i, c) in row.char_indices() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '`' => code = !code,
+                '|' if !code => {
+                    self.segment(&row[start..i]);
+                    self.verbatim("|");
+                    start = i + 1;
+                }
+                _ => {}
+            }
+        }
+        self.segment(&row[start..]);
+    }
+
+    fn segment(&mut self, text: &str) {
+        self.document.push_protected(text, &INLINE);
+    }
+
+    fn verbatim(&mut self, text: &str) {
+        self.flush();
+        self.document.push_verbatim(text);
+    }
+
+    fn flush(&mut self) {
+        if !self.paragraph.is_empty() {
+            let paragraph = std::mem::take(&mut self.paragraph);
+            self.segment(&paragraph);
+        }
+    }
+
+    fn finish(mut self) -> Document {
+        self.flush();
+        self.document
+    }
+}
+
+/// The opening fence of a fenced code block, e.g. "```" or "~~~~".
+fn fence(line: &str) -> Option<&str> {
+    let marker = line.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let length = line.len() - line.trim_start_matches(marker).len();
+    let info = &line[length..];
+    (length >= 3 && !(marker == '`' && info.contains('`'))).then_some(&line[..length])
+}
+
+/// Whether the line is a thematic break such as `---`, `***` or `_ _ _`.
+fn is_thematic_break(line: &str) -> bool {
+    let mut marks = line.chars().filter(|c| !c.is_whitespace());
+    let Some(mark) = marks.next().filter(|c| matches!(c, '-' | '*' | '_')) else {
+        return false;
+    };
+    let count = 1 + marks.clone().count();
+    count >= 3 && marks.all(|c| c == mark)
+}
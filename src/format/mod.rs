@@ -0,0 +1,385 @@
+use std::path::Path;
+
+use clap::ValueEnum;
+use futures_util::future::try_join_all;
+use regex::Regex;
+use tokio::sync::Semaphore;
+
+use crate::{Error, Protected, Result, TranslationRequest, Translator};
+
+mod android;
+mod apple;
+mod archive;
+mod csv;
+mod docx;
+mod epub;
+mod fluent;
+mod html;
+mod json;
+mod markdown;
+mod po;
+mod properties;
+mod subtitle;
+mod xliff;
+mod yaml;
+
+pub use android::*;
+pub use apple::*;
+pub(crate) use archive::*;
+pub use csv::*;
+pub use docx::*;
+pub use epub::*;
+pub use fluent::*;
+pub use html::*;
+pub use json::*;
+pub use markdown::*;
+pub use po::*;
+pub use properties::*;
+pub use subtitle::*;
+pub use xliff::*;
+pub use yaml::*;
+
+/// The kinds of documents trlt can translate while preserving their structure.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DocumentKind {
+    /// Plain text, translated as a whole.
+    #[default]
+    Text,
+    /// Markdown: only prose is translated, code and URLs are kept.
+    Markdown,
+    /// HTML: text and `alt`/`title` attributes are translated, markup, scripts and styles are kept.
+    Html,
+    /// JSON locale files: only string values are translated, keys and formatting are kept.
+    Json,
+    /// YAML locale files, including Rails-style files whose root key is the language code.
+    Yaml,
+    /// gettext `.po` files: only untranslated and fuzzy entries are translated.
+    Po,
+    /// XLIFF 1.2 and 2.0: untranslated `<source>` segments are translated into `<target>`.
+    Xliff,
+    /// SubRip subtitles: only caption text is translated, cue numbers and timestamps are kept.
+    Srt,
+    /// WebVTT subtitles: only caption text is translated, the header and cue metadata are kept.
+    Vtt,
+    /// Android string resources: strings, string arrays and plurals are translated.
+    Android,
+    /// Apple `.strings` files: only values are translated, keys and comments are kept.
+    Strings,
+    /// Apple `.stringsdict` files: format strings and plural forms are translated.
+    Stringsdict,
+    /// Mozilla Fluent files: only text is translated, placeables and selectors are kept.
+    Fluent,
+    /// Java `.properties` files: only values are translated, keys and comments are kept.
+    Properties,
+    /// CSV and TSV files: only the column given by `--column` is translated.
+    Csv,
+    /// Word documents: paragraphs are translated, styles, tables and images are kept.
+    Docx,
+    /// EPUB ebooks: content documents are translated, the package, styles and images are kept.
+    Epub,
+}
+
+/// Options that control how documents are read and written.
+#[derive(Debug, Clone, Default)]
+pub struct DocumentOptions {
+    /// The target language, written into documents that declare their language.
+    pub to: String,
+    /// The source language, if known, which the language documents declare must be to be
+    /// replaced with the target language.
+    pub from: Option<String>,
+    /// Escape non-ASCII characters as `\uXXXX` in `.properties` files.
+    pub escape_unicode: bool,
+    /// The CSV column to translate, as a header name or a one-based index.
+    pub column: Option<String>,
+    /// The Markdown front matter fields to translate.
+    pub front_matter_fields: Vec<String>,
+}
+
+impl DocumentKind {
+    /// Detect the kind of a document from its file extension.
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        if is_android_resource(path) {
+            return DocumentKind::Android;
+        }
+        match extension.as_str() {
+            "md" | "markdown" | "mdx" => DocumentKind::Markdown,
+            "html" | "htm" | "xhtml" => DocumentKind::Html,
+            "json" => DocumentKind::Json,
+            "yml" | "yaml" => DocumentKind::Yaml,
+            "po" | "pot" => DocumentKind::Po,
+            "xlf" | "xliff" => DocumentKind::Xliff,
+            "srt" => DocumentKind::Srt,
+            "vtt" => DocumentKind::Vtt,
+            "strings" => DocumentKind::Strings,
+            "stringsdict" => DocumentKind::Stringsdict,
+            "ftl" => DocumentKind::Fluent,
+            "properties" => DocumentKind::Properties,
+            "csv" | "tsv" => DocumentKind::Csv,
+            "docx" => DocumentKind::Docx,
+            "epub" => DocumentKind::Epub,
+            _ => DocumentKind::Text,
+        }
+    }
+
+    /// Whether documents of this kind are binary archives, which are read from and written to
+    /// files rather than parsed as text.
+    pub fn is_archive(&self) -> bool {
+        matches!(self, DocumentKind::Docx | DocumentKind::Epub)
+    }
+
+    /// Translate an archive document from the file `input` into the file `output`.
+    pub async fn translate_file(
+        &self,
+        input: &Path,
+        output: &Path,
+        translator: &dyn Translator,
+        request: &TranslationRequest,
+        jobs: usize,
+    ) -> Result<()> {
+        match self {
+            DocumentKind::Docx => translate_docx(input, output, translator, request, jobs).await,
+            DocumentKind::Epub => translate_epub(input, output, translator, request, jobs).await,
+            _ => Err(Error::Custom(format!(
+                "{:?} documents are not archives",
+                self
+            ))),
+        }
+    }
+
+    /// Split `input` into the parts to keep and the segments to translate.
+    pub fn parse(&self, input: &str, options: &DocumentOptions) -> Result<Document> {
+        let to = options.to.as_str();
+        match self {
+            DocumentKind::Text => {
+                let mut document = Document::new();
+                document.push_segment(input);
+                Ok(document)
+            }
+            DocumentKind::Markdown => Ok(parse_markdown(input, &options.front_matter_fields)),
+            DocumentKind::Html => Ok(parse_html(input, to)),
+            DocumentKind::Json => parse_json(input),
+            DocumentKind::Yaml => Ok(parse_yaml(input, to, options.from.as_deref())),
+            DocumentKind::Po => parse_po(input, to),
+            DocumentKind::Xliff => Ok(parse_xliff(input, to)),
+            DocumentKind::Srt => Ok(parse_srt(input)),
+            DocumentKind::Vtt => Ok(parse_vtt(input)),
+            DocumentKind::Android => Ok(parse_android(input)),
+            DocumentKind::Strings => Ok(parse_strings(input)),
+            DocumentKind::Stringsdict => Ok(parse_stringsdict(input)),
+            DocumentKind::Fluent => Ok(parse_fluent(input)),
+            DocumentKind::Properties => Ok(parse_properties(input, options.escape_unicode)),
+            DocumentKind::Docx | DocumentKind::Epub => Err(Error::Custom(format!(
+                "{:?} documents can only be translated from and to files",
+                self
+            ))),
+            DocumentKind::Csv => match &options.column {
+                Some(column) => parse_csv(input, column),
+                None => Err(Error::Custom(
+                    "Translating a CSV file needs the column to translate (--column)".to_string(),
+                )),
+            },
+        }
+    }
+}
+
+/// Turns translated text into the syntax of the document, e.g. by escaping it.
+pub type Encoder = Box<dyn Fn(&str) -> String + Send + Sync>;
+
+/// A piece of a document that needs translation.
+pub struct DocumentSegment {
+    leading: String,
+    pub source: Protected,
+    trailing: String,
+    encode: Option<Encoder>,
+}
+
+impl DocumentSegment {
+    /// Whether the segment has anything to translate, as opposed to numbers or symbols only.
+    pub fn needs_translation(&self) -> bool {
+        self.source.text.chars().any(char::is_alphabetic)
+    }
+
+    /// The source text, with the protected spans put back.
+    pub fn original(&self) -> String {
+        self.source
+            .restore(&self.source.text)
+            .unwrap_or_else(|_| self.source.text.clone())
+    }
+
+    fn render(&self, translated: &str) -> String {
+        let text = format!("{}{}{}", self.leading, translated, self.trailing);
+        match &self.encode {
+            Some(encode) => encode(&text),
+            None => text,
+        }
+    }
+}
+
+enum Part {
+    Verbatim(String),
+    Segment(DocumentSegment),
+}
+
+/// A document split into parts that are kept as-is and segments that are translated.
+#[derive(Default)]
+pub struct Document {
+    parts: Vec<Part>,
+    /// The number of preceding segments sent along with each segment as context, so that
+    /// e.g. dialogue split across subtitle cues reads naturally.
+    pub context: usize,
+}
+
+impl Document {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A plain text split into its paragraphs, each translated on its own.
+    pub fn paragraphs(input: &str) -> Self {
+        let mut document = Self::new();
+        for paragraph in input.split_inclusive("\n\n") {
+            document.push_segment(paragraph);
+        }
+        document
+    }
+
+    /// Add text that is kept as-is.
+    pub fn push_verbatim(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        match self.parts.last_mut() {
+            Some(Part::Verbatim(verbatim)) => verbatim.push_str(text),
+            _ => self.parts.push(Part::Verbatim(text.to_string())),
+        }
+    }
+
+    /// Add text to translate. Surrounding whitespace is kept out of the translation.
+    pub fn push_segment(&mut self, text: &str) {
+        self.push(text, None, None);
+    }
+
+    /// Add text to translate, keeping the matches of `pattern` (e.g. inline code) as-is.
+    pub fn push_protected(&mut self, text: &str, pattern: &Regex) {
+        self.push(text, Some(pattern), None);
+    }
+
+    /// Add text to translate, passing the result (surrounding whitespace included) through
+    /// `encode` before it is written.
+    pub fn push_encoded(&mut self, text: &str, encode: Encoder) {
+        self.push(text, None, Some(encode));
+    }
+
+    /// Add text to translate, keeping the matches of `pattern` as-is and passing the result
+    /// through `encode`.
+    pub fn push_protected_encoded(&mut self, text: &str, pattern: &Regex, encode: Encoder) {
+        self.push(text, Some(pattern), Some(encode));
+    }
+
+    fn push(&mut self, text: &str, pattern: Option<&Regex>, encode: Option<Encoder>) {
+        let content = text.trim();
+        let leading = &text[..text.len() - text.trim_start().len()];
+        let trailing = &text[text.trim_end().len()..];
+
+        if encode.is_none() && content.is_empty() {
+            self.push_verbatim(text);
+            return;
+        }
+
+        let source = match pattern {
+            Some(pattern) => crate::protect_matches(content, pattern),
+            None => Protected::plain(content),
+        };
+        self.parts.push(Part::Segment(DocumentSegment {
+            leading: leading.to_string(),
+            source,
+            trailing: trailing.to_string(),
+            encode,
+        }));
+    }
+
+    pub fn segments(&self) -> impl Iterator<Item = &DocumentSegment> {
+        self.parts.iter().filter_map(|part| match part {
+            Part::Segment(segment) => Some(segment),
+            Part::Verbatim(_) => None,
+        })
+    }
+
+    /// Render the document with the given translations, one per segment, in order.
+    pub fn render(&self, translations: &[String]) -> Result<String> {
+        let mut output = String::new();
+        let mut translations = translations.iter();
+
+        for part in &self.parts {
+            match part {
+                Part::Verbatim(text) => output.push_str(text),
+                Part::Segment(segment) => {
+                    let translated = translations.next().map(String::as_str).unwrap_or_default();
+                    output.push_str(&segment.render(&segment.source.restore(translated)?));
+                }
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+/// Translate every segment of `document` into the language of `request`, running at most
+/// `jobs` translations at a time, and render the translated document.
+pub async fn translate_document(
+    document: &Document,
+    translator: &dyn Translator,
+    request: &TranslationRequest,
+    jobs: usize,
+) -> Result<String> {
+    let translations = translate_segments(document, translator, request, jobs).await?;
+    document.render(&translations)
+}
+
+/// Translate every segment of `document` into the language of `request`, running at most
+/// `jobs` translations at a time, giving the translations in the order of the segments.
+pub async fn translate_segments(
+    document: &Document,
+    translator: &dyn Translator,
+    request: &TranslationRequest,
+    jobs: usize,
+) -> Result<Vec<String>> {
+    let semaphore = Semaphore::new(jobs.max(1));
+    let segments = document.segments().collect::<Vec<_>>();
+    try_join_all(segments.iter().enumerate().map(|(i, segment)| {
+        let semaphore = &semaphore;
+        let segments = &segments;
+        async move {
+            if !segment.needs_translation() {
+                return Ok(segment.source.text.clone());
+            }
+
+            let _permit = semaphore
+                .acquire()
+                .await
+                .expect("semaphore is never closed");
+            let preceding = (document.context > 0 && i > 0).then(|| {
+                segments[i.saturating_sub(document.context)..i]
+                    .iter()
+                    .map(|segment| segment.original())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            });
+            let segment_request = TranslationRequest {
+                text: segment.source.text.clone(),
+                preceding: preceding.or_else(|| request.preceding.clone()),
+                ..request.clone()
+            };
+            translator
+                .translate(&segment_request)
+                .await
+                .map(|translation| translation.text)
+        }
+    }))
+    .await
+}
@@ -0,0 +1,158 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::{Document, Error, Result};
+
+static LANGUAGE_HEADER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"^"Language: [^\\"]*"#).expect("language header pattern is valid")
+});
+
+/// A keyword of an entry, such as `msgid` or `msgstr[1]`, with its (possibly multi-line) string.
+struct Field {
+    keyword: String,
+    /// The entry lines the field spans.
+    lines: std::ops::Range<usize>,
+    value: String,
+}
+
+/// Split a gettext `.po` file so that only the `msgstr` of untranslated or fuzzy entries is
+/// translated. Comments, flags, plurals and every other entry are kept as they are.
+///
+/// The `Language` header is set to `to`.
+pub fn parse_po(input: &str, to: &str) -> Result<Document> {
+    let mut document = Document::new();
+    let mut entry = Vec::new();
+
+    for line in input.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            push_entry(&mut document, &entry, to)?;
+            entry.clear();
+            document.push_verbatim(line);
+        } else {
+            entry.push(line);
+        }
+    }
+    push_entry(&mut document, &entry, to)?;
+
+    Ok(document)
+}
+
+fn push_entry(document: &mut Document, lines: &[&str], to: &str) -> Result<()> {
+    let mut fields: Vec<Field> = Vec::new();
+    let mut fuzzy = false;
+
+    for (i, line) in lines.iter().enumerate() {
+        let line = line.trim();
+        if line.starts_with("#~") {
+            // Obsolete entries are kept as they are.
+            lines.iter().for_each(|line| document.push_verbatim(line));
+            return Ok(());
+        } else if let Some(flags) = line.strip_prefix("#,") {
+            fuzzy |= flags.split(',').any(|flag| flag.trim() == "fuzzy");
+        } else if line.starts_with('#') {
+            // Translator comments and references are kept as they are.
+        } else if line.starts_with('"') {
+            let field = fields
+                .last_mut()
+                .ok_or_else(|| Error::Custom(format!("Unexpected string in .po file: {}", line)))?;
+            field.value.push_str(&decode(line)?);
+            field.lines.end = i + 1;
+        } else {
+            let (keyword, value) = line
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| Error::Custom(format!("Invalid line in .po file: {}", line)))?;
+            fields.push(Field {
+                keyword: keyword.to_string(),
+                lines: i..i + 1,
+                value: decode(value.trim())?,
+            });
+        }
+    }
+
+    let value = |keyword: &str| {
+        fields
+            .iter()
+            .find(|field| field.keyword == keyword)
+            .map(|field| field.value.as_str())
+    };
+    let msgid = value("msgid").unwrap_or_default();
+    let plural = value("msgid_plural");
+    let msgstrs = fields
+        .iter()
+        .filter(|field| field.keyword.starts_with("msgstr"));
+    let untranslated = msgstrs.clone().all(|field| field.value.is_empty());
+
+    if msgid.is_empty() {
+        // The header entry.
+        for line in lines {
+            match LANGUAGE_HEADER.find(line) {
+                Some(header) => {
+                    document.push_verbatim("\"Language: ");
+                    document.push_verbatim(to);
+                    document.push_verbatim(&line[header.end()..]);
+                }
+                None => document.push_verbatim(line),
+            }
+        }
+        return Ok(());
+    }
+    if !untranslated && !fuzzy {
+        lines.iter().for_each(|line| document.push_verbatim(line));
+        return Ok(());
+    }
+
+    let mut next = 0;
+    for field in msgstrs {
+        lines[next..field.lines.start]
+            .iter()
+            .for_each(|line| document.push_verbatim(line));
+        let source = match field.keyword.as_str() {
+            "msgstr" | "msgstr[0]" => msgid,
+            _ => plural.unwrap_or(msgid),
+        };
+        document.push_verbatim(&format!("{} \"", field.keyword));
+        document.push_encoded(source, Box::new(encode));
+        document.push_verbatim("\"\n");
+        next = field.lines.end;
+    }
+    lines[next..]
+        .iter()
+        .for_each(|line| document.push_verbatim(line));
+
+    Ok(())
+}
+
+/// Decode a quoted `.po` string.
+fn decode(quoted: &str) -> Result<String> {
+    let inner = quoted
+        .strip_prefix('"')
+        .and_then(|quoted| quoted.strip_suffix('"'))
+        .ok_or_else(|| Error::Custom(format!("Invalid string in .po file: {}", quoted)))?;
+
+    let mut text = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => text.push('\n'),
+            Some('t') => text.push('\t'),
+            Some('r') => text.push('\r'),
+            Some(c) => text.push(c),
+            None => {}
+        }
+    }
+    Ok(text)
+}
+
+/// Escape text for use inside a `.po` string, without the surrounding quotes.
+fn encode(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t")
+        .replace('\r', "\\r")
+}
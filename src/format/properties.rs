@@ -0,0 +1,147 @@
+use crate::Document;
+
+/// Split a Java `.properties` file so that only the values are translated. Keys, comments
+/// and their order are kept as they are.
+///
+/// Values are written in UTF-8, or with every non-ASCII character escaped as `\uXXXX` when
+/// `escape_unicode` is set, for tools that still read properties files as Latin-1.
+pub fn parse_properties(input: &str, escape_unicode: bool) -> Document {
+    let mut document = Document::new();
+    let mut lines = input.split_inclusive('\n');
+
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim_start();
+        if trimmed.trim().is_empty() || trimmed.starts_with(['#', '!']) {
+            document.push_verbatim(line);
+            continue;
+        }
+
+        // A value ending with an odd number of backslashes continues on the next line.
+        let mut raw = line.to_string();
+        let mut logical = line.trim_end_matches(['\n', '\r']).to_string();
+        let mut ending = &line[logical.len()..];
+        while continues(&logical) {
+            logical.pop();
+            let Some(next) = lines.next() else {
+                break;
+            };
+            raw.push_str(next);
+            let next_content = next.trim_end_matches(['\n', '\r']);
+            logical.push_str(next_content.trim_start());
+            ending = &next[next_content.len()..];
+        }
+
+        let value_start = value_start(&logical);
+        let value = decode(&logical[value_start..]);
+        if value.chars().any(char::is_alphabetic) {
+            document.push_verbatim(&logical[..value_start]);
+            let encode = move |text: &str| encode(text, escape_unicode);
+            document.push_encoded(&value, Box::new(encode));
+            document.push_verbatim(ending);
+        } else {
+            // Values with nothing to translate keep their original escapes and line breaks.
+            document.push_verbatim(&raw);
+        }
+    }
+
+    document
+}
+
+fn continues(line: &str) -> bool {
+    (line.len() - line.trim_end_matches('\\').len()) % 2 == 1
+}
+
+/// Where the value starts: after the key, which ends at an unescaped `=`, `:` or whitespace,
+/// and the separator around it.
+fn value_start(line: &str) -> usize {
+    let mut chars = line.char_indices().skip_while(|(_, c)| c.is_whitespace());
+    let mut key_end = line.len();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '=' | ':' => {
+                key_end = i;
+                break;
+            }
+            _ if c.is_whitespace() => {
+                key_end = i;
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    let separator = [' ', '\t', '\x0c'];
+    let rest = line[key_end..].trim_start_matches(separator);
+    let value = rest
+        .strip_prefix(['=', ':'])
+        .unwrap_or(rest)
+        .trim_start_matches(separator);
+    line.len() - value.len()
+}
+
+/// Undo the escaping of a value, including `\uXXXX` sequences.
+fn decode(value: &str) -> String {
+    let mut decoded = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    let mut pending_surrogate = None;
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            decoded.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => decoded.push('\n'),
+            Some('t') => decoded.push('\t'),
+            Some('r') => decoded.push('\r'),
+            Some('f') => decoded.push('\x0c'),
+            Some('u') => {
+                let hex = (0..4).filter_map(|_| chars.next()).collect::<String>();
+                let Ok(unit) = u16::from_str_radix(&hex, 16) else {
+                    decoded.push_str("\\u");
+                    decoded.push_str(&hex);
+                    continue;
+                };
+                let units = match pending_surrogate.take() {
+                    Some(high) => vec![high, unit],
+                    None if (0xD800..0xDC00).contains(&unit) => {
+                        pending_surrogate = Some(unit);
+                        continue;
+                    }
+                    None => vec![unit],
+                };
+                decoded.extend(char::decode_utf16(units).map(|c| c.unwrap_or('\u{FFFD}')));
+            }
+            Some(c) => decoded.push(c),
+            None => {}
+        }
+    }
+    decoded
+}
+
+/// Escape a value, optionally writing non-ASCII characters as `\uXXXX`.
+fn encode(text: &str, escape_unicode: bool) -> String {
+    let mut encoded = String::with_capacity(text.len());
+
+    for (i, c) in text.chars().enumerate() {
+        match c {
+            '\\' => encoded.push_str("\\\\"),
+            '\n' => encoded.push_str("\\n"),
+            '\t' => encoded.push_str("\\t"),
+            '\r' => encoded.push_str("\\r"),
+            ' ' if i == 0 => encoded.push_str("\\ "),
+            _ if escape_unicode && !c.is_ascii() => {
+                let mut units = [0; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    encoded.push_str(&format!("\\u{:04X}", unit));
+                }
+            }
+            _ => encoded.push(c),
+        }
+    }
+    encoded
+}
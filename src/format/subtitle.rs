@@ -0,0 +1,75 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::Document;
+
+/// The number of preceding cues sent along with each cue as context.
+pub const DEFAULT_CUE_CONTEXT: usize = 2;
+
+/// Formatting tags within captions, such as `<i>` or `{\an8}`, which are kept as they are.
+static SRT_INLINE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<[^>]+>|\{\\[^}]*\}").expect("inline pattern is valid"));
+
+/// Tags within WebVTT captions, such as `<v Roger>`, `<c.yellow>` or timestamps, and
+/// character references, which are kept as they are.
+static VTT_INLINE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"<[^>]+>|&(?:#\d+|#x[0-9a-fA-F]+|[A-Za-z]+);").expect("inline pattern is valid")
+});
+
+/// WebVTT blocks that hold metadata rather than captions.
+const VTT_METADATA: &[&str] = &["WEBVTT", "NOTE", "STYLE", "REGION"];
+
+/// Split SubRip subtitles into their caption text, which is translated, and their cue
+/// numbers and timestamps, which are kept as they are.
+pub fn parse_srt(input: &str) -> Document {
+    let mut document = parse_cues(input, &SRT_INLINE);
+    document.context = DEFAULT_CUE_CONTEXT;
+    document
+}
+
+/// Split WebVTT subtitles into their caption text, which is translated, and their header,
+/// notes, styles, regions and cue timings and settings, which are kept as they are.
+pub fn parse_vtt(input: &str) -> Document {
+    let mut document = parse_cues(input, &VTT_INLINE);
+    document.context = DEFAULT_CUE_CONTEXT;
+    document
+}
+
+/// Split subtitles into blocks separated by blank lines, translating the text that follows
+/// the timing line of each cue. Blocks without a timing line are kept as they are.
+fn parse_cues(input: &str, inline: &Regex) -> Document {
+    let mut document = Document::new();
+    let mut block = Vec::new();
+
+    for line in input.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            push_cue(&mut document, &block, inline);
+            block.clear();
+            document.push_verbatim(line);
+        } else {
+            block.push(line);
+        }
+    }
+    push_cue(&mut document, &block, inline);
+
+    document
+}
+
+fn push_cue(document: &mut Document, block: &[&str], inline: &Regex) {
+    let metadata = block.first().is_some_and(|line| {
+        VTT_METADATA.iter().any(|keyword| {
+            line.trim_end() == *keyword || line.starts_with(&format!("{} ", keyword))
+        })
+    });
+    let timing = block.iter().position(|line| line.contains("-->"));
+    let (false, Some(timing)) = (metadata, timing) else {
+        block.iter().for_each(|line| document.push_verbatim(line));
+        return;
+    };
+
+    block[..=timing]
+        .iter()
+        .for_each(|line| document.push_verbatim(line));
+    document.push_protected(&block[timing + 1..].concat(), inline);
+}
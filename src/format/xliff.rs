@@ -0,0 +1,182 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::Document;
+
+/// The elements the parser acts on: the root and `<file>` elements, which carry the target
+/// language, and the units holding a source and a target.
+static ELEMENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)<xliff\b[^>]*>|<file\b[^>]*>|<(?:trans-unit|segment)\b[^>]*>.*?</(?:trans-unit|segment)>")
+        .expect("element pattern is valid")
+});
+
+static SOURCE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)<source\b[^>]*>(.*?)</source>").expect("source pattern is valid")
+});
+
+static TARGET: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)(<target\b[^>]*>)(.*?)</target>|<target\b[^>]*/>")
+        .expect("target pattern is valid")
+});
+
+static STATE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\sstate="([^"]*)""#).expect("state pattern is valid"));
+
+/// Inline elements and entities within a segment, which are kept as they are.
+static INLINE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"<[^>]+>|&(?:#\d+|#x[0-9a-fA-F]+|[A-Za-z][A-Za-z0-9]*);")
+        .expect("inline pattern is valid")
+});
+
+/// States of a target that still needs translating.
+const UNTRANSLATED_STATES: &[&str] = &["new", "needs-translation", "initial"];
+
+/// Split an XLIFF 1.2 or 2.0 file so that each untranslated `<source>` is translated into its
+/// `<target>`, which is marked as translated. The rest of the XML is kept as it is.
+pub fn parse_xliff(input: &str, to: &str) -> Document {
+    let mut document = Document::new();
+    let mut version2 = false;
+    let mut last = 0;
+
+    for element in ELEMENT.find_iter(input) {
+        document.push_verbatim(&input[last..element.start()]);
+        last = element.end();
+        let text = element.as_str();
+
+        if text.starts_with("<xliff") {
+            version2 = text.contains("version=\"2") || text.contains("trgLang");
+            let tag = if version2 {
+                set_attribute(text, "trgLang", to)
+            } else {
+                text.to_string()
+            };
+            document.push_verbatim(&tag);
+        } else if text.starts_with("<file") {
+            let tag = if version2 {
+                text.to_string()
+            } else {
+                set_attribute(text, "target-language", to)
+            };
+            document.push_verbatim(&tag);
+        } else {
+            push_unit(&mut document, text, version2);
+        }
+    }
+    document.push_verbatim(&input[last..]);
+
+    document
+}
+
+/// Add a `<trans-unit>` (1.2) or `<segment>` (2.0), translating its source when its target
+/// is missing, empty or not yet translated.
+fn push_unit(document: &mut Document, unit: &str, version2: bool) {
+    let open_end = unit.find('>').map_or(unit.len(), |end| end + 1);
+    let open = &unit[..open_end];
+    let (Some(source), target) = (SOURCE.captures(unit), TARGET.captures(unit)) else {
+        return document.push_verbatim(unit);
+    };
+
+    let state_holder = if version2 {
+        Some(open)
+    } else {
+        target
+            .as_ref()
+            .and_then(|target| target.get(1))
+            .map(|tag| tag.as_str())
+    };
+    let state = state_holder
+        .and_then(|tag| STATE.captures(tag))
+        .map(|captures| captures[1].to_string());
+    let translated = target
+        .as_ref()
+        .and_then(|target| target.get(2))
+        .is_some_and(|content| !content.as_str().trim().is_empty());
+    let needs_translation =
+        !translated || state.is_some_and(|state| UNTRANSLATED_STATES.contains(&state.as_str()));
+    let source_text = &source[1];
+    if !needs_translation || source_text.trim().is_empty() {
+        return document.push_verbatim(unit);
+    }
+
+    if version2 {
+        document.push_verbatim(&set_attribute(open, "state", "translated"));
+    } else {
+        document.push_verbatim(open);
+    }
+    let target_open = if version2 {
+        "<target>".to_string()
+    } else {
+        set_attribute("<target>", "state", "translated")
+    };
+
+    match target {
+        Some(target) => {
+            let whole = target.get(0).expect("a match has a whole group");
+            document.push_verbatim(&unit[open_end..whole.start()]);
+            let open = match target.get(1) {
+                Some(tag) if !version2 => set_attribute(tag.as_str(), "state", "translated"),
+                Some(tag) => tag.as_str().to_string(),
+                None => target_open,
+            };
+            document.push_verbatim(&open);
+            push_target(document, source_text);
+            document.push_verbatim("</target>");
+            document.push_verbatim(&unit[whole.end()..]);
+        }
+        None => {
+            let source_end = source.get(0).expect("a match has a whole group");
+            let indent = unit[..source_end.start()]
+                .rsplit('\n')
+                .next()
+                .filter(|indent| indent.trim().is_empty())
+                .map(|indent| format!("\n{}", indent))
+                .unwrap_or_default();
+            document.push_verbatim(&unit[open_end..source_end.end()]);
+            document.push_verbatim(&indent);
+            document.push_verbatim(&target_open);
+            push_target(document, source_text);
+            document.push_verbatim("</target>");
+            document.push_verbatim(&unit[source_end.end()..]);
+        }
+    }
+}
+
+fn push_target(document: &mut Document, source: &str) {
+    document.push_protected_encoded(source, &INLINE, Box::new(escape_ampersands));
+}
+
+/// Set an attribute of a start tag, adding it when missing.
+fn set_attribute(tag: &str, name: &str, value: &str) -> String {
+    let pattern = Regex::new(&format!(r#"\s{}="[^"]*""#, regex::escape(name)))
+        .expect("attribute pattern is valid");
+    let attribute = format!(" {}=\"{}\"", name, value);
+
+    if pattern.is_match(tag) {
+        return pattern.replace(tag, attribute.as_str()).into_owned();
+    }
+    let end = if tag.ends_with("/>") {
+        tag.len() - 2
+    } else {
+        tag.len() - 1
+    };
+    format!("{}{}{}", &tag[..end], attribute, &tag[end..])
+}
+
+/// Escape the `&` characters that do not start an entity, which the provider may add.
+fn escape_ampersands(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for (i, c) in text.char_indices() {
+        let entity = || {
+            INLINE
+                .find_at(text, i)
+                .is_some_and(|m| m.start() == i && m.as_str().starts_with('&'))
+        };
+        if c == '&' && !entity() {
+            escaped.push_str("&amp;");
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
@@ -0,0 +1,232 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::{encode_json_string, find_language, is_written_in, Document};
+
+/// A language code such as `en`, `pt-BR` or `zh_Hant`, used as the root key of Rails-style
+/// locale files.
+static LANGUAGE_CODE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^[a-z]{2,3}(?:[-_][A-Za-z0-9]{2,8})*$").expect("language pattern is valid")
+});
+
+/// Plain values that YAML reads as something other than a string.
+const NON_STRINGS: &[&str] = &["true", "false", "yes", "no", "on", "off", "null", "~"];
+
+/// Split a YAML locale file into its string values, which are translated, and everything
+/// else (keys, comments and formatting), which is kept as it is.
+///
+/// In Rails-style files, whose root key is the code of the language they are written in, the
+/// root key is set to `to`. That language is `from`, or the one detected in the values when it
+/// is not known.
+pub fn parse_yaml(input: &str, to: &str, from: Option<&str>) -> Document {
+    let (document, root) = parse_yaml_with(input, None);
+    let Some(root) = root else {
+        return document;
+    };
+    let primary = |language: &str| {
+        let language = language.replace('_', "-");
+        language
+            .split('-')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase()
+    };
+    // e.g. `ar` is a language code, while `app` is not.
+    let is_locale = find_language(&primary(&root))
+        .is_some_and(|(code, _)| code.eq_ignore_ascii_case(&primary(&root)))
+        && match from {
+            Some(from) => primary(from) == primary(&root),
+            None => {
+                let text = document
+                    .segments()
+                    .map(|segment| segment.source.text.as_str())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                is_written_in(&text, &root)
+            }
+        };
+    match is_locale {
+        true => parse_yaml_with(input, Some(to)).0,
+        false => document,
+    }
+}
+
+/// Split a YAML file as [`parse_yaml`] does, setting a root key that looks like a language
+/// code to `to`, if any, and returning that key.
+fn parse_yaml_with(input: &str, to: Option<&str>) -> (Document, Option<String>) {
+    let mut document = Document::new();
+    let mut root = None;
+    let mut root_seen = false;
+    let mut lines = input.split_inclusive('\n').peekable();
+
+    while let Some(line) = lines.next() {
+        let content = line.trim_end_matches(['\n', '\r']);
+        let trimmed = content.trim_start();
+        let indent = content.len() - trimmed.len();
+
+        if trimmed.is_empty() || trimmed.starts_with('#') || matches!(trimmed, "---" | "...") {
+            document.push_verbatim(line);
+            continue;
+        }
+
+        // Sequence markers and the mapping key are kept as they are.
+        let mut prefix = indent;
+        while content[prefix..].starts_with("- ") || &content[prefix..] == "-" {
+            prefix += 1;
+            prefix += content[prefix..].len() - content[prefix..].trim_start().len();
+        }
+        let key = key_end(&content[prefix..]).map(|end| (prefix, prefix + end));
+        if let Some((_, end)) = key {
+            prefix = end + 1;
+            prefix += content[prefix..].len() - content[prefix..].trim_start().len();
+        }
+        let value = &content[prefix..];
+
+        if let (Some((start, end)), 0, false) = (key, indent, root_seen) {
+            root_seen = true;
+            if value.is_empty() && start == 0 && LANGUAGE_CODE.is_match(&content[..end]) {
+                root = Some(content[..end].to_string());
+                if let Some(to) = to {
+                    document.push_verbatim(to);
+                    document.push_verbatim(&line[end..]);
+                    continue;
+                }
+            }
+        }
+
+        if value.starts_with(['|', '>']) {
+            document.push_verbatim(line);
+            push_block_scalar(&mut document, &mut lines, indent);
+            continue;
+        }
+
+        document.push_verbatim(&line[..prefix]);
+        push_yaml_scalar(&mut document, value);
+        document.push_verbatim(&line[content.len()..]);
+    }
+
+    (document, root)
+}
+
+/// The position of the `:` ending the mapping key at the start of `text`, if any.
+fn key_end(text: &str) -> Option<usize> {
+    let end = match text.chars().next()? {
+        quote @ ('"' | '\'') => text[1..].find(quote)? + 2,
+        _ => text
+            .find(": ")
+            .or_else(|| text.strip_suffix(':').map(str::len))?,
+    };
+    text[end..].starts_with(':').then_some(end)
+}
+
+/// Add a scalar value, translating it when it is a string.
+pub(crate) fn push_yaml_scalar(document: &mut Document, value: &str) {
+    if let Some(inner) = value.strip_prefix('"') {
+        let Some(end) = quoted_end(inner, '"') else {
+            return document.push_verbatim(value);
+        };
+        let Ok(text) = serde_json::from_str::<String>(&value[..end + 2]) else {
+            return document.push_verbatim(value);
+        };
+        document.push_verbatim("\"");
+        document.push_encoded(&text, Box::new(encode_json_string));
+        document.push_verbatim(&value[end + 1..]);
+    } else if let Some(inner) = value.strip_prefix('\'') {
+        let Some(end) = quoted_end(inner, '\'') else {
+            return document.push_verbatim(value);
+        };
+        document.push_verbatim("'");
+        document.push_encoded(
+            &inner[..end].replace("''", "'"),
+            Box::new(|text| text.replace('\'', "''")),
+        );
+        document.push_verbatim(&value[end + 1..]);
+    } else if value.is_empty() || value.starts_with(['&', '*', '!', '[', '{', '@', '`', '%']) {
+        document.push_verbatim(value);
+    } else {
+        let end = value.find(" #").unwrap_or(value.len());
+        let text = value[..end].trim_end();
+        if NON_STRINGS.contains(&text.to_ascii_lowercase().as_str()) {
+            return document.push_verbatim(value);
+        }
+        document.push_encoded(text, Box::new(encode_plain));
+        document.push_verbatim(&value[text.len()..]);
+    }
+}
+
+/// The position of the closing quote in the rest of a quoted scalar.
+fn quoted_end(inner: &str, quote: char) -> Option<usize> {
+    let mut chars = inner.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' if quote == '"' => {
+                chars.next();
+            }
+            '\'' if quote == '\'' && chars.peek().is_some_and(|(_, next)| *next == '\'') => {
+                chars.next();
+            }
+            _ if c == quote => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Write a translated plain scalar, quoting it when the translation would not parse as one.
+fn encode_plain(text: &str) -> String {
+    let needs_quotes = text.contains(": ")
+        || text.contains(" #")
+        || text.ends_with(':')
+        || text.starts_with(['-', '?', ':', ',', '[', ']', '{', '}', '#', '&', '*', '!'])
+        || text.starts_with(['|', '>', '\'', '"', '%', '@', '`']);
+    if needs_quotes {
+        format!("\"{}\"", encode_json_string(text))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Add the lines of a `|` or `>` block scalar, which are more indented than its key.
+fn push_block_scalar<'a>(
+    document: &mut Document,
+    lines: &mut std::iter::Peekable<impl Iterator<Item = &'a str>>,
+    key_indent: usize,
+) {
+    let mut block = Vec::new();
+    while let Some(line) = lines.peek() {
+        let trimmed = line.trim_start();
+        if !trimmed.trim().is_empty() && line.len() - trimmed.len() <= key_indent {
+            break;
+        }
+        block.push(*line);
+        lines.next();
+    }
+
+    let indent = block
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or_default();
+    let text = block
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or("\n"))
+        .collect::<String>();
+
+    let padding = " ".repeat(indent);
+    document.push_encoded(
+        &text,
+        Box::new(move |text| {
+            text.split_inclusive('\n')
+                .map(|line| {
+                    if line.trim().is_empty() {
+                        line.to_string()
+                    } else {
+                        format!("{}{}", padding, line)
+                    }
+                })
+                .collect()
+        }),
+    );
+}
@@ -0,0 +1,197 @@
+use std::{fs, path::Path, path::PathBuf};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    write_atomic, Config, Error, OnChunk, Result, Translation, TranslationRequest, Translator,
+};
+
+/// A term and how it must be translated.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GlossaryTerm {
+    pub source: String,
+    pub target: String,
+    /// The source language. Without it, the term applies to any source language.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    /// The target language.
+    pub to: String,
+}
+
+impl GlossaryTerm {
+    /// Whether the term applies to a translation from `from` to `to`.
+    pub fn applies_to(&self, from: Option<&str>, to: &str) -> bool {
+        let same_language = |a: &str, b: &str| a.eq_ignore_ascii_case(b);
+        same_language(&self.to, to)
+            && match (&self.from, from) {
+                (Some(term_from), Some(from)) => same_language(term_from, from),
+                _ => true,
+            }
+    }
+}
+
+/// The user glossary, stored as TOML in the config directory.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Glossary {
+    #[serde(default, rename = "term")]
+    pub terms: Vec<GlossaryTerm>,
+}
+
+impl Glossary {
+    pub fn path() -> PathBuf {
+        dirs::config_dir()
+            .expect("Failed to get config directory")
+            .join("trlt")
+            .join("glossary.toml")
+    }
+
+    /// Load the glossary, or an empty one if it does not exist yet.
+    pub fn load() -> Result<Self> {
+        match fs::read_to_string(Self::path()) {
+            Ok(contents) => Ok(toml::from_str(&contents)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Load the glossary set in `config`, which must exist, or else the user glossary.
+    pub fn load_for(config: &Config) -> Result<Self> {
+        match &config.glossary {
+            Some(path) => {
+                let contents = fs::read_to_string(path).map_err(|e| {
+                    Error::Custom(format!(
+                        "Unable to read the glossary {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?;
+                Ok(toml::from_str(&contents)?)
+            }
+            None => Self::load(),
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_atomic(&path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Add a term, replacing any existing entry for the same source term and language pair.
+    pub fn add(&mut self, term: GlossaryTerm) {
+        self.terms.retain(|existing| {
+            !(existing.source == term.source
+                && existing.from == term.from
+                && existing.to.eq_ignore_ascii_case(&term.to))
+        });
+        self.terms.push(term);
+    }
+
+    /// Remove the entries for `source`, optionally only for one target language.
+    /// Returns how many were removed.
+    pub fn remove(&mut self, source: &str, to: Option<&str>) -> usize {
+        let before = self.terms.len();
+        self.terms.retain(|term| {
+            term.source != source || to.is_some_and(|to| !term.to.eq_ignore_ascii_case(to))
+        });
+        before - self.terms.len()
+    }
+
+    /// Import terms from a CSV file with `source,target` columns, and optionally `from` and
+    /// `to` columns overriding the given languages. Returns how many were imported.
+    pub fn import_csv(
+        &mut self,
+        path: &Path,
+        from: Option<&str>,
+        to: Option<&str>,
+    ) -> Result<usize> {
+        let csv_error = |e: csv::Error| Error::Custom(format!("Invalid CSV: {}", e));
+        let mut reader = csv::Reader::from_path(path).map_err(csv_error)?;
+        let headers = reader.headers().map_err(csv_error)?.clone();
+        let column = |name: &str| headers.iter().position(|header| header.trim() == name);
+        let (Some(source), Some(target)) = (column("source"), column("target")) else {
+            return Err(Error::Custom(
+                "The CSV file must have `source` and `target` columns".to_string(),
+            ));
+        };
+        let (from_column, to_column) = (column("from"), column("to"));
+
+        let mut count = 0;
+        for record in reader.records() {
+            let record = record.map_err(csv_error)?;
+            let field = |index: Option<usize>| {
+                index
+                    .and_then(|index| record.get(index))
+                    .map(str::trim)
+                    .filter(|value| !value.is_empty())
+            };
+            let Some(term_to) = field(to_column).or(to) else {
+                return Err(Error::Custom(
+                    "Missing target language: add a `to` column or pass --to".to_string(),
+                ));
+            };
+            self.add(GlossaryTerm {
+                source: field(Some(source)).unwrap_or_default().to_string(),
+                target: field(Some(target)).unwrap_or_default().to_string(),
+                from: field(from_column).or(from).map(str::to_string),
+                to: term_to.to_string(),
+            });
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    /// The terms for a language pair that appear in `text`.
+    pub fn terms_for(&self, from: Option<&str>, to: &str, text: &str) -> Vec<GlossaryTerm> {
+        let text = text.to_lowercase();
+        self.terms
+            .iter()
+            .filter(|term| term.applies_to(from, to))
+            .filter(|term| text.contains(&term.source.to_lowercase()))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Translator that adds the glossary terms found in each request to its prompt.
+pub struct GlossaryTranslator {
+    inner: Box<dyn Translator>,
+    glossary: Glossary,
+}
+
+impl GlossaryTranslator {
+    pub fn new(inner: Box<dyn Translator>, glossary: Glossary) -> Self {
+        Self { inner, glossary }
+    }
+
+    fn with_terms(&self, request: &TranslationRequest) -> TranslationRequest {
+        TranslationRequest {
+            glossary: self
+                .glossary
+                .terms_for(request.from.as_deref(), &request.to, &request.text),
+            ..request.clone()
+        }
+    }
+}
+
+#[async_trait]
+impl Translator for GlossaryTranslator {
+    async fn translate(&self, request: &TranslationRequest) -> Result<Translation> {
+        self.inner.translate(&self.with_terms(request)).await
+    }
+
+    async fn translate_stream(
+        &self,
+        request: &TranslationRequest,
+        on_chunk: OnChunk<'_>,
+    ) -> Result<Translation> {
+        self.inner
+            .translate_stream(&self.with_terms(request), on_chunk)
+            .await
+    }
+}
@@ -0,0 +1,140 @@
+use std::{net::SocketAddr, pin::Pin, sync::Arc};
+
+use clap::ValueEnum;
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::UnboundedReceiverStream, Stream};
+use tonic::{Request, Response, Status};
+
+use crate::{
+    DetectBody, Error, Result, ServerState, TranslateBody, TranslationRequest, Translator,
+};
+
+mod proto {
+    tonic::include_proto!("trlt.v1");
+}
+
+use proto::{
+    translation_service_server::{TranslationService, TranslationServiceServer},
+    DetectRequest, DetectResponse, TranslateChunk, TranslateRequest, TranslateResponse,
+};
+
+/// The address the gRPC service listens on when it is enabled without one.
+pub const DEFAULT_GRPC_ADDRESS: &str = "127.0.0.1:50051";
+
+impl From<Error> for Status {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::Custom(_) => Status::invalid_argument(error.to_string()),
+            Error::MissingApiKey(_) => Status::failed_precondition(error.to_string()),
+            _ => Status::unavailable(error.to_string()),
+        }
+    }
+}
+
+struct GrpcService {
+    state: Arc<ServerState>,
+}
+
+/// Parse an enum value sent as a string, e.g. a provider or a tone.
+fn parse_value<T: ValueEnum>(value: Option<String>, field: &str) -> Result<Option<T>> {
+    value
+        .map(|value| {
+            T::from_str(&value, true)
+                .map_err(|_| Error::Custom(format!("Invalid {}: `{}`", field, value)))
+        })
+        .transpose()
+}
+
+impl GrpcService {
+    fn prepare(
+        &self,
+        request: TranslateRequest,
+    ) -> Result<(Arc<dyn Translator>, TranslationRequest)> {
+        self.state.prepare(TranslateBody {
+            provider: parse_value(request.provider, "provider")?,
+            tone: parse_value(request.tone, "tone")?,
+            text: request.text,
+            from: request.from,
+            to: request.to,
+            context: request.context,
+        })
+    }
+}
+
+type ChunkStream = Pin<Box<dyn Stream<Item = std::result::Result<TranslateChunk, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl TranslationService for GrpcService {
+    async fn translate(
+        &self,
+        request: Request<TranslateRequest>,
+    ) -> std::result::Result<Response<TranslateResponse>, Status> {
+        let (translator, request) = self.prepare(request.into_inner())?;
+        let translation = translator.translate(&request).await?;
+        Ok(Response::new(TranslateResponse {
+            text: translation.text,
+            detected_source: translation.detected_source,
+            provider: translation
+                .provider
+                .and_then(|provider| Some(provider.to_possible_value()?.get_name().to_string())),
+        }))
+    }
+
+    type TranslateStreamStream = ChunkStream;
+
+    async fn translate_stream(
+        &self,
+        request: Request<TranslateRequest>,
+    ) -> std::result::Result<Response<Self::TranslateStreamStream>, Status> {
+        let (translator, request) = self.prepare(request.into_inner())?;
+        let (sender, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            let chunks = sender.clone();
+            let mut on_chunk = move |chunk: &str| {
+                chunks
+                    .send(Ok(TranslateChunk {
+                        text: chunk.to_string(),
+                    }))
+                    .ok();
+            };
+            if let Err(error) = translator.translate_stream(&request, &mut on_chunk).await {
+                sender.send(Err(error.into())).ok();
+            }
+        });
+        Ok(Response::new(Box::pin(UnboundedReceiverStream::new(
+            receiver,
+        ))))
+    }
+
+    async fn detect(
+        &self,
+        request: Request<DetectRequest>,
+    ) -> std::result::Result<Response<DetectResponse>, Status> {
+        let request = request.into_inner();
+        let detection = self
+            .state
+            .detect(DetectBody {
+                provider: parse_value(request.provider, "provider")?,
+                text: request.text,
+                offline: request.offline,
+            })
+            .await?;
+        Ok(Response::new(DetectResponse {
+            language: detection.language,
+            confidence: detection.confidence,
+        }))
+    }
+}
+
+/// Serve the `trlt.v1.TranslationService` gRPC service on `address`, sharing the translators
+/// of the HTTP server, until the process is interrupted.
+pub(crate) async fn serve_grpc(address: SocketAddr, state: Arc<ServerState>) -> Result<()> {
+    eprintln!("Serving gRPC on {}", address);
+    tonic::transport::Server::builder()
+        .add_service(TranslationServiceServer::new(GrpcService { state }))
+        .serve_with_shutdown(address, async {
+            tokio::signal::ctrl_c().await.ok();
+        })
+        .await
+        .map_err(|e| Error::Custom(format!("gRPC server failed: {}", e)))
+}
@@ -0,0 +1,283 @@
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde_json::json;
+
+use crate::{Config, Error, ProviderKind, Result, Translation, TranslationRequest};
+
+/// A translation recorded in the history.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    pub id: i64,
+    pub created_at: DateTime<Utc>,
+    /// The file the source was read from, if any.
+    pub name: Option<String>,
+    pub from: Option<String>,
+    pub to: String,
+    pub provider: String,
+    pub model: Option<String>,
+    pub source: String,
+    pub result: String,
+}
+
+impl HistoryEntry {
+    /// The source and target languages, e.g. `auto -> pt`.
+    pub fn pair(&self) -> String {
+        format!("{} -> {}", self.from.as_deref().unwrap_or("auto"), self.to)
+    }
+
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let created_at: String = row.get(1)?;
+        Ok(Self {
+            id: row.get(0)?,
+            created_at: DateTime::parse_from_rfc3339(&created_at)
+                .map(|date| date.with_timezone(&Utc))
+                .unwrap_or_default(),
+            name: row.get(2)?,
+            from: row.get(3)?,
+            to: row.get(4)?,
+            provider: row.get(5)?,
+            model: row.get(6)?,
+            source: row.get(7)?,
+            result: row.get(8)?,
+        })
+    }
+}
+
+const COLUMNS: &str =
+    "id, created_at, name, source_lang, target_lang, provider, model, source, result";
+
+/// How [`export_history`] writes the entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum HistoryFormat {
+    /// A JSON array of objects.
+    Json,
+    /// A CSV file with a header row.
+    Csv,
+}
+
+/// Write `entries` in `format`, naming their results `target` as the translation memory does.
+pub fn export_history(
+    entries: &[HistoryEntry],
+    format: HistoryFormat,
+    writer: impl Write,
+) -> Result<()> {
+    let export_error =
+        |e: &dyn std::fmt::Display| Error::Custom(format!("Unable to export the history: {}", e));
+    match format {
+        HistoryFormat::Json => {
+            let entries = entries
+                .iter()
+                .map(|entry| {
+                    json!({
+                        "id": entry.id,
+                        "date": entry.created_at.to_rfc3339(),
+                        "input": entry.name,
+                        "from": entry.from,
+                        "to": entry.to,
+                        "provider": entry.provider,
+                        "model": entry.model,
+                        "source": entry.source,
+                        "target": entry.result,
+                    })
+                })
+                .collect::<Vec<_>>();
+            let mut writer = writer;
+            serde_json::to_writer_pretty(&mut writer, &entries).map_err(|e| export_error(&e))?;
+            writeln!(writer)?;
+        }
+        HistoryFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(writer);
+            writer
+                .write_record([
+                    "id", "date", "input", "from", "to", "provider", "model", "source", "target",
+                ])
+                .map_err(|e| export_error(&e))?;
+            for entry in entries {
+                writer
+                    .write_record([
+                        entry.id.to_string().as_str(),
+                        &entry.created_at.to_rfc3339(),
+                        entry.name.as_deref().unwrap_or_default(),
+                        entry.from.as_deref().unwrap_or_default(),
+                        &entry.to,
+                        &entry.provider,
+                        entry.model.as_deref().unwrap_or_default(),
+                        &entry.source,
+                        &entry.result,
+                    ])
+                    .map_err(|e| export_error(&e))?;
+            }
+            writer.flush()?;
+        }
+    }
+    Ok(())
+}
+
+/// Turn the words of a search into an FTS5 query matching entries that have all of them,
+/// so that punctuation in the search is not read as query syntax.
+fn fts_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A persistent record of the translations made, backed by SQLite, so that a translation
+/// that was printed but not saved can be found again.
+pub struct TranslationHistory {
+    conn: Mutex<Connection>,
+}
+
+impl TranslationHistory {
+    pub fn new(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS history (
+                id INTEGER PRIMARY KEY,
+                created_at TEXT NOT NULL,
+                name TEXT,
+                source_lang TEXT,
+                target_lang TEXT NOT NULL,
+                provider TEXT NOT NULL,
+                model TEXT,
+                source TEXT NOT NULL,
+                result TEXT NOT NULL
+            );",
+        )?;
+        let indexed: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE name = 'history_fts')",
+            [],
+            |row| row.get(0),
+        )?;
+        if !indexed {
+            // The full-text index of the sources and results, kept up to date by triggers.
+            conn.execute_batch(
+                "BEGIN;
+                 CREATE VIRTUAL TABLE history_fts USING fts5(
+                     source, result, content = 'history', content_rowid = 'id'
+                 );
+                 CREATE TRIGGER history_insert AFTER INSERT ON history BEGIN
+                     INSERT INTO history_fts (rowid, source, result)
+                     VALUES (new.id, new.source, new.result);
+                 END;
+                 CREATE TRIGGER history_delete AFTER DELETE ON history BEGIN
+                     INSERT INTO history_fts (history_fts, rowid, source, result)
+                     VALUES ('delete', old.id, old.source, old.result);
+                 END;
+                 INSERT INTO history_fts (history_fts) VALUES ('rebuild');
+                 COMMIT;",
+            )?;
+        }
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// The history in the user data directory, e.g. `$HOME/.local/share/trlt`.
+    pub fn open() -> Result<Self> {
+        Self::new(&Self::default_path())
+    }
+
+    pub fn default_path() -> PathBuf {
+        dirs::data_dir()
+            .expect("Failed to get data directory")
+            .join("trlt")
+            .join("history.db")
+    }
+
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn.lock().expect("history lock poisoned")
+    }
+
+    /// Record the translation of `request`, made with `provider` unless the translation
+    /// tells otherwise, returning the id of the entry.
+    pub fn record(
+        &self,
+        name: Option<&str>,
+        request: &TranslationRequest,
+        translation: &Translation,
+        provider: ProviderKind,
+        config: &Config,
+    ) -> Result<i64> {
+        let model = translation.model(provider, config);
+        let provider = translation.provider.unwrap_or(provider);
+        let conn = self.conn();
+        conn.execute(
+            "INSERT INTO history (created_at, name, source_lang, target_lang, provider, model,
+                                  source, result)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                Utc::now().to_rfc3339(),
+                name,
+                request.from,
+                request.to,
+                provider.to_string(),
+                model,
+                request.text,
+                translation.text
+            ],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// The latest `limit` entries, the most recent first.
+    pub fn list(&self, limit: usize) -> Result<Vec<HistoryEntry>> {
+        let conn = self.conn();
+        let mut statement = conn.prepare(&format!(
+            "SELECT {COLUMNS} FROM history ORDER BY id DESC LIMIT ?1"
+        ))?;
+        let entries = statement
+            .query_map(params![limit as i64], HistoryEntry::from_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(entries)
+    }
+
+    /// The latest `limit` entries whose source or result has every word of `query`, the
+    /// most recent first.
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<HistoryEntry>> {
+        let query = fts_query(query);
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+        let conn = self.conn();
+        let mut statement = conn.prepare(&format!(
+            "SELECT {COLUMNS} FROM history
+             WHERE id IN (SELECT rowid FROM history_fts WHERE history_fts MATCH ?1)
+             ORDER BY id DESC LIMIT ?2"
+        ))?;
+        let entries = statement
+            .query_map(params![query, limit as i64], HistoryEntry::from_row)?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(entries)
+    }
+
+    pub fn get(&self, id: i64) -> Result<Option<HistoryEntry>> {
+        let entry = self
+            .conn()
+            .query_row(
+                &format!("SELECT {COLUMNS} FROM history WHERE id = ?1"),
+                params![id],
+                HistoryEntry::from_row,
+            )
+            .optional()?;
+        Ok(entry)
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        self.conn().execute("DELETE FROM history", [])?;
+        Ok(())
+    }
+}
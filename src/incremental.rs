@@ -0,0 +1,122 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{write_atomic, Error, Result, Task, Translation, TranslationRequest, Translator};
+
+/// The file next to `output` that records the segments translated into it, e.g.
+/// `.README.pt.md.trlt.json` for `README.pt.md`.
+pub fn incremental_state_path(output: &Path) -> PathBuf {
+    let name = output.file_name().unwrap_or_default().to_string_lossy();
+    output.with_file_name(format!(".{}.trlt.json", name))
+}
+
+/// What an incremental state file holds.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IncrementalState {
+    /// A hash of the settings the segments were translated with. Segments translated with
+    /// other settings are not reused.
+    settings: String,
+    /// The translation of each segment, by the fingerprint of its request.
+    segments: BTreeMap<String, String>,
+}
+
+fn hash(value: &str) -> String {
+    hex::encode(Sha256::digest(value))
+}
+
+/// Translator that reuses the translations of the segments that did not change since the
+/// previous run, as recorded in a state file, and only sends the other segments on.
+pub struct IncrementalTranslator<'a> {
+    inner: &'a dyn Translator,
+    path: PathBuf,
+    settings: String,
+    previous: HashMap<String, String>,
+    current: Mutex<BTreeMap<String, String>>,
+    reused: AtomicUsize,
+}
+
+impl<'a> IncrementalTranslator<'a> {
+    /// Load the state file at `path`, if any. `settings` describes everything besides the
+    /// requests themselves that affects the translations, such as the provider and model.
+    pub fn open(inner: &'a dyn Translator, path: PathBuf, settings: &str) -> Result<Self> {
+        let settings = hash(settings);
+        let previous = match fs::read_to_string(&path) {
+            Ok(contents) => {
+                let state: IncrementalState = serde_json::from_str(&contents).map_err(|e| {
+                    Error::Custom(format!("Invalid state file {}: {}", path.display(), e))
+                })?;
+                if state.settings == settings {
+                    state.segments.into_iter().collect()
+                } else {
+                    HashMap::new()
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            inner,
+            path,
+            settings,
+            previous,
+            current: Mutex::new(BTreeMap::new()),
+            reused: AtomicUsize::new(0),
+        })
+    }
+
+    /// Write the segments translated in this run to the state file, dropping the ones that
+    /// are no longer in the document.
+    pub fn save(&self) -> Result<()> {
+        let state = IncrementalState {
+            settings: self.settings.clone(),
+            segments: self.current.lock().expect("state lock poisoned").clone(),
+        };
+        let contents = serde_json::to_string_pretty(&state)
+            .map_err(|e| Error::Custom(format!("Unable to write the state file: {}", e)))?;
+        write_atomic(&self.path, contents + "\n")?;
+        Ok(())
+    }
+
+    /// The number of segments reused from the previous run, and the number translated in all.
+    pub fn stats(&self) -> (usize, usize) {
+        let total = self.current.lock().expect("state lock poisoned").len();
+        (self.reused.load(Ordering::Relaxed), total)
+    }
+}
+
+#[async_trait]
+impl Translator for IncrementalTranslator<'_> {
+    async fn translate(&self, request: &TranslationRequest) -> Result<Translation> {
+        if request.task != Task::Translate {
+            return self.inner.translate(request).await;
+        }
+
+        let key = request.fingerprint();
+        let translation = match self.previous.get(&key) {
+            Some(text) => {
+                self.reused.fetch_add(1, Ordering::Relaxed);
+                Translation {
+                    text: text.clone(),
+                    ..Default::default()
+                }
+            }
+            None => self.inner.translate(request).await?,
+        };
+        self.current
+            .lock()
+            .expect("state lock poisoned")
+            .insert(key, translation.text.clone());
+        Ok(translation)
+    }
+}
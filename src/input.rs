@@ -0,0 +1,196 @@
+use std::{
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use clipboard::{ClipboardContext, ClipboardProvider};
+
+use crate::{DocumentKind, Error, Result};
+
+/// The command-line argument that reads the input from the clipboard.
+pub const CLIPBOARD_INPUT: &str = "clip";
+
+/// Where the text to translate comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Input {
+    Stdin,
+    Clipboard,
+    File(PathBuf),
+    Text(String),
+}
+
+impl Input {
+    /// Resolve a command-line argument: `-` is stdin, an existing file is read, `clip` is the
+    /// clipboard, anything else is translated as-is.
+    pub fn parse(arg: &str) -> Self {
+        if arg == "-" {
+            Input::Stdin
+        } else if Path::new(arg).is_file() {
+            Input::File(PathBuf::from(arg))
+        } else if arg == CLIPBOARD_INPUT {
+            Input::Clipboard
+        } else {
+            Input::Text(arg.to_string())
+        }
+    }
+
+    pub fn read(&self) -> Result<String> {
+        match self {
+            Input::Stdin => {
+                let mut buffer = String::new();
+                io::stdin().read_to_string(&mut buffer)?;
+                Ok(buffer)
+            }
+            Input::Clipboard => ClipboardContext::new()
+                .and_then(|mut context| context.get_contents())
+                .map_err(|e| Error::Custom(format!("Failed to read the clipboard: {}", e))),
+            Input::File(path) if is_pdf(path) => pdf_extract::extract_text(path).map_err(|e| {
+                Error::Custom(format!(
+                    "Failed to extract the text of {}: {}",
+                    path.display(),
+                    e
+                ))
+            }),
+            Input::File(path) => Ok(fs::read_to_string(path)?),
+            Input::Text(text) => Ok(text.clone()),
+        }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Input::File(path) => Some(path),
+            _ => None,
+        }
+    }
+}
+
+/// Expand the command-line inputs, resolving glob patterns such as `docs/*.md`.
+///
+/// Patterns are expanded here so they also work when the shell does not expand them
+/// (e.g. quoted patterns or Windows shells). Text following a file is refused, as it is
+/// most likely an output path that does not exist yet.
+pub fn expand_inputs(args: &[String]) -> Result<Vec<Input>> {
+    let mut inputs = Vec::new();
+
+    for arg in args {
+        let is_pattern = arg.contains(['*', '?', '[']) && !Path::new(arg).exists();
+        if !is_pattern {
+            let input = Input::parse(arg);
+            // `trlt translate in.txt out.txt` used to name the output.
+            if let (Some(Input::File(_)), Input::Text(_)) = (inputs.first(), &input) {
+                return Err(Error::InvalidInput(format!(
+                    "{} is not a file; to write the translation to it, use `--out {}`",
+                    arg, arg
+                )));
+            }
+            inputs.push(input);
+            continue;
+        }
+
+        let paths = glob::glob(arg)
+            .map_err(|e| Error::Custom(format!("Invalid pattern {}: {}", arg, e)))?
+            .filter_map(|entry| entry.ok())
+            .filter(|path| path.is_file())
+            .collect::<Vec<_>>();
+
+        if paths.is_empty() {
+            return Err(Error::Custom(format!("No files match {}", arg)));
+        }
+        inputs.extend(paths.into_iter().map(Input::File));
+    }
+
+    Ok(inputs)
+}
+
+/// The default template used to name translated files.
+pub const DEFAULT_NAME_TEMPLATE: &str = "{stem}.{lang}.{ext}";
+
+/// Render an output file name from `template`.
+///
+/// The template can use `{name}` (the whole file name), `{stem}` (the name without its
+/// extension), `{ext}` (the extension) and `{lang}` (the target language).
+pub fn output_file_name(path: &Path, lang: &str, template: &str) -> String {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = if is_pdf(path) {
+        TEXT_LAYER_EXTENSION.into()
+    } else {
+        path.extension().unwrap_or_default().to_string_lossy()
+    };
+
+    let rendered = template
+        .replace("{name}", &name)
+        .replace("{stem}", &stem)
+        .replace("{ext}", &extension)
+        .replace("{lang}", lang);
+
+    // Files without an extension would otherwise end with a dangling dot.
+    rendered.trim_end_matches('.').to_string()
+}
+
+/// The output path for a translated file in batch mode.
+///
+/// With an output directory and no template, the file keeps its name inside that
+/// directory. Otherwise it is named after `template` (or [`DEFAULT_NAME_TEMPLATE`]), e.g.
+/// `README.md` becomes `README.pt.md`, next to the source or inside the output directory.
+pub fn batch_output_path(
+    path: &Path,
+    to: &str,
+    out_dir: Option<&Path>,
+    template: Option<&str>,
+) -> PathBuf {
+    match (out_dir, template) {
+        (Some(out_dir), None) => out_dir.join(path.file_name().unwrap_or_default()),
+        (Some(out_dir), Some(template)) => out_dir.join(output_file_name(path, to, template)),
+        (None, template) => path.with_file_name(output_file_name(
+            path,
+            to,
+            template.unwrap_or(DEFAULT_NAME_TEMPLATE),
+        )),
+    }
+}
+
+/// File extensions translated in recursive mode. Any other file is copied untouched.
+pub const TEXT_EXTENSIONS: &[&str] = &[
+    "md", "markdown", "mdx", "txt", "text", "rst", "adoc", "html", "htm",
+];
+
+/// The extension of the text extracted from a PDF, which is what gets translated.
+pub const TEXT_LAYER_EXTENSION: &str = "txt";
+
+/// Whether `path` is a PDF, whose text layer is extracted and translated.
+pub fn is_pdf(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("pdf"))
+}
+
+/// Whether a file is translated (rather than copied) in recursive mode: text files, PDFs and
+/// any document kind trlt understands.
+pub fn is_translatable(path: &Path) -> bool {
+    let is_text = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .is_some_and(|extension| TEXT_EXTENSIONS.contains(&extension.as_str()));
+    is_text || is_pdf(path) || DocumentKind::from_path(path) != DocumentKind::Text
+}
+
+/// List every file under `root`, recursively, sorted by path.
+pub fn walk_dir(root: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
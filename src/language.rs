@@ -0,0 +1,414 @@
+use crate::{Error, Result};
+
+/// The ISO 639-1 languages, and common regional variants, with their English names.
+pub const LANGUAGES: &[(&str, &str)] = &[
+    ("aa", "Afar"),
+    ("ab", "Abkhazian"),
+    ("af", "Afrikaans"),
+    ("ak", "Akan"),
+    ("am", "Amharic"),
+    ("an", "Aragonese"),
+    ("ar", "Arabic"),
+    ("as", "Assamese"),
+    ("av", "Avaric"),
+    ("ay", "Aymara"),
+    ("az", "Azerbaijani"),
+    ("ba", "Bashkir"),
+    ("be", "Belarusian"),
+    ("bg", "Bulgarian"),
+    ("bi", "Bislama"),
+    ("bm", "Bambara"),
+    ("bn", "Bengali"),
+    ("bo", "Tibetan"),
+    ("br", "Breton"),
+    ("bs", "Bosnian"),
+    ("ca", "Catalan"),
+    ("ce", "Chechen"),
+    ("ch", "Chamorro"),
+    ("co", "Corsican"),
+    ("cr", "Cree"),
+    ("cs", "Czech"),
+    ("cu", "Church Slavic"),
+    ("cv", "Chuvash"),
+    ("cy", "Welsh"),
+    ("da", "Danish"),
+    ("de", "German"),
+    ("dv", "Divehi"),
+    ("dz", "Dzongkha"),
+    ("ee", "Ewe"),
+    ("el", "Greek"),
+    ("en", "English"),
+    ("en-GB", "English (United Kingdom)"),
+    ("en-US", "English (United States)"),
+    ("eo", "Esperanto"),
+    ("es", "Spanish"),
+    ("es-419", "Spanish (Latin America)"),
+    ("es-MX", "Spanish (Mexico)"),
+    ("et", "Estonian"),
+    ("eu", "Basque"),
+    ("fa", "Persian"),
+    ("ff", "Fulah"),
+    ("fi", "Finnish"),
+    ("fj", "Fijian"),
+    ("fo", "Faroese"),
+    ("fr", "French"),
+    ("fr-CA", "French (Canada)"),
+    ("fy", "Western Frisian"),
+    ("ga", "Irish"),
+    ("gd", "Scottish Gaelic"),
+    ("gl", "Galician"),
+    ("gn", "Guarani"),
+    ("gu", "Gujarati"),
+    ("gv", "Manx"),
+    ("ha", "Hausa"),
+    ("he", "Hebrew"),
+    ("hi", "Hindi"),
+    ("ho", "Hiri Motu"),
+    ("hr", "Croatian"),
+    ("ht", "Haitian Creole"),
+    ("hu", "Hungarian"),
+    ("hy", "Armenian"),
+    ("hz", "Herero"),
+    ("ia", "Interlingua"),
+    ("id", "Indonesian"),
+    ("ie", "Interlingue"),
+    ("ig", "Igbo"),
+    ("ii", "Sichuan Yi"),
+    ("ik", "Inupiaq"),
+    ("io", "Ido"),
+    ("is", "Icelandic"),
+    ("it", "Italian"),
+    ("iu", "Inuktitut"),
+    ("ja", "Japanese"),
+    ("jv", "Javanese"),
+    ("ka", "Georgian"),
+    ("kg", "Kongo"),
+    ("ki", "Kikuyu"),
+    ("kj", "Kuanyama"),
+    ("kk", "Kazakh"),
+    ("kl", "Kalaallisut"),
+    ("km", "Khmer"),
+    ("kn", "Kannada"),
+    ("ko", "Korean"),
+    ("kr", "Kanuri"),
+    ("ks", "Kashmiri"),
+    ("ku", "Kurdish"),
+    ("kv", "Komi"),
+    ("kw", "Cornish"),
+    ("ky", "Kyrgyz"),
+    ("la", "Latin"),
+    ("lb", "Luxembourgish"),
+    ("lg", "Ganda"),
+    ("li", "Limburgish"),
+    ("ln", "Lingala"),
+    ("lo", "Lao"),
+    ("lt", "Lithuanian"),
+    ("lu", "Luba-Katanga"),
+    ("lv", "Latvian"),
+    ("mg", "Malagasy"),
+    ("mh", "Marshallese"),
+    ("mi", "Maori"),
+    ("mk", "Macedonian"),
+    ("ml", "Malayalam"),
+    ("mn", "Mongolian"),
+    ("mr", "Marathi"),
+    ("ms", "Malay"),
+    ("mt", "Maltese"),
+    ("my", "Burmese"),
+    ("na", "Nauru"),
+    ("nb", "Norwegian Bokmål"),
+    ("nd", "North Ndebele"),
+    ("ne", "Nepali"),
+    ("ng", "Ndonga"),
+    ("nl", "Dutch"),
+    ("nn", "Norwegian Nynorsk"),
+    ("no", "Norwegian"),
+    ("nr", "South Ndebele"),
+    ("nv", "Navajo"),
+    ("ny", "Chichewa"),
+    ("oc", "Occitan"),
+    ("oj", "Ojibwa"),
+    ("om", "Oromo"),
+    ("or", "Odia"),
+    ("os", "Ossetian"),
+    ("pa", "Punjabi"),
+    ("pi", "Pali"),
+    ("pl", "Polish"),
+    ("ps", "Pashto"),
+    ("pt", "Portuguese"),
+    ("pt-BR", "Portuguese (Brazil)"),
+    ("pt-PT", "Portuguese (Portugal)"),
+    ("qu", "Quechua"),
+    ("rm", "Romansh"),
+    ("rn", "Rundi"),
+    ("ro", "Romanian"),
+    ("ru", "Russian"),
+    ("rw", "Kinyarwanda"),
+    ("sa", "Sanskrit"),
+    ("sc", "Sardinian"),
+    ("sd", "Sindhi"),
+    ("se", "Northern Sami"),
+    ("sg", "Sango"),
+    ("si", "Sinhala"),
+    ("sk", "Slovak"),
+    ("sl", "Slovenian"),
+    ("sm", "Samoan"),
+    ("sn", "Shona"),
+    ("so", "Somali"),
+    ("sq", "Albanian"),
+    ("sr", "Serbian"),
+    ("ss", "Swati"),
+    ("st", "Southern Sotho"),
+    ("su", "Sundanese"),
+    ("sv", "Swedish"),
+    ("sw", "Swahili"),
+    ("ta", "Tamil"),
+    ("te", "Telugu"),
+    ("tg", "Tajik"),
+    ("th", "Thai"),
+    ("ti", "Tigrinya"),
+    ("tk", "Turkmen"),
+    ("tl", "Tagalog"),
+    ("tn", "Tswana"),
+    ("to", "Tonga"),
+    ("tr", "Turkish"),
+    ("ts", "Tsonga"),
+    ("tt", "Tatar"),
+    ("tw", "Twi"),
+    ("ty", "Tahitian"),
+    ("ug", "Uyghur"),
+    ("uk", "Ukrainian"),
+    ("ur", "Urdu"),
+    ("uz", "Uzbek"),
+    ("ve", "Venda"),
+    ("vi", "Vietnamese"),
+    ("vo", "Volapük"),
+    ("wa", "Walloon"),
+    ("wo", "Wolof"),
+    ("xh", "Xhosa"),
+    ("yi", "Yiddish"),
+    ("yo", "Yoruba"),
+    ("za", "Zhuang"),
+    ("zh", "Chinese"),
+    ("zh-Hans", "Chinese (Simplified)"),
+    ("zh-Hant", "Chinese (Traditional)"),
+    ("zu", "Zulu"),
+];
+
+/// Find a language by code or English name, ignoring case and accepting `_` for `-`.
+pub fn find_language(language: &str) -> Option<(&'static str, &'static str)> {
+    let normalized = language.trim().replace('_', "-");
+    LANGUAGES.iter().copied().find(|(code, name)| {
+        code.eq_ignore_ascii_case(&normalized) || name.eq_ignore_ascii_case(&normalized)
+    })
+}
+
+/// Read a language pair such as `fr:en`, or `:en` to detect the source language, as the codes
+/// of its source and target languages.
+pub fn parse_language_pair(pair: &str) -> Result<(Option<String>, String)> {
+    let Some((from, to)) = pair.split_once(':') else {
+        return Err(Error::InvalidInput(format!(
+            "Invalid language pair `{}`; write it as `from:to`, e.g. `fr:en`",
+            pair
+        )));
+    };
+    let from = Some(from.trim())
+        .filter(|from| !from.is_empty())
+        .map(resolve_language)
+        .transpose()?;
+    Ok((from, resolve_language(to)?))
+}
+
+/// Whether `text` looks written in `language`, a code, name or tag such as `pt-BR`, as
+/// detected offline. Text whose language cannot be detected is taken not to be.
+pub fn is_written_in(text: &str, language: &str) -> bool {
+    let Some(detected) = whatlang::detect_lang(text) else {
+        return false;
+    };
+    let normalized = language.trim().replace('_', "-");
+    let primary = normalized.split('-').next().unwrap_or_default();
+    match detected {
+        // Chinese is detected as Mandarin.
+        whatlang::Lang::Cmn => primary.eq_ignore_ascii_case("zh"),
+        detected => find_language(&normalized)
+            .or_else(|| find_language(primary))
+            .is_some_and(|(_, name)| name.eq_ignore_ascii_case(detected.eng_name())),
+    }
+}
+
+/// Other names of languages and of their regional variants, in lower case, with their codes.
+const LANGUAGE_ALIASES: &[(&str, &str)] = &[
+    ("brazilian portuguese", "pt-BR"),
+    ("brazilian", "pt-BR"),
+    ("european portuguese", "pt-PT"),
+    ("simplified chinese", "zh-Hans"),
+    ("traditional chinese", "zh-Hant"),
+    ("mandarin", "zh"),
+    ("british english", "en-GB"),
+    ("american english", "en-US"),
+    ("latin american spanish", "es-419"),
+    ("mexican spanish", "es-MX"),
+    ("castilian", "es"),
+    ("canadian french", "fr-CA"),
+    ("farsi", "fa"),
+    ("flemish", "nl"),
+];
+
+/// `name` in lower case and without the accents of Latin letters, e.g. `portugues` for
+/// `Português`, with `_` read as `-`.
+fn fold(name: &str) -> String {
+    name.trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'á' | 'à' | 'â' | 'ã' | 'ä' | 'å' => 'a',
+            'ç' => 'c',
+            'é' | 'è' | 'ê' | 'ë' => 'e',
+            'í' | 'ì' | 'î' | 'ï' => 'i',
+            'ñ' => 'n',
+            'ó' | 'ò' | 'ô' | 'õ' | 'ö' => 'o',
+            'ú' | 'ù' | 'û' | 'ü' => 'u',
+            '_' => '-',
+            c => c,
+        })
+        .collect()
+}
+
+/// The code of the language whose native name, e.g. `Deutsch`, folds to `folded`.
+fn native_language(folded: &str) -> Option<&'static str> {
+    let lang = whatlang::Lang::all()
+        .iter()
+        .find(|lang| fold(lang.name()) == folded)?;
+    match lang {
+        whatlang::Lang::Cmn => Some("zh"),
+        lang => find_language(lang.eng_name()).map(|(code, _)| code),
+    }
+}
+
+/// Resolve a language code, English or native name, or common variant such as "brazilian
+/// portuguese" to its code, e.g. `pt-BR`, ignoring case and accents. Tags such as `de-AT` whose
+/// language is known are kept as they are.
+///
+/// A name is also recognized by its start when only one language starts that way. Otherwise,
+/// the languages it could be, or the closest match, are suggested.
+pub fn resolve_language(language: &str) -> Result<String> {
+    let folded = fold(language);
+    let code = LANGUAGES
+        .iter()
+        .find(|(code, name)| fold(code) == folded || fold(name) == folded)
+        .map(|(code, _)| *code)
+        .or_else(|| {
+            LANGUAGE_ALIASES
+                .iter()
+                .find(|(alias, _)| *alias == folded)
+                .map(|(_, code)| *code)
+        })
+        .or_else(|| native_language(&folded));
+    if let Some(code) = code {
+        return Ok(code.to_string());
+    }
+    let normalized = language.trim().replace('_', "-");
+    if let Some((primary, _)) = normalized.split_once('-') {
+        if primary.len() == 2 && find_language(primary).is_some() {
+            return Ok(normalized);
+        }
+    }
+
+    if folded.chars().count() >= 3 {
+        let candidates = LANGUAGES
+            .iter()
+            .filter(|(_, name)| fold(name).starts_with(&folded))
+            .collect::<Vec<_>>();
+        match candidates.as_slice() {
+            [] => {}
+            [(code, _)] => return Ok(code.to_string()),
+            candidates => {
+                let candidates = candidates
+                    .iter()
+                    .map(|(code, name)| format!("`{}` ({})", code, name))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                return Err(Error::InvalidInput(format!(
+                    "Ambiguous language `{}`. Did you mean one of {}?",
+                    language, candidates
+                )));
+            }
+        }
+    }
+
+    let suggestion = LANGUAGES
+        .iter()
+        .flat_map(|(code, name)| {
+            [code, name].map(|candidate| {
+                let distance = edit_distance(&folded, &fold(candidate));
+                (distance, *code, *name)
+            })
+        })
+        .min_by_key(|(distance, _, _)| *distance)
+        .filter(|(distance, _, _)| *distance <= normalized.len().div_ceil(2));
+
+    Err(Error::InvalidInput(match suggestion {
+        Some((_, code, name)) => format!(
+            "Unknown language `{}`. Did you mean `{}` ({})? Run `trlt languages` to list the supported languages.",
+            language, code, name
+        ),
+        None => format!(
+            "Unknown language `{}`. Run `trlt languages` to list the supported languages.",
+            language
+        ),
+    }))
+}
+
+/// The language to translate to when none is given: `configured`, resolved to its code, or the
+/// language of the system locale as set by `LC_ALL`, `LC_MESSAGES` or `LANG`, or English.
+pub fn default_target_language(configured: Option<&str>) -> Result<String> {
+    if let Some(language) = configured {
+        return resolve_language(language);
+    }
+    Ok(locale_language().unwrap_or_else(|| "en".to_string()))
+}
+
+/// The language of the system locale, e.g. `pt-BR` for `pt_BR.UTF-8`, or `de` for `de_AT`, if
+/// it is a known language.
+fn locale_language() -> Option<String> {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .into_iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|locale| !locale.is_empty())?;
+    // e.g. `pt_BR.UTF-8@euro`, where `C` and `POSIX` tell no language.
+    let tag = locale
+        .split(['.', '@'])
+        .next()
+        .unwrap_or_default()
+        .replace('_', "-");
+    if let Some((code, _)) = find_language(&tag) {
+        return Some(code.to_string());
+    }
+    let primary = tag.split('-').next().unwrap_or_default();
+    find_language(primary)
+        .filter(|(code, _)| code.len() == 2)
+        .map(|(code, _)| code.to_string())
+}
+
+/// Check that `language` is a known language, as [`resolve_language`] reads it, suggesting the
+/// closest match otherwise.
+pub fn validate_language(language: &str) -> Result<()> {
+    resolve_language(language).map(|_| ())
+}
+
+/// The Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
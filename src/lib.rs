@@ -1,5 +1,63 @@
+mod batch;
+mod budget;
+mod cache;
+mod checkpoint;
+mod chunk;
+mod compare;
 mod config;
 mod error;
+mod estimate;
+mod format;
+mod glossary;
+mod grpc;
+mod history;
+mod incremental;
+mod input;
+mod language;
+mod memory;
+mod output;
+mod placeholder;
+mod progress;
+mod prompt;
+mod provider;
+mod quality;
+mod review;
+mod serve;
+mod stdio;
+mod style;
+mod sync;
+mod tui;
+mod usage;
+mod verify;
 
+pub use batch::*;
+pub use budget::*;
+pub use cache::*;
+pub use checkpoint::*;
+pub use chunk::*;
+pub use compare::*;
 pub use config::*;
 pub use error::*;
+pub use estimate::*;
+pub use format::*;
+pub use glossary::*;
+pub use grpc::*;
+pub use history::*;
+pub use incremental::*;
+pub use input::*;
+pub use language::*;
+pub use memory::*;
+pub use output::*;
+pub use placeholder::*;
+pub use progress::*;
+pub use prompt::*;
+pub use provider::*;
+pub use quality::*;
+pub use review::*;
+pub use serve::*;
+pub use stdio::*;
+pub use style::*;
+pub use sync::*;
+pub use tui::*;
+pub use usage::*;
+pub use verify::*;
@@ -1,14 +1,42 @@
 use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    ffi::OsString,
     fs,
-    io::{self, Read},
-    path::Path,
+    io::{self, BufRead, IsTerminal, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::{Arc, OnceLock},
+    time::{Duration, Instant},
 };
 
-use anyhow::Result;
-use clap::{Parser, Subcommand};
+use anyhow::{Context, Result};
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::{env::Shells, ArgValueCandidates, CompleteEnv, CompletionCandidate, Shell};
 use clipboard::{ClipboardContext, ClipboardProvider};
-use serde_json::json;
-use trlt::Config;
+use futures_util::future::join_all;
+use tokio::sync::Semaphore;
+use tracing_subscriber::EnvFilter;
+use trlt::{
+    BudgetTranslator, Cache, CachedTranslator, Checkpoint, CheckpointTranslator, ChunkedTranslator,
+    ColorMode, ConcurrencyLimitTranslator, Config, ConfigFile, Document, DocumentKind,
+    DocumentOptions, DryRunTranslator, Glossary, GlossaryTerm, GlossaryTranslator, HistoryEntry,
+    HistoryFormat, IncrementalTranslator, Input, MemoryTranslator, PlaceholderTranslator, Progress,
+    PromptOptions, PromptTranslator, ProviderKind, QualityScore, SessionSettings, Tone,
+    Translation, TranslationHistory, TranslationMemory, TranslationRequest, Translator,
+    UsageGrouping, UsageLog, UsageMeter, UsageTranslator, DEFAULT_OPENAI_MODEL, DELETED_STYLE,
+    DIMMED_STYLE, ERROR_STYLE, HEADING_STYLE, INSERTED_STYLE, WARNING_STYLE,
+};
+
+/// The exit codes, listed at the end of `trlt --help`. They are defined as `trlt::EXIT_*`.
+const EXIT_CODES: &str = "Exit codes:
+  0  Success
+  1  Any other failure
+  2  Invalid arguments or input, such as an unknown language or a missing input file
+  3  Missing or invalid config file or API key
+  4  The provider rejected the credentials
+  5  The provider kept rejecting requests for exceeding its rate limit
+  6  The provider could not be reached, timed out or kept failing
+  7  Some inputs of a batch failed to translate";
 
 /// The translator CLI (trlt) is a command-line tool to translate text using the OpenAI API.
 ///
@@ -18,144 +46,3117 @@ use trlt::Config;
 /// If no input is provided, it will read from stdin. If no output is provided, it will write to stdout.
 ///
 /// The language to translate from can be auto-detected or specified using the `-f|--from` option.
+///
+/// `translate` is the default command, so `trlt "bonjour" -t en` is the same as `trlt translate "bonjour" -t en`.
 #[derive(Debug, Parser)]
-#[command(name = "trlt", version, author, about, long_about)]
+#[command(name = "trlt", version, author, about, long_about, after_long_help = EXIT_CODES)]
 struct Cli {
     #[command(subcommand)]
     command: Command,
+    /// Log what trlt does to stderr: request timings, chunking and retries with `-v`, and cache hits with `-vv`.
+    ///
+    /// The `RUST_LOG` environment variable takes precedence, e.g. `RUST_LOG=trlt=debug,reqwest=debug`.
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+    /// Only print errors, leaving out warnings, the progress bar and the tokens used by `translate`.
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+    /// When to color the output. `auto` colors it when it goes to a terminal and `NO_COLOR` is not set.
+    #[arg(long, value_enum, default_value_t, global = true)]
+    color: ColorMode,
+    /// The profile of the config file to use, e.g. `work` for the settings under `[profile.work]`.
+    #[arg(long, global = true, env = "TRLT_PROFILE", add = ArgValueCandidates::new(profile_candidates))]
+    profile: Option<String>,
 }
 
 #[derive(Subcommand, Debug)]
 enum Command {
-    /// Initialize the trlt CLI by creating a configuration file in $HOME/.config/trlt.toml.
+    /// Initialize the trlt CLI by creating a configuration file in $HOME/.config/trlt/config.toml.
     Init {
         /// The OpenAI API key. If not provided, it will be read from the environment variable `OPENAI_API_KEY`.
         #[arg(short, long, env = "OPENAI_API_KEY")]
         api_key: Option<String>,
         /// The language model to use. If not provided, it will use the default language model for translation.
-        #[arg(short, long, default_value = "gpt-4o-mini")]
+        #[arg(short, long, default_value = DEFAULT_OPENAI_MODEL)]
         model: String,
+        /// The default translation provider.
+        #[arg(short, long, value_enum, default_value_t = ProviderKind::OpenAi)]
+        provider: ProviderKind,
+        /// Store the API key in the system keyring rather than in the config file.
+        ///
+        /// When the keyring cannot be used, e.g. on headless systems, the API key is written to the config file instead.
+        #[arg(long)]
+        keyring: bool,
     },
     /// Translate text, file or stdin using the OpenAI API.
-    Translate {
-        /// The input to be translated. If not provided or is "-", read from stdin. This can be a file path or a string.
+    Translate(Box<TranslateArgs>),
+    /// Detect the language of text, a file or stdin, without translating it.
+    Detect {
+        /// The input whose language is detected. If "-" or not provided, read from stdin.
+        #[arg(default_value = "-")]
         input: String,
-        /// The output file path. If not provided, write to stdout.
-        output: Option<String>,
-        /// The language to translate from. If not provided, it will be auto-detected.
+        /// Detect the language offline instead of asking the provider.
+        #[arg(long)]
+        offline: bool,
+        /// The provider to ask. If not provided, it will use the one in the config file.
+        #[arg(short, long, value_enum, conflicts_with = "offline")]
+        provider: Option<ProviderKind>,
+    },
+    /// Start an interactive session translating each line as it is typed.
+    ///
+    /// Lines starting with `:` are commands, e.g. `:to de`, `:model gpt-4o` or `:tone formal`. Type `:help` to list them.
+    Repl(SessionArgs),
+    /// Open a full-screen interface with panes for the source, its translation, the session history and the settings.
+    ///
+    /// The source is translated again whenever you stop typing.
+    Tui(SessionArgs),
+    /// Translate files, then translate them again whenever they change, keeping the translated files up to date.
+    ///
+    /// Takes the same options as `translate`. Translations are written next to their source, e.g. `en.json`
+    /// becomes `en.pt.json`, unless `--out` is given. Unchanged segments are answered from the cache, so only
+    /// the keys and segments that changed are sent to the provider.
+    Watch(Box<TranslateArgs>),
+    /// Watch the clipboard, replacing the text copied in another language with its translation.
+    ///
+    /// Text already in the target language, as detected offline, is left alone. With `--print`, the translations
+    /// are printed instead and the clipboard is left untouched.
+    WatchClipboard {
+        #[command(flatten)]
+        session: SessionArgs,
+        /// Print the translations rather than copying them to the clipboard.
+        #[arg(long)]
+        print: bool,
+    },
+    /// Translate the keys of a JSON locale file that are missing or empty in another one, and merge them into it in place.
+    ///
+    /// Values already in the target file are never changed, so human translations are kept. The languages are
+    /// taken from the file names, e.g. `en.json` and `pt.json`, unless `--from` and `--to` are given.
+    Sync {
+        /// The locale file in the source language.
+        source: PathBuf,
+        /// The locale file to complete. It is created if it does not exist.
+        target: PathBuf,
+        /// The language of the source file.
+        #[arg(short, long, add = ArgValueCandidates::new(language_candidates))]
+        from: Option<String>,
+        /// The language of the target file.
+        #[arg(short, long, add = ArgValueCandidates::new(language_candidates))]
+        to: Option<String>,
+        /// The translation provider to use. If not provided, it will use the one in the config file.
+        #[arg(short, long, value_enum)]
+        provider: Option<ProviderKind>,
+        /// The model to use. If not provided, it will use the one in the config file for the provider.
+        #[arg(short, long)]
+        model: Option<String>,
+        /// The maximum number of keys to translate concurrently.
+        #[arg(short, long, default_value_t = 4)]
+        jobs: usize,
+    },
+    /// Speak newline-delimited JSON-RPC 2.0 on stdin and stdout, for editor plugins that run trlt as a subprocess.
+    ///
+    /// The methods are `translate`, `detect` and `cancel`. `translate` and `detect` take the same parameters as
+    /// the `serve` endpoints, and `cancel` takes the `id` of a running request.
+    Stdio {
+        /// The provider used when a request does not name one. If not provided, it will use the one in the config file.
+        #[arg(short, long, value_enum)]
+        provider: Option<ProviderKind>,
+        /// The model to use. If not provided, it will use the one in the config file for the provider.
         #[arg(short, long)]
+        model: Option<String>,
+    },
+    /// Run a local HTTP server with `POST /translate` and `POST /detect` endpoints.
+    ///
+    /// Editors, browser extensions and scripts can share one running process, its connections and the
+    /// translation cache. Both endpoints take and return JSON, e.g. `{"text": "Hello", "to": "pt"}`.
+    /// With `--grpc`, the `trlt.v1.TranslationService` described in `proto/trlt.proto` is served too.
+    Serve {
+        /// The address to listen on.
+        #[arg(short, long, default_value = trlt::DEFAULT_SERVE_ADDRESS)]
+        address: std::net::SocketAddr,
+        /// Also serve the gRPC service, on the given address or on 127.0.0.1:50051.
+        #[arg(long, num_args = 0..=1, default_missing_value = trlt::DEFAULT_GRPC_ADDRESS)]
+        grpc: Option<std::net::SocketAddr>,
+        /// The provider used when a request does not name one. If not provided, it will use the one in the config file.
+        #[arg(short, long, value_enum)]
+        provider: Option<ProviderKind>,
+        /// The model to use. If not provided, it will use the one in the config file for the provider.
+        #[arg(short, long)]
+        model: Option<String>,
+    },
+    /// List the supported languages and their codes.
+    Languages,
+    /// Print the script that completes the commands, options and language codes of trlt in a shell.
+    ///
+    /// Load it when the shell starts, e.g. with `source <(trlt completions bash)` in `~/.bashrc`,
+    /// `source <(trlt completions zsh)` in `~/.zshrc` or `trlt completions fish | source` in
+    /// `~/.config/fish/config.fish`. The script asks trlt for the completions, so loading it anew keeps it in sync
+    /// with the installed version.
+    Completions {
+        /// The shell to complete in.
+        shell: Shell,
+    },
+    /// Print the man page of trlt, or write the man pages of trlt and each of its commands to a directory.
+    ///
+    /// The pages written to a directory are named after the commands, e.g. `trlt.1` and `trlt-translate.1`, ready to
+    /// install in a `man1` directory.
+    Man {
+        /// The directory to write the man pages to. If not provided, print the man page of trlt to stdout.
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+    },
+    /// List the models offered by a provider, with context sizes and list prices when known.
+    Models {
+        /// The provider to ask. If not provided, it will use the one in the config file.
+        #[arg(short, long, value_enum)]
+        provider: Option<ProviderKind>,
+    },
+    /// Check the config file, the API key, the provider and its model, the clipboard and the cache, telling how to fix
+    /// what fails.
+    Doctor,
+    /// Show the tokens used by past runs of `trlt translate`, the requests they sent and their estimated cost.
+    Usage {
+        /// How to group the runs.
+        #[arg(short, long, value_enum, default_value_t)]
+        by: UsageGrouping,
+        /// How many days back to go, counting today.
+        #[arg(short, long, default_value_t = 30)]
+        days: u32,
+    },
+    /// Manage the local translation cache.
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommand,
+    },
+    /// Manage the translation memory, which is consulted for exact matches before calling the provider.
+    Memory {
+        #[command(subcommand)]
+        command: MemoryCommand,
+    },
+    /// Manage the glossary of terms that must be translated consistently.
+    Glossary {
+        #[command(subcommand)]
+        command: GlossaryCommand,
+    },
+    /// Browse the translations made by `trlt translate` and `trlt repl`.
+    History {
+        #[command(subcommand)]
+        command: HistoryCommand,
+    },
+    /// Read and edit the settings of the config file, e.g. `trlt config set model gpt-4o`.
+    ///
+    /// Settings are named by their dotted path in the file, such as `anthropic.model`. With `--profile`, `set` and
+    /// `unset` edit the settings of the profile.
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ConfigCommand {
+    /// Print the value of a setting, as used with the selected profile, the project config file and the `TRLT_*`
+    /// environment variables.
+    Get {
+        /// The setting, e.g. `model`.
+        key: String,
+    },
+    /// Set a setting, checking that it exists and that the value suits it.
+    Set {
+        /// The setting, e.g. `model`.
+        key: String,
+        /// The value, read as TOML when it can be, e.g. `5` or `["deepl"]`, and as a string otherwise.
+        value: String,
+    },
+    /// Remove a setting, so that its default is used.
+    Unset {
+        /// The setting, e.g. `model`.
+        key: String,
+    },
+    /// Encrypt the config file with a passphrase, which is then asked for whenever it is read, unless `TRLT_PASSPHRASE`
+    /// is set.
+    Encrypt,
+    /// Decrypt the config file, storing it in plain text again.
+    Decrypt,
+}
+
+/// The settings an interactive session starts with.
+#[derive(Args, Debug)]
+struct SessionArgs {
+    /// The language to translate from. If not provided, it will be auto-detected.
+    #[arg(short, long, add = ArgValueCandidates::new(language_candidates))]
+    from: Option<String>,
+    /// The language to translate to. If not provided, it will use the one in the config file, or
+    /// the language of the system locale.
+    #[arg(short, long, add = ArgValueCandidates::new(language_candidates))]
+    to: Option<String>,
+    /// The translation provider to use. If not provided, it will use the one in the config file.
+    #[arg(short, long, value_enum)]
+    provider: Option<ProviderKind>,
+    /// The model to use. If not provided, it will use the one in the config file for the provider.
+    #[arg(short, long)]
+    model: Option<String>,
+    /// The register of the translations.
+    #[arg(long, value_enum)]
+    tone: Option<Tone>,
+}
+
+#[derive(Subcommand, Debug)]
+enum GlossaryCommand {
+    /// Add a term to the glossary, replacing any existing entry for the same term and languages.
+    Add {
+        /// The term in the source language.
+        source: String,
+        /// How the term must be translated.
+        target: String,
+        /// The source language. If not provided, the term applies to any source language.
+        #[arg(short, long, add = ArgValueCandidates::new(language_candidates))]
         from: Option<String>,
-        /// The language to translate to.
-        #[arg(short, long, default_value = "en")]
+        /// The target language.
+        #[arg(short, long, add = ArgValueCandidates::new(language_candidates))]
         to: String,
     },
+    /// Remove a term from the glossary.
+    Remove {
+        /// The term in the source language.
+        source: String,
+        /// Only remove the entry for this target language.
+        #[arg(short, long, add = ArgValueCandidates::new(language_candidates))]
+        to: Option<String>,
+    },
+    /// List the glossary terms.
+    List {
+        /// Only list the terms for this source language.
+        #[arg(short, long, add = ArgValueCandidates::new(language_candidates))]
+        from: Option<String>,
+        /// Only list the terms for this target language.
+        #[arg(short, long, add = ArgValueCandidates::new(language_candidates))]
+        to: Option<String>,
+    },
+    /// Import terms from a CSV file with `source` and `target` columns, and optional `from` and `to` columns.
+    Import {
+        /// The CSV file to import.
+        path: PathBuf,
+        /// The source language of terms without a `from` column.
+        #[arg(short, long, add = ArgValueCandidates::new(language_candidates))]
+        from: Option<String>,
+        /// The target language of terms without a `to` column.
+        #[arg(short, long, add = ArgValueCandidates::new(language_candidates))]
+        to: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum MemoryCommand {
+    /// Import the segment pairs of a TMX file into the translation memory.
+    Import {
+        /// The TMX file to import.
+        path: PathBuf,
+    },
+    /// Export the translation memory to a TMX file.
+    Export {
+        /// The TMX file to write.
+        path: PathBuf,
+    },
+    /// Show the number of segment pairs in the translation memory.
+    Stats,
+    /// Remove every segment pair from the translation memory.
+    Clear,
+}
+
+#[derive(Subcommand, Debug)]
+enum HistoryCommand {
+    /// List the latest translations, the most recent first.
+    List {
+        /// How many translations to list.
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+        /// Write the translations in full to stdout in this format, for archiving or processing.
+        #[arg(long, value_enum)]
+        export: Option<HistoryFormat>,
+    },
+    /// Search the sources and results of past translations for every word of a query.
+    Search {
+        /// The words to search for.
+        query: String,
+        /// How many translations to list.
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+        /// Write the translations in full to stdout in this format, for archiving or processing.
+        #[arg(long, value_enum)]
+        export: Option<HistoryFormat>,
+    },
+    /// Show a translation in full, with its source.
+    Show {
+        /// The id of the translation, as listed by `trlt history list`.
+        id: i64,
+    },
+    /// Remove every translation from the history.
+    Clear,
+}
+
+#[derive(Subcommand, Debug)]
+enum CacheCommand {
+    /// Remove every cached translation.
+    Clear,
+    /// Show the number of cached translations and the disk space they use.
+    Stats,
+}
+
+#[derive(Args, Debug, Clone)]
+struct TranslateArgs {
+    /// The inputs to be translated. If "-", read from stdin, and if "clip", from the clipboard. Each input can be a file path, a glob pattern or a string.
+    ///
+    /// If not provided, the inputs set in the config file, e.g. by a `.trlt.toml` of the project, are translated.
+    input: Vec<String>,
+    /// Translate the contents of the clipboard, like a "clip" input.
+    #[arg(short, long, conflicts_with_all = ["recursive", "jsonl"])]
+    clipboard: bool,
+    /// The output path. If not provided, write to stdout.
+    ///
+    /// With several inputs, this is the directory the translated files are written to. If not provided,
+    /// each translated file is written next to its input, e.g. `README.md` becomes `README.pt.md`.
+    /// With several target languages, one file is written per language, named after `--name-template`.
+    #[arg(short, long = "out", visible_alias = "output")]
+    out: Option<PathBuf>,
+    /// The language to translate from. If not provided, it will be auto-detected.
+    #[arg(short, long, add = ArgValueCandidates::new(language_candidates))]
+    from: Option<String>,
+    /// The languages to translate to. Several languages can be given as a comma-separated list or by repeating the option.
+    ///
+    /// If not provided, it will use the one in the config file, or the language of the system locale, e.g. `pt-BR` for
+    /// `LANG=pt_BR.UTF-8`, and English otherwise.
+    #[arg(short, long, add = ArgValueCandidates::new(language_candidates), value_delimiter = ',')]
+    to: Vec<String>,
+    /// The languages to translate from and to, as `from:to`, e.g. `fr:en`, or `:en` to detect the source language.
+    ///
+    /// A first input such as `fr:en`, followed by other inputs, is read the same way, e.g. `trlt fr:en "bonjour"`.
+    #[arg(long, conflicts_with_all = ["from", "to"])]
+    pair: Option<String>,
+    /// The template used to name output files, with the `{name}`, `{stem}`, `{ext}` and `{lang}` variables.
+    ///
+    /// Defaults to `{stem}.{lang}.{ext}` when translating several files or into several languages.
+    #[arg(long)]
+    name_template: Option<String>,
+    /// The translation provider to use. If not provided, it will use the one in the config file.
+    #[arg(short, long, value_enum)]
+    provider: Option<ProviderKind>,
+    /// The model to use. If not provided, it will use the one in the config file for the provider.
+    #[arg(short, long)]
+    model: Option<String>,
+    /// The base URL of an OpenAI-compatible API. If not provided, it will use the one in the config file.
+    #[arg(long)]
+    base_url: Option<String>,
+    /// The name of a prompt template from the `[templates]` section of the config file.
+    #[arg(long)]
+    template: Option<String>,
+    /// The register of the translation. DeepL maps it to its formality setting.
+    #[arg(long, value_enum)]
+    tone: Option<Tone>,
+    /// A description of where the text is used, e.g. "UI button labels for a banking app", so short or ambiguous strings are translated for their domain.
+    #[arg(long)]
+    context: Option<String>,
+    /// The sampling temperature. If not provided, it will use the one in the config file or the provider's default.
+    #[arg(long)]
+    temperature: Option<f64>,
+    /// The nucleus sampling probability. If not provided, it will use the one in the config file or the provider's default.
+    #[arg(long)]
+    top_p: Option<f64>,
+    /// The maximum number of tokens generated per request. If not provided, it will use the one in the config file or the provider's default.
+    #[arg(long)]
+    max_tokens: Option<u32>,
+    /// The seed for deterministic sampling, on providers that support it.
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Wait for the whole translation instead of printing it as it arrives.
+    #[arg(long)]
+    no_stream: bool,
+    /// Print the translations written to files to stdout as well, so that they can be piped onward.
+    #[arg(long)]
+    tee: bool,
+    /// How to print the translations. With `json`, each translation is printed as a
+    /// `{ "translation", "detected_source", "model", "tokens", "duration_ms" }` object on its own line,
+    /// for scripts and editor plugins. Translations written to files are still written to them.
+    #[arg(long, value_enum, default_value_t, conflicts_with_all = ["jsonl", "alternatives", "dry_run"])]
+    format: OutputFormat,
+    /// Translate every file in the input directories, mirroring the directory tree into `--out`.
+    ///
+    /// Text files are translated and any other file is copied untouched.
+    #[arg(short, long, requires = "out")]
+    recursive: bool,
+    /// The maximum number of requests to send to the provider at a time, across all the inputs
+    /// and the segments of each.
+    #[arg(short, long, default_value_t = 4)]
+    jobs: usize,
+    /// The maximum size of a chunk, in bytes. Longer inputs are split into chunks at paragraph or sentence boundaries.
+    #[arg(long)]
+    chunk_size: Option<usize>,
+    /// How many times a request is sent when it fails with a rate limit, a server error or a dropped connection.
+    /// If not provided, it will use the one in the config file, or 3.
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    max_attempts: Option<u32>,
+    /// How long a request to the provider may take, in seconds, before it is given up.
+    /// If not provided, it will use the one in the config file, or 120.
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    timeout: Option<u64>,
+    /// Always call the provider, ignoring and not updating the local cache.
+    #[arg(long)]
+    no_cache: bool,
+    /// Do not consult or update the translation memory.
+    #[arg(long)]
+    no_memory: bool,
+    /// Do not record the translations in the history.
+    #[arg(long)]
+    no_history: bool,
+    /// Do not copy the translation to the clipboard. Set `clipboard = false` in the config file to never copy it.
+    #[arg(long)]
+    no_clipboard: bool,
+    /// Show a desktop notification when the translation finishes, telling whether it succeeded, so that long batches
+    /// and documents can be left running.
+    #[arg(long)]
+    notify: bool,
+    /// Do not add glossary terms to the prompt.
+    #[arg(long)]
+    no_glossary: bool,
+    /// Send interpolation placeholders such as `{name}`, `%s` or `{{var}}` to the provider as-is,
+    /// instead of protecting them and checking that they survive the translation.
+    #[arg(long)]
+    no_placeholders: bool,
+    /// How to read the input. If not provided, it is detected from the file extension.
+    ///
+    /// Structured documents such as Markdown are split into segments so only their prose is translated.
+    #[arg(long, value_enum)]
+    mode: Option<DocumentKind>,
+    /// The number of preceding segments of a structured document sent as context with each segment,
+    /// so that e.g. dialogue split across subtitle cues reads naturally.
+    ///
+    /// Defaults to 2 for subtitles and 0 otherwise.
+    #[arg(long)]
+    segment_context: Option<usize>,
+    /// Write non-ASCII characters of `.properties` files as `\uXXXX` escapes, for tools that read them as Latin-1.
+    #[arg(long)]
+    escape_unicode: bool,
+    /// Translate a CSV or TSV file, the same as `--mode csv`.
+    #[arg(long, conflicts_with = "mode")]
+    csv: bool,
+    /// The CSV column to translate, as a header name or a one-based index. The other columns are kept as they are.
+    #[arg(long)]
+    column: Option<String>,
+    /// The Markdown front matter fields to translate. Any other field is kept as it is.
+    #[arg(long, value_delimiter = ',', default_values = trlt::DEFAULT_FRONT_MATTER_FIELDS)]
+    front_matter_fields: Vec<String>,
+    /// Read the inputs as JSON Lines batches of `{ "id", "text", "to", "from" }` requests, and write one
+    /// `{ "id", "text" }` result per line, or `{ "id", "error" }` when a request fails.
+    ///
+    /// Requests without `to` or `from` use `--to` and `--from`.
+    #[arg(long, conflicts_with_all = ["recursive", "mode"])]
+    jsonl: bool,
+    /// Give this many candidate translations as a numbered list, or as a JSON array when `--out` is a `.json` file.
+    #[arg(short = 'n', long, value_parser = clap::value_parser!(u8).range(2..), conflicts_with_all = ["recursive", "mode", "jsonl"])]
+    alternatives: Option<u8>,
+    /// Show the source and the translation side by side on stderr, paragraph by paragraph or segment by
+    /// segment, to proofread them. On terminals narrower than 80 columns, each translation follows its source.
+    #[arg(long, conflicts_with_all = ["jsonl", "alternatives", "dry_run"])]
+    show_diff: bool,
+    /// Translate the result back into the source language and flag the segments whose back-translation diverges from the source.
+    #[arg(long, conflicts_with_all = ["jsonl", "alternatives"])]
+    verify: bool,
+    /// How similar, between 0 and 1, a back-translation must be to its source not to be flagged by `--verify`.
+    #[arg(long, default_value_t = trlt::DEFAULT_VERIFY_THRESHOLD, requires = "verify")]
+    verify_threshold: f64,
+    /// Ask a language model to score each translated segment from 1 to 5, and report the segments that need a review.
+    #[arg(long, conflicts_with_all = ["jsonl", "alternatives"])]
+    score: bool,
+    /// The provider that scores the translations. If not provided, it will use the translating provider.
+    #[arg(long, value_enum, requires = "score")]
+    score_provider: Option<ProviderKind>,
+    /// The model that scores the translations. If not provided, it will use the one of the scoring provider.
+    #[arg(long, requires = "score")]
+    score_model: Option<String>,
+    /// Segments scoring this or less are reported for review.
+    #[arg(long, default_value_t = trlt::DEFAULT_SCORE_THRESHOLD, value_parser = clap::value_parser!(u8).range(1..=5), requires = "score")]
+    score_threshold: u8,
+    /// Review each translated segment on the terminal, accepting, editing, retrying or skipping it. Skipped segments keep their source text.
+    #[arg(long, conflicts_with_all = ["jsonl", "alternatives"])]
+    review: bool,
+    /// Write the scores of every segment, with their reasons, to this JSON file.
+    #[arg(long, requires = "score")]
+    score_report: Option<PathBuf>,
+    /// Accept languages that `trlt languages` does not list, such as constructed or historical languages.
+    #[arg(long)]
+    any_language: bool,
+    /// Reuse the translations of the segments that did not change since the last run, and only send the changed ones to the provider.
+    ///
+    /// Text inputs are translated paragraph by paragraph. The translated segments are recorded in a hidden
+    /// file next to each output, e.g. `.README.pt.md.trlt.json` for `README.pt.md`.
+    #[arg(long, conflicts_with_all = ["jsonl", "alternatives"])]
+    incremental: bool,
+    /// Resume an interrupted batch job, given its id, skipping the inputs and segments it already translated.
+    ///
+    /// Runs with several inputs, and `--jsonl` batches, record their progress as they go and print the id
+    /// of the job when they fail or are interrupted. The job is resumed with its original options.
+    #[arg(long, value_name = "JOB_ID", conflicts_with = "input")]
+    resume: Option<String>,
+    /// Count the requests and tokens the run would use and estimate its cost, without calling the provider or
+    /// writing anything.
+    ///
+    /// Tokens are counted locally, assuming translations about as long as their source. Translations that
+    /// would come from the cache or the translation memory are counted too.
+    #[arg(long, conflicts_with_all = ["jsonl", "alternatives", "review", "score", "incremental", "resume"])]
+    dry_run: bool,
+    /// Run even if the estimated cost goes over the budget set in the config file.
+    #[arg(short, long)]
+    yes: bool,
+    /// Overwrite output files that already exist. Without it, trlt asks before overwriting them when run
+    /// on a terminal, and refuses to otherwise.
+    ///
+    /// Outputs of `--incremental` runs are always updated, as they are meant to be.
+    #[arg(long)]
+    force: bool,
+}
+
+impl TranslateArgs {
+    /// Set `--from` and `--to` from `--pair`, or from a first input that is a language pair, and
+    /// resolve the languages to their codes unless `--any-language` is given.
+    fn resolve_languages(&mut self) -> Result<()> {
+        self.resolve_pair()?;
+        if !self.any_language {
+            self.from = self
+                .from
+                .as_deref()
+                .map(trlt::resolve_language)
+                .transpose()?;
+            self.to = self
+                .to
+                .iter()
+                .map(|to| trlt::resolve_language(to))
+                .collect::<trlt::Result<_>>()?;
+        }
+        Ok(())
+    }
+
+    /// Use the inputs and target languages of `config` when none are given, and the default
+    /// target language otherwise.
+    fn apply_defaults(&mut self, config: &Config) -> Result<()> {
+        if self.input.is_empty() && !self.clipboard && !self.jsonl {
+            if config.input.is_empty() {
+                return Err(trlt::Error::InvalidInput(
+                    "No input given; pass one, or set `input` in the config file".to_string(),
+                )
+                .into());
+            }
+            self.input = config.input.clone();
+        }
+        if self.to.is_empty() {
+            self.to = match config.to.as_slice() {
+                [] => vec![trlt::default_target_language(None)?],
+                languages => languages
+                    .iter()
+                    .map(|language| trlt::resolve_language(language))
+                    .collect::<trlt::Result<_>>()?,
+            };
+        }
+        Ok(())
+    }
+
+    fn resolve_pair(&mut self) -> Result<()> {
+        let (from, to) = match self.pair.take() {
+            Some(pair) => trlt::parse_language_pair(&pair)?,
+            None => {
+                let pair = match self.input.as_slice() {
+                    [first, _, ..] if !Path::new(first).exists() => {
+                        trlt::parse_language_pair(first).ok()
+                    }
+                    _ => None,
+                };
+                let Some(pair) = pair else {
+                    return Ok(());
+                };
+                self.input.remove(0);
+                pair
+            }
+        };
+        self.from = from;
+        self.to = vec![to];
+        Ok(())
+    }
+
+    /// How to read an input: as given on the command line, or detected from its path.
+    fn mode_for(&self, path: Option<&Path>) -> DocumentKind {
+        self.mode
+            .or(self.csv.then_some(DocumentKind::Csv))
+            .unwrap_or_else(|| path.map(DocumentKind::from_path).unwrap_or_default())
+    }
+}
+
+/// How the translations are printed to stdout.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// The translated text.
+    #[default]
+    Text,
+    /// A JSON object per translation, with the model and the tokens it used.
+    Json,
+}
+
+/// How a translation that is not written to a file reaches stdout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Print {
+    /// As it arrives.
+    Stream,
+    /// Once it is complete.
+    Whole,
+    /// Not at all, as it is printed in another form.
+    Nothing,
+}
+
+/// The translation of the text of a job, and the scores of its segments.
+struct Translated {
+    request: TranslationRequest,
+    translation: Translation,
+    scores: Vec<QualityScore>,
+}
+
+/// A single file or text to translate, and where to write the result.
+struct Job {
+    input: Input,
+    to: String,
+    output: Option<PathBuf>,
+    /// Copy the input to the output as-is instead of translating it.
+    copy: bool,
+}
+
+impl Job {
+    /// What identifies the job in a checkpoint.
+    fn key(&self) -> String {
+        format!("{}\0{}", self.name(), self.to)
+    }
+
+    /// The name of the input in messages.
+    fn name(&self) -> String {
+        self.input
+            .path()
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|| "input".to_string())
+    }
 }
 
 #[tokio::main]
-async fn main() {
-    let cli = Cli::parse();
+async fn main() -> ExitCode {
+    // Answers the completion scripts of `trlt completions`, which run trlt with `COMPLETE` set.
+    CompleteEnv::with_factory(Cli::command).complete();
+    let cli = Cli::parse_from(with_default_command(std::env::args_os()));
+    cli.color.apply();
+    PROFILE
+        .set(cli.profile.clone())
+        .expect("the profile is only set once");
+    init_logging(cli.verbose, cli.quiet);
+    match run(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            anstream::eprintln!("{ERROR_STYLE}Error:{ERROR_STYLE:#} {:#}", e);
+            ExitCode::from(exit_code(&e))
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<()> {
     match cli.command {
-        Command::Init { api_key, model } => init(api_key, model),
-        Command::Translate {
+        Command::Init {
+            api_key,
+            model,
+            provider,
+            keyring,
+        } => init(api_key, model, provider, keyring),
+        Command::Translate(args) => translate(*args, cli.quiet).await,
+        Command::Detect {
             input,
-            output,
+            offline,
+            provider,
+        } => detect(&input, offline, provider).await,
+        Command::Repl(args) => repl(args).await,
+        Command::Tui(args) => tui(args).await,
+        Command::Watch(args) => watch(*args).await,
+        Command::WatchClipboard { session, print } => watch_clipboard(session, print).await,
+        Command::Sync {
+            source,
+            target,
             from,
             to,
-        } => {
-            let input_content = if input == "-" {
-                let mut buffer = String::new();
-                io::stdin()
-                    .read_to_string(&mut buffer)
-                    .expect("Failed to read from stdin");
-                buffer
-            } else if Path::new(&input).is_file() {
-                fs::read_to_string(&input).expect("Failed to read input file")
-            } else {
-                input.clone()
-            };
-            translate(&input_content, &output, &from, &to)
-                .await
-                .unwrap();
+            provider,
+            model,
+            jobs,
+        } => sync(&source, &target, from, to, provider, model, jobs).await,
+        Command::Stdio { provider, model } => stdio(provider, model).await,
+        Command::Serve {
+            address,
+            grpc,
+            provider,
+            model,
+        } => serve(address, grpc, provider, model).await,
+        Command::Languages => {
+            languages();
+            Ok(())
+        }
+        Command::Completions { shell } => completions(shell),
+        Command::Man { out } => man(out),
+        Command::Models { provider } => models(provider).await,
+        Command::Doctor => doctor().await,
+        Command::Usage { by, days } => usage_stats(by, days),
+        Command::Cache { command } => cache(command),
+        Command::Memory { command } => memory(command),
+        Command::Glossary { command } => glossary(command),
+        Command::Config { command } => config(command),
+        Command::History { command } => history(command),
+    }
+}
+
+/// Some inputs or requests of a batch failed to translate.
+#[derive(Debug)]
+struct PartialFailure {
+    failures: usize,
+    total: usize,
+    /// What the batch is made of, e.g. `inputs`.
+    unit: &'static str,
+}
+
+impl std::fmt::Display for PartialFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Failed to translate {} of {} {}",
+            self.failures, self.total, self.unit
+        )
+    }
+}
+
+impl std::error::Error for PartialFailure {}
+
+/// The exit code for `error`, from the first of its causes that tells why it failed.
+fn exit_code(error: &anyhow::Error) -> u8 {
+    for cause in error.chain() {
+        if let Some(e) = cause.downcast_ref::<trlt::Error>() {
+            return e.exit_code();
+        }
+        if cause.is::<PartialFailure>() {
+            return trlt::EXIT_PARTIAL_FAILURE;
         }
+        if let Some(e) = cause.downcast_ref::<io::Error>() {
+            if e.kind() == io::ErrorKind::NotFound {
+                return trlt::EXIT_INVALID_INPUT;
+            }
+        }
+    }
+    trlt::EXIT_FAILURE
+}
+
+/// The command line `args`, with `translate` inserted before the first argument that is not a
+/// global option when it is not a command, so that `trlt "bonjour" -t en` translates.
+fn with_default_command(args: impl IntoIterator<Item = OsString>) -> Vec<OsString> {
+    let mut args = args.into_iter().collect::<Vec<_>>();
+    let cli = Cli::command();
+    let mut index = 1;
+    while let Some(arg) = args.get(index).and_then(|arg| arg.to_str()) {
+        match arg {
+            "--color" | "--profile" => index += 2,
+            "--quiet" | "--verbose" => index += 1,
+            arg if arg.starts_with("--color=") || arg.starts_with("--profile=") => index += 1,
+            arg if arg.len() > 1
+                && arg.starts_with('-')
+                && arg[1..].chars().all(|c| "qv".contains(c)) =>
+            {
+                index += 1
+            }
+            _ => break,
+        }
+    }
+    let Some(arg) = args.get(index) else {
+        return args;
+    };
+    let arg = arg.to_string_lossy();
+    let is_command = arg == "help"
+        || cli.get_subcommands().any(|command| {
+            command.get_name() == arg || command.get_all_aliases().any(|alias| alias == arg)
+        });
+    if !is_command && !["-h", "--help", "-V", "--version"].contains(&arg.as_ref()) {
+        args.insert(index, "translate".into());
     }
+    args
+}
+
+/// The profile of the config file selected with `--profile` or `TRLT_PROFILE`.
+static PROFILE: OnceLock<Option<String>> = OnceLock::new();
+
+/// The config file, which `trlt init` creates, with the settings of the selected profile.
+fn read_config() -> Result<Config> {
+    let profile = PROFILE.get().and_then(Option::as_deref);
+    Config::read_profile(profile)
+        .context("Failed to read the config file; run `trlt init --help` to create one")
+}
+
+/// Log to stderr at the level asked for on the command line, or as set by `RUST_LOG`.
+fn init_logging(verbose: u8, quiet: bool) {
+    let directives = match (quiet, verbose) {
+        (true, _) => "error",
+        (false, 0) => "warn",
+        (false, 1) => "warn,trlt=info",
+        (false, 2) => "warn,trlt=debug",
+        (false, _) => "debug",
+    };
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(directives));
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(|| trlt::ProgressStderr)
+        .with_ansi(anstream::AutoStream::choice(&io::stderr()) != anstream::ColorChoice::Never)
+        .with_target(verbose > 1)
+        .without_time()
+        .init();
 }
 
-fn init(api_key: Option<String>, model: String) {
-    let config = Config::new(api_key, model).unwrap();
+fn init(
+    api_key: Option<String>,
+    model: String,
+    provider: ProviderKind,
+    keyring: bool,
+) -> Result<()> {
+    let mut config = Config::new(api_key, model, provider)?;
+    if keyring && !config.api_key.is_empty() {
+        match config.store_api_key_in_keyring() {
+            Ok(()) => println!("API key stored in the system keyring"),
+            Err(e) => eprintln!("{}; the API key is written to the config file instead.", e),
+        }
+    }
 
-    config.write_to_file().unwrap();
+    config.write_to_file()?;
 
     println!(
         "Config file created successfully in {}",
         Config::config_path().display()
     );
+    Ok(())
 }
 
-async fn translate(
-    input: &str,
-    output: &Option<String>,
-    from: &Option<String>,
-    to: &str,
-) -> Result<()> {
-    let config = Config::read_from_file().expect("Failed to read config file. Please run `trlt init --help` to help you create a config file.");
-    let client = reqwest::Client::new();
-
-    let prompt = if let Some(from_lang) = from {
-        format!("Translate this from {} to {}: {}", from_lang, to, input)
+/// Build the translator for the `translate` and `watch` commands, with the config it was built
+/// from, the primary provider and the meter of the tokens it uses.
+fn build_translator(
+    args: &TranslateArgs,
+) -> Result<(ChunkedTranslator, Config, ProviderKind, Arc<UsageMeter>)> {
+    let mut config = read_config()?;
+    if args.base_url.is_some() {
+        config.base_url = args.base_url.clone();
+    }
+    if args.max_attempts.is_some() {
+        config.max_attempts = args.max_attempts;
+    }
+    if args.timeout.is_some() {
+        config.timeout_secs = args.timeout;
+    }
+    let primary = args.provider.unwrap_or(config.provider);
+    if let Some(model) = &args.model {
+        config.set_model(primary, model.clone())?;
+    }
+    let generation = &mut config.generation;
+    generation.temperature = args.temperature.or(generation.temperature);
+    generation.top_p = args.top_p.or(generation.top_p);
+    generation.max_tokens = args.max_tokens.or(generation.max_tokens);
+    generation.seed = args.seed.or(generation.seed);
+    let usage = Arc::new(UsageMeter::default());
+    let provider: Box<dyn Translator> = if args.dry_run {
+        let model = config
+            .model_for(primary)
+            .unwrap_or_else(|| primary.to_string());
+        Box::new(DryRunTranslator::new(model, primary.is_language_model()))
     } else {
-        format!("Translate this to {}: {}", to, input)
-    };
-
-    let response = client
-        .post("https://api.openai.com/v1/chat/completions")
-        .header("Authorization", format!("Bearer {}", config.api_key))
-        .json(&json!({
-            "model": config.model,
-            "messages": [{
-                "role": "system",
-                "content": "You are a translator that only gives the translated text."
-            }, {
-                "role": "user",
-                "content": prompt
-            }]
-        }))
-        .send()
-        .await?;
+        // Files, their segments and chunks are translated concurrently, each up to `--jobs`,
+        // so the requests are limited where they are sent.
+        Box::new(ConcurrencyLimitTranslator::new(
+            trlt::translator_chain(&config, primary)?,
+            args.jobs,
+        ))
+    };
+    let mut translator: Box<dyn Translator> =
+        Box::new(UsageTranslator::new(provider, usage.clone(), primary));
+    if !args.dry_run && !args.yes {
+        if let Some(limit) = budget_limit(&config)? {
+            translator = Box::new(BudgetTranslator::new(
+                translator,
+                usage.clone(),
+                config.clone(),
+                limit,
+            ));
+        }
+    }
+    if !args.no_placeholders {
+        translator = Box::new(PlaceholderTranslator::new(
+            translator,
+            trlt::DEFAULT_PLACEHOLDER_RETRIES,
+        ));
+    }
+    // A dry run would record its made-up translations.
+    if !args.no_cache && !args.dry_run {
+        translator = Box::new(CachedTranslator::new(
+            translator,
+            Cache::open(),
+            primary,
+            &config,
+        ));
+    }
+    if !args.no_memory && !args.dry_run {
+        match TranslationMemory::open() {
+            Ok(memory) => {
+                translator = Box::new(MemoryTranslator::new(
+                    translator,
+                    memory,
+                    config.model_for(primary),
+                ))
+            }
+            Err(e) => eprintln!("Failed to open the translation memory: {}", e),
+        }
+    }
+    if !args.no_glossary {
+        let glossary = Glossary::load_for(&config)?;
+        if !glossary.terms.is_empty() {
+            translator = Box::new(GlossaryTranslator::new(translator, glossary));
+        }
+    }
+    let prompt = PromptOptions {
+        system_prompt: config.system_prompt.clone(),
+        template: args
+            .template
+            .as_deref()
+            .map(|name| config.template(name))
+            .transpose()?,
+        tone: args.tone,
+        hint: args.context.clone(),
+    };
+    if !prompt.is_empty() {
+        translator = Box::new(PromptTranslator::new(translator, prompt));
+    }
+    let translator = ChunkedTranslator::new(
+        translator,
+        args.chunk_size
+            .or(config.chunk_size)
+            .unwrap_or(trlt::DEFAULT_CHUNK_SIZE),
+        args.jobs,
+    );
+    Ok((translator, config, primary, usage))
+}
+
+async fn translate(args: TranslateArgs, quiet: bool) -> Result<()> {
+    let (mut args, checkpoint) = match &args.resume {
+        Some(id) => {
+            let checkpoint = Checkpoint::open(id)?;
+            std::env::set_current_dir(checkpoint.dir())?;
+            let program = std::iter::once("trlt".to_string());
+            let args = program.chain(checkpoint.args().iter().cloned());
+            let cli = Cli::try_parse_from(with_default_command(args.map(OsString::from)))?;
+            let Command::Translate(args) = cli.command else {
+                return Err(anyhow::anyhow!("The job `{}` is not a translation", id));
+            };
+            (*args, Some(checkpoint))
+        }
+        None => (args, None),
+    };
+    args.resolve_languages()?;
+    let (translator, config, primary, usage) = build_translator(&args)?;
+    args.apply_defaults(&config)?;
+    args.no_clipboard |= config.clipboard == Some(false);
+    if args.dry_run {
+        return dry_run(&args, &translator, &config, &usage).await;
+    }
+    if !args.yes {
+        check_budget(&args, &config).await?;
+    }
+
+    let started = Instant::now();
+    let progress = Arc::new(Progress::new(usage.clone(), config.clone()));
+    let translator = translator.with_progress(progress.clone());
+    let result = run_translation(
+        &args,
+        &translator,
+        &config,
+        primary,
+        checkpoint,
+        &usage,
+        (!quiet).then_some(progress.as_ref()),
+    )
+    .await;
+    if usage.requests() > 0 {
+        if let Err(e) = UsageLog::open().and_then(|log| log.record(&usage, &config)) {
+            eprintln!("Failed to record the usage: {}", e);
+        }
+    }
+    if !quiet {
+        if let Some(summary) = usage.summary(&config) {
+            eprintln!("{}", summary);
+        }
+    }
+    if args.notify {
+        notify_finished(&result, started.elapsed(), usage.summary(&config)).await;
+    }
+    result
+}
+
+/// Show a desktop notification telling how a translation run ended.
+async fn notify_finished(result: &Result<()>, elapsed: Duration, usage: Option<String>) {
+    let (summary, mut body) = match result {
+        Ok(()) => (
+            "Translation finished",
+            format!("Finished in {}s.", elapsed.as_secs()),
+        ),
+        Err(e) => ("Translation failed", format!("{:#}", e)),
+    };
+    if let Some(usage) = usage {
+        body = format!("{}\n{}", body, usage);
+    }
+    let notification = notify_rust::Notification::new()
+        .appname("trlt")
+        .summary(summary)
+        .body(&body)
+        .show_async()
+        .await;
+    if let Err(e) = notification {
+        eprintln!("Failed to show the notification: {}", e);
+    }
+}
 
-    let response_json: serde_json::Value = response.json().await?;
+/// How much the run may cost, as set by the `[budget]` section of the config file.
+fn budget_limit(config: &Config) -> Result<Option<f64>> {
+    match &config.budget {
+        Some(budget) => Ok(budget.remaining(&UsageLog::open()?)?),
+        None => Ok(None),
+    }
+}
 
-    if response_json["error"]["message"].as_str().is_some() {
+/// Refuse to start a run whose estimated cost goes over the budget.
+async fn check_budget(args: &TranslateArgs, config: &Config) -> Result<()> {
+    let Some(limit) = budget_limit(config)? else {
+        return Ok(());
+    };
+    if limit <= 0.0 {
         return Err(anyhow::anyhow!(
-            "Failed to translate text: {}",
-            response_json["error"]["message"]
+            "The budget is used up; run again with --yes to go over it"
         ));
     }
+    // Estimating reads the inputs, so it would consume stdin. Such runs are still stopped
+    // once they use up the budget.
+    if args.jsonl || args.input.iter().any(|input| input == "-") {
+        return Ok(());
+    }
 
-    let response_text = response_json["choices"][0]["message"]["content"]
-        .as_str()
-        .unwrap()
-        .to_string();
+    let estimate = TranslateArgs {
+        dry_run: true,
+        ..args.clone()
+    };
+    let (translator, _, _, usage) = build_translator(&estimate)?;
+    translate_dry(&estimate, &translator).await?;
+    match usage.cost(config) {
+        Some(cost) if cost > limit => Err(anyhow::anyhow!(
+            "The run would cost {}, over the ${:.2} left in the budget; run again with --yes to go ahead",
+            trlt::format_cost(cost),
+            limit
+        )),
+        Some(_) => Ok(()),
+        None => {
+            eprintln!(
+                "The cost of the run cannot be estimated, as the price of the model is not known."
+            );
+            Ok(())
+        }
+    }
+}
 
-    if response_text.is_empty() {
+async fn run_translation(
+    args: &TranslateArgs,
+    translator: &ChunkedTranslator,
+    config: &Config,
+    primary: ProviderKind,
+    checkpoint: Option<Checkpoint>,
+    usage: &Arc<UsageMeter>,
+    progress: Option<&Progress>,
+) -> Result<()> {
+    if args.alternatives.is_some() || args.jsonl {
+        check_overwrite(args, args.out.as_deref())?;
+    }
+    if let Some(count) = args.alternatives {
+        return translate_alternatives(translator, args, count.into()).await;
+    }
+    if args.review && args.input.iter().any(|input| input == "-") {
         return Err(anyhow::anyhow!(
-            "Failed to translate text: Empty response from API"
+            "--review reads answers from stdin, so the input cannot come from stdin"
         ));
     }
+    let jobs = if args.jsonl {
+        Vec::new()
+    } else {
+        plan_jobs(args)?
+    };
+    check_overwrite(
+        args,
+        jobs.iter()
+            .filter(|job| {
+                !checkpoint
+                    .as_ref()
+                    .is_some_and(|c| c.is_completed(&job.key()))
+            })
+            .filter_map(|job| job.output.as_deref()),
+    )?;
+    let batch = jobs.len() > 1 || args.recursive;
+
+    // Batches record their progress so that they can be resumed if they are interrupted.
+    let checkpoint = match checkpoint {
+        Some(checkpoint) => Some(checkpoint),
+        None if batch || args.jsonl => Some(Checkpoint::create(
+            std::env::args().skip(1).collect(),
+            std::env::current_dir()?,
+        )?),
+        None => None,
+    };
+    let checkpointed = checkpoint
+        .as_ref()
+        .map(|checkpoint| CheckpointTranslator::new(translator, checkpoint));
+    let translator: &dyn Translator = match &checkpointed {
+        Some(checkpointed) => checkpointed,
+        None => translator,
+    };
+    let interrupted =
+        |id: &str| eprintln!("Resume this job with `trlt translate --resume {}`.", id);
+
+    if args.jsonl {
+        let (failures, total) = tokio::select! {
+            failures = translate_batch(translator, args) => failures?,
+            _ = tokio::signal::ctrl_c() => {
+                if let Some(checkpoint) = &checkpoint {
+                    interrupted(checkpoint.id());
+                }
+                return Err(anyhow::anyhow!("Interrupted"));
+            }
+        };
+        if let Some(checkpoint) = checkpoint {
+            match failures {
+                0 => checkpoint.remove()?,
+                _ => interrupted(checkpoint.id()),
+            }
+        }
+        if failures > 0 {
+            return Err(PartialFailure {
+                failures,
+                total,
+                unit: "requests",
+            }
+            .into());
+        }
+        return Ok(());
+    }
 
-    if let Some(output_path) = output {
-        let path = Path::new(output_path);
-        fs::write(path, response_text.clone())?;
+    let scorer = if args.score {
+        let kind = args.score_provider.unwrap_or(primary);
+        if !kind.is_language_model() {
+            return Err(anyhow::anyhow!(
+                "{} cannot score translations; choose a language model with --score-provider",
+                kind
+            ));
+        }
+        let mut scorer_config = config.clone();
+        if let Some(model) = &args.score_model {
+            scorer_config.set_model(kind, model.clone())?;
+        }
+        let scorer = trlt::translator(&scorer_config, kind)?;
+        Some(Box::new(UsageTranslator::new(scorer, usage.clone(), kind)) as Box<dyn Translator>)
     } else {
-        println!("{}", response_text);
+        None
+    };
+
+    let incremental = incremental_settings(args, config, primary);
+    let completed = jobs
+        .iter()
+        .filter(|job| {
+            checkpoint
+                .as_ref()
+                .is_some_and(|c| c.is_completed(&job.key()))
+        })
+        .count();
+    if completed > 0 {
+        eprintln!(
+            "Skipping {} inputs translated before the interruption.",
+            completed
+        );
     }
+    let jobs = jobs
+        .iter()
+        .filter(|job| {
+            !checkpoint
+                .as_ref()
+                .is_some_and(|c| c.is_completed(&job.key()))
+        })
+        .collect::<Vec<_>>();
 
-    if let Ok(mut ctx) = ClipboardContext::new() {
-        if let Err(e) = ctx.set_contents(response_text.to_string()) {
-            eprintln!("Failed to copy to clipboard: {:?}", e);
+    let history = if args.no_history {
+        None
+    } else {
+        Some(TranslationHistory::open()?)
+    };
+
+    // The bar would get in the way of reviews and of translations streamed to the terminal,
+    // which only get a spinner until they start.
+    if let (Some(progress), false) = (progress, args.review) {
+        if batch {
+            progress.start_inputs(jobs.len());
+        } else if streams_to_terminal(args, &jobs) {
+            progress.start_waiting();
         } else {
-            println!("\nOutput copied to clipboard.");
+            progress.start_chunks(io::stdout().is_terminal());
+        }
+    }
+
+    // Reviews are interactive, so files are reviewed one at a time.
+    let semaphore = Semaphore::new(if args.review { 1 } else { args.jobs.max(1) });
+    let run = join_all(jobs.iter().map(|job| async {
+        let _permit = semaphore.acquire().await?;
+        let started = Instant::now();
+        let translated = run_job(
+            job,
+            translator,
+            args,
+            batch,
+            primary,
+            scorer.as_deref(),
+            incremental.as_deref(),
+        )
+        .await;
+        if let Some(progress) = progress {
+            progress.input_done(translated.is_err());
+        }
+        let translated = translated?;
+        if let Some(checkpoint) = &checkpoint {
+            checkpoint.complete(&job.key())?;
+        }
+        let Some(translated) = translated else {
+            return Ok(Vec::new());
+        };
+        if let Some(history) = &history {
+            let name = job.input.path().map(|path| path.display().to_string());
+            if let Err(e) = history.record(
+                name.as_deref(),
+                &translated.request,
+                &translated.translation,
+                primary,
+                config,
+            ) {
+                trlt::suspend_progress(|| {
+                    eprintln!("Failed to record the translation in the history: {}", e)
+                });
+            }
+        }
+        if args.format == OutputFormat::Json {
+            print_json(job, &translated, started.elapsed(), primary, config)?;
+        }
+        Ok(translated.scores)
+    }));
+    let results: Vec<Result<_>> = tokio::select! {
+        results = run => results,
+        _ = tokio::signal::ctrl_c() => {
+            if let Some(progress) = progress {
+                progress.finish();
+            }
+            if let Some(checkpoint) = &checkpoint {
+                interrupted(checkpoint.id());
+            }
+            return Err(anyhow::anyhow!("Interrupted"));
+        }
+    };
+    if let Some(progress) = progress {
+        progress.finish();
+    }
+
+    let mut failures = 0;
+    let mut first_failure = None;
+    let mut report = Vec::new();
+    for (job, result) in jobs.iter().copied().zip(results) {
+        match result {
+            Ok(scores) => report.extend(scores.into_iter().map(|score| (job, score))),
+            Err(e) if batch => {
+                failures += 1;
+                eprintln!("Failed to translate {}: {:#}", job.name(), e);
+                first_failure.get_or_insert(e);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    if args.score {
+        report_scores(&report, args)?;
+    }
+
+    if failures > 0 {
+        if let Some(checkpoint) = &checkpoint {
+            interrupted(checkpoint.id());
+        }
+        let failure = PartialFailure {
+            failures,
+            total: jobs.len(),
+            unit: "inputs",
+        };
+        // When every input failed, the cause of the first failure tells why.
+        return Err(match first_failure {
+            Some(e) if failures == jobs.len() => e.context(failure.to_string()),
+            _ => failure.into(),
+        });
+    }
+    if let Some(checkpoint) = checkpoint {
+        checkpoint.remove()?;
+    }
+
+    Ok(())
+}
+
+/// The settings that `--incremental` records along with the translated segments, as they
+/// affect the translations without being part of the requests.
+fn incremental_settings(
+    args: &TranslateArgs,
+    config: &Config,
+    primary: ProviderKind,
+) -> Option<String> {
+    args.incremental.then(|| {
+        format!(
+            "{:?}",
+            (
+                primary,
+                config.model_for(primary),
+                &config.system_prompt,
+                &config.generation,
+                &args.template,
+                args.tone,
+                &args.context,
+                args.no_glossary,
+            )
+        )
+    })
+}
+
+/// Split `text` into the segments to translate, as set by the options of `args`.
+fn parse_document(
+    args: &TranslateArgs,
+    mode: DocumentKind,
+    text: &str,
+    to: &str,
+) -> Result<Document> {
+    let options = DocumentOptions {
+        to: to.to_string(),
+        from: args.from.clone(),
+        escape_unicode: args.escape_unicode,
+        column: args.column.clone(),
+        front_matter_fields: args.front_matter_fields.clone(),
+    };
+    let mut document = match mode {
+        DocumentKind::Text => Document::paragraphs(text),
+        mode => mode.parse(text, &options)?,
+    };
+    if let Some(context) = args.segment_context {
+        document.context = context;
+    }
+    Ok(document)
+}
+
+/// Go through the inputs as a run would, with the translator of a dry run.
+async fn translate_dry(args: &TranslateArgs, translator: &dyn Translator) -> Result<()> {
+    for job in plan_jobs(args)? {
+        if job.copy {
+            continue;
+        }
+        let request = TranslationRequest::new(String::new(), args.from.clone(), job.to.clone());
+        let mode = args.mode_for(job.input.path());
+        if mode.is_archive() {
+            let path = job.input.path().ok_or_else(|| {
+                anyhow::anyhow!("{:?} documents can only be translated from files", mode)
+            })?;
+            // Archives are only translated from file to file, so write to a scratch file.
+            let scratch = std::env::temp_dir().join(format!("trlt-dry-run-{}", std::process::id()));
+            let result = mode
+                .translate_file(path, &scratch, translator, &request, args.jobs)
+                .await;
+            let _ = fs::remove_file(&scratch);
+            result?;
+            continue;
+        }
+
+        let request = TranslationRequest {
+            text: job.input.read()?,
+            ..request
+        };
+        match mode {
+            DocumentKind::Text => {
+                translator.translate(&request).await?;
+            }
+            mode => {
+                let document = parse_document(args, mode, &request.text, &job.to)?;
+                trlt::translate_segments(&document, translator, &request, args.jobs).await?;
+            }
         }
     }
+    Ok(())
+}
+
+/// Tell how many requests the run would send and what they would cost, without calling the
+/// provider or writing anything.
+async fn dry_run(
+    args: &TranslateArgs,
+    translator: &dyn Translator,
+    config: &Config,
+    usage: &UsageMeter,
+) -> Result<()> {
+    translate_dry(args, translator).await?;
 
+    let totals = usage.totals();
+    let prompt: u64 = totals.iter().map(|usage| usage.prompt_tokens).sum();
+    let completion: u64 = totals.iter().map(|usage| usage.completion_tokens).sum();
+    let cost = match usage.cost(config) {
+        Some(cost) => trlt::format_cost(cost),
+        None => "an unknown cost, as the price of the model is not known".to_string(),
+    };
+    let requests = usage.requests();
+    println!(
+        "Would send {} request{} with about {} prompt and {} completion tokens, for {}.",
+        requests,
+        if requests == 1 { "" } else { "s" },
+        prompt,
+        completion,
+        cost
+    );
     Ok(())
 }
+
+async fn run_job(
+    job: &Job,
+    translator: &dyn Translator,
+    args: &TranslateArgs,
+    batch: bool,
+    primary: ProviderKind,
+    scorer: Option<&dyn Translator>,
+    incremental: Option<&str>,
+) -> Result<Option<Translated>> {
+    let Job {
+        input,
+        to,
+        output,
+        copy,
+    } = job;
+
+    if let Some(parent) = output.as_deref().and_then(Path::parent) {
+        fs::create_dir_all(parent)?;
+    }
+    if let (true, Some(path), Some(output)) = (*copy, input.path(), output) {
+        trlt::write_atomic_with(output, |file| {
+            io::copy(&mut fs::File::open(path)?, file)?;
+            Ok(())
+        })?;
+        return Ok(None);
+    }
+
+    let incremental = match (incremental, output) {
+        (Some(settings), Some(output)) => Some(IncrementalTranslator::open(
+            translator,
+            trlt::incremental_state_path(output),
+            settings,
+        )?),
+        (Some(_), None) => {
+            return Err(anyhow::anyhow!(
+                "--incremental records the translations next to the output, which must be a file"
+            ))
+        }
+        (None, _) => None,
+    };
+    let translator: &dyn Translator = match &incremental {
+        Some(incremental) => incremental,
+        None => translator,
+    };
+
+    let mode = args.mode_for(input.path());
+    if mode.is_archive() {
+        let path = input.path().ok_or_else(|| {
+            anyhow::anyhow!("{:?} documents can only be translated from files", mode)
+        })?;
+        let output = output.as_ref().ok_or_else(|| {
+            anyhow::anyhow!("{:?} documents can only be translated to files", mode)
+        })?;
+        let request = TranslationRequest::new(String::new(), args.from.clone(), to.clone());
+        mode.translate_file(path, output, translator, &request, args.jobs)
+            .await
+            .with_context(|| format!("Failed to translate {}", path.display()))?;
+        trlt::suspend_progress(|| eprintln!("{} -> {}", path.display(), output.display()));
+        if let Some(incremental) = &incremental {
+            save_incremental(incremental, job)?;
+        }
+        if args.verify || args.score {
+            trlt::suspend_progress(|| {
+                eprintln!("{:?} documents cannot be verified or scored yet.", mode)
+            });
+        }
+        return Ok(None);
+    }
+
+    let text = input.read()?;
+    let request = TranslationRequest::new(text, args.from.clone(), to.clone());
+    // Concurrent jobs would interleave their output, so only stream single translations.
+    let print = match args.format {
+        OutputFormat::Json => Print::Nothing,
+        OutputFormat::Text if output.is_some() && !args.tee => Print::Nothing,
+        OutputFormat::Text if !batch && !args.no_stream => Print::Stream,
+        OutputFormat::Text => Print::Whole,
+    };
+    let label = (args.to.len() > 1).then_some(to.as_str());
+    let (translation, pairs) = match mode {
+        DocumentKind::Text if !args.review && incremental.is_none() => {
+            let translation =
+                translate_one(translator, &request, output.as_deref(), print, label).await?;
+            let pairs = trlt::paragraph_pairs(&request.text, &translation.text);
+            (translation, pairs)
+        }
+        mode => {
+            let document = parse_document(args, mode, &request.text, to)?;
+            let mut translations =
+                trlt::translate_segments(&document, translator, &request, args.jobs)
+                    .await
+                    .context("Failed to translate text")?;
+            if args.review {
+                translations =
+                    trlt::review_segments(&document, translations, translator, &request).await?;
+            }
+            let text = document.render(&translations)?;
+            let print = match print {
+                Print::Stream => Print::Whole,
+                print => print,
+            };
+            write_translation(&text, output.as_deref(), label, print)?;
+            let pairs = document
+                .segments()
+                .zip(translations)
+                .filter(|(segment, _)| segment.needs_translation())
+                .map(|(segment, translation)| (segment.source.text.clone(), translation))
+                .collect();
+            (
+                Translation {
+                    text,
+                    ..Default::default()
+                },
+                pairs,
+            )
+        }
+    };
+
+    if let Some(incremental) = &incremental {
+        save_incremental(incremental, job)?;
+    }
+
+    let scores = match scorer {
+        Some(scorer) => trlt::score_translations(
+            &pairs,
+            scorer,
+            request.from.as_deref(),
+            &request.to,
+            args.jobs,
+        )
+        .await
+        .context("Failed to score the translation")?,
+        None => Vec::new(),
+    };
+    if args.show_diff {
+        let mut view = trlt::side_by_side(&pairs, terminal_width());
+        if batch {
+            view = format!(
+                "{HEADING_STYLE}{} ({}){HEADING_STYLE:#}\n{}",
+                job.name(),
+                to,
+                view
+            );
+        }
+        trlt::suspend_progress(|| anstream::eprintln!("{}", view));
+    }
+    if args.verify {
+        verify(translator, args, &request, pairs).await?;
+    }
+
+    trlt::suspend_progress(|| {
+        if let Some(used) = translation.provider.filter(|used| *used != primary) {
+            eprintln!("Translated with fallback provider {}.", used);
+        }
+        if let (true, Some(output)) = (batch, output) {
+            eprintln!("{} -> {}", job.name(), output.display());
+        }
+    });
+    if !batch && !args.no_clipboard {
+        trlt::suspend_progress(|| copy_to_clipboard(&translation.text));
+    }
+
+    Ok(Some(Translated {
+        request,
+        translation,
+        scores,
+    }))
+}
+
+/// How often `watch` checks its inputs for changes.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Translate the inputs, then translate them again whenever they change until interrupted.
+async fn watch(mut args: TranslateArgs) -> Result<()> {
+    args.resolve_languages()?;
+    if args.jsonl || args.alternatives.is_some() || args.review || args.score {
+        return Err(anyhow::anyhow!(
+            "--jsonl, --alternatives, --review and --score cannot be used with watch"
+        ));
+    }
+    let (translator, config, primary, _) = build_translator(&args)?;
+    args.apply_defaults(&config)?;
+    let incremental = incremental_settings(&args, &config, primary);
+    let template = args
+        .name_template
+        .as_deref()
+        .unwrap_or(trlt::DEFAULT_NAME_TEMPLATE);
+
+    let mut modified = HashMap::new();
+    eprintln!("Watching for changes. Press Ctrl+C to stop.");
+    loop {
+        // Planned again every time so that new files matching a glob pattern are picked up.
+        let mut jobs = plan_jobs(&args)?;
+        for job in &mut jobs {
+            let Some(path) = job.input.path() else {
+                return Err(anyhow::anyhow!("watch only translates files"));
+            };
+            // Translations are kept in files next to their source rather than printed.
+            if job.output.is_none() {
+                job.output = Some(trlt::batch_output_path(path, &job.to, None, Some(template)));
+            }
+        }
+        // Outputs matching an input pattern would otherwise be translated again.
+        let outputs = jobs
+            .iter()
+            .filter_map(|job| job.output.clone())
+            .collect::<HashSet<_>>();
+
+        let mut changed = HashMap::new();
+        for job in &jobs {
+            let path = job.input.path().unwrap_or(Path::new(""));
+            if outputs.contains(path) {
+                continue;
+            }
+            let Ok(time) = fs::metadata(path).and_then(|metadata| metadata.modified()) else {
+                continue;
+            };
+            if modified.get(path) != Some(&time) {
+                changed.insert(path.to_path_buf(), time);
+            }
+        }
+
+        let semaphore = Semaphore::new(args.jobs.max(1));
+        let pending = jobs
+            .iter()
+            .filter(|job| {
+                job.input
+                    .path()
+                    .is_some_and(|path| changed.contains_key(path))
+            })
+            .collect::<Vec<_>>();
+        let results = join_all(pending.iter().map(|job| async {
+            let _permit = semaphore.acquire().await?;
+            run_job(
+                job,
+                &translator,
+                &args,
+                true,
+                primary,
+                None,
+                incremental.as_deref(),
+            )
+            .await
+        }))
+        .await;
+        for (job, result) in pending.iter().zip(results) {
+            if let Err(e) = result {
+                eprintln!("Failed to translate {}: {}", job.name(), e);
+                // Files that failed are translated again on the next check.
+                if let Some(path) = job.input.path() {
+                    changed.remove(path);
+                }
+            }
+        }
+        modified.extend(changed);
+
+        tokio::select! {
+            _ = tokio::time::sleep(WATCH_INTERVAL) => {}
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+    }
+}
+
+/// Translate the text copied to the clipboard whenever it changes and is not in the target
+/// language, copying the translation back or printing it.
+async fn watch_clipboard(args: SessionArgs, print: bool) -> Result<()> {
+    let (config, settings) = session_config(args)?;
+    let provider = settings.provider;
+    let translator = session_translator(&config, provider)?;
+    let history = TranslationHistory::open()?;
+    let mut clipboard = ClipboardContext::new()
+        .map_err(|e| anyhow::anyhow!("Failed to open the clipboard: {}", e))?;
+
+    // Neither what was copied before watching started nor the translations copied back are
+    // translated.
+    let mut last = clipboard.get_contents().unwrap_or_default();
+    eprintln!("Watching the clipboard. Press Ctrl+C to stop.");
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(WATCH_INTERVAL) => {}
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+        let Ok(text) = clipboard.get_contents() else {
+            continue;
+        };
+        if text == last || text.trim().is_empty() {
+            continue;
+        }
+        last = text.clone();
+        if trlt::is_written_in(&text, &settings.to) {
+            continue;
+        }
+
+        let request = TranslationRequest {
+            tone: settings.tone,
+            ..TranslationRequest::new(text, settings.from.clone(), settings.to.clone())
+        };
+        let translation = match translator.translate(&request).await {
+            Ok(translation) => translation,
+            Err(e) => {
+                eprintln!("Failed to translate: {}", e);
+                continue;
+            }
+        };
+        if let Err(e) = history.record(None, &request, &translation, provider, &config) {
+            eprintln!("Failed to record the translation in the history: {}", e);
+        }
+        if print {
+            println!("{}\n", translation.text.trim_end());
+            continue;
+        }
+        match clipboard.set_contents(translation.text.clone()) {
+            Ok(()) => {
+                eprintln!("Replaced the copied text with its translation.");
+                last = translation.text;
+            }
+            Err(e) => eprintln!("Failed to copy to clipboard: {:?}", e),
+        }
+    }
+}
+
+/// Print the translation of `job` as a line of JSON, for `--format json`.
+fn print_json(
+    job: &Job,
+    translated: &Translated,
+    duration: Duration,
+    primary: ProviderKind,
+    config: &Config,
+) -> Result<()> {
+    let translation = &translated.translation;
+    let line = serde_json::json!({
+        "input": job.input.path(),
+        "to": job.to,
+        "translation": translation.text,
+        "detected_source": translation.detected_source,
+        "model": translation.model(primary, config),
+        "tokens": translation.usage.as_ref().map(|usage| serde_json::json!({
+            "prompt": usage.prompt_tokens,
+            "completion": usage.completion_tokens,
+        })),
+        "duration_ms": duration.as_millis() as u64,
+    });
+    let line = serde_json::to_string(&line)?;
+    trlt::suspend_progress(|| writeln!(io::stdout().lock(), "{}", line))?;
+    Ok(())
+}
+
+/// The width of the terminal, from `COLUMNS` or the terminal itself, or 80 columns when it
+/// is unknown.
+fn terminal_width() -> usize {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .or_else(|| {
+            ratatui::crossterm::terminal::size()
+                .ok()
+                .map(|(columns, _)| columns.into())
+        })
+        .unwrap_or(trlt::MIN_SIDE_BY_SIDE_WIDTH)
+}
+
+/// Whether the translation of the single job in `jobs` is streamed to a terminal, where a
+/// progress bar would mix with it.
+fn streams_to_terminal(args: &TranslateArgs, jobs: &[&Job]) -> bool {
+    args.format == OutputFormat::Text
+        && !args.no_stream
+        && jobs.iter().any(|job| job.output.is_none() || args.tee)
+        && io::stdout().is_terminal()
+}
+
+/// Record the segments translated for `job`, and report how many were reused.
+fn save_incremental(incremental: &IncrementalTranslator, job: &Job) -> Result<()> {
+    incremental.save()?;
+    let (reused, total) = incremental.stats();
+    trlt::suspend_progress(|| {
+        eprintln!(
+            "{} ({}): reused {} of {} segments, translated {}.",
+            job.name(),
+            job.to,
+            reused,
+            total,
+            total - reused
+        )
+    });
+    Ok(())
+}
+
+/// Report the segments scoring `--score-threshold` or less, and write every score to
+/// `--score-report`.
+fn report_scores(report: &[(&Job, QualityScore)], args: &TranslateArgs) -> Result<()> {
+    let flagged = report
+        .iter()
+        .filter(|(_, score)| score.score <= args.score_threshold)
+        .collect::<Vec<_>>();
+    let average = report
+        .iter()
+        .map(|(_, score)| f64::from(score.score))
+        .sum::<f64>()
+        / report.len().max(1) as f64;
+    anstream::eprintln!(
+        "{HEADING_STYLE}Scored {} segments: {:.1} on average, {} to review.{HEADING_STYLE:#}",
+        report.len(),
+        average,
+        flagged.len()
+    );
+    for (job, score) in flagged {
+        anstream::eprintln!(
+            "  {WARNING_STYLE}[{}/5]{WARNING_STYLE:#} {} ({}): {}\n        {DIMMED_STYLE}{}{DIMMED_STYLE:#}",
+            score.score,
+            job.name(),
+            job.to,
+            score.source.trim(),
+            score.reason
+        );
+    }
+
+    if let Some(path) = &args.score_report {
+        let entries = report
+            .iter()
+            .map(|(job, score)| {
+                let mut entry = serde_json::to_value(score)?;
+                entry["input"] = job.name().into();
+                entry["to"] = job.to.clone().into();
+                Ok(entry)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        trlt::write_atomic(path, serde_json::to_string_pretty(&entries)? + "\n")?;
+    }
+    Ok(())
+}
+
+/// Translate the `(source, translation)` pairs back into the source language and report the
+/// ones that diverge from their source.
+async fn verify(
+    translator: &dyn Translator,
+    args: &TranslateArgs,
+    request: &TranslationRequest,
+    pairs: Vec<(String, String)>,
+) -> Result<()> {
+    let from = match &request.from {
+        Some(from) => from.clone(),
+        None => trlt::detect_offline(&request.text)
+            .map(|detection| detection.language)
+            .ok_or_else(|| {
+                anyhow::anyhow!("Unable to detect the source language to verify; use --from")
+            })?,
+    };
+    let verifications = trlt::back_translate(pairs, translator, &from, &request.to, args.jobs)
+        .await
+        .context("Failed to back-translate")?;
+
+    let diverging = verifications
+        .iter()
+        .filter(|verification| verification.diverges(args.verify_threshold))
+        .collect::<Vec<_>>();
+    trlt::suspend_progress(|| {
+        anstream::eprintln!(
+            "{HEADING_STYLE}Verified {} segments into {}: {} diverge from their back-translation.{HEADING_STYLE:#}",
+            verifications.len(),
+            request.to,
+            diverging.len()
+        );
+        for verification in diverging {
+            anstream::eprintln!(
+                "  {WARNING_STYLE}[{:.0}%]{WARNING_STYLE:#} {}\n        {DELETED_STYLE}back: {}{DELETED_STYLE:#}",
+                verification.similarity * 100.0,
+                verification.source.trim(),
+                verification.back_translation.trim()
+            );
+        }
+    });
+    Ok(())
+}
+
+/// Translate a single input into a single language, giving `count` candidate translations.
+async fn translate_alternatives(
+    translator: &dyn Translator,
+    args: &TranslateArgs,
+    count: usize,
+) -> Result<()> {
+    let ([input], [to]) = (args.input.as_slice(), args.to.as_slice()) else {
+        return Err(anyhow::anyhow!(
+            "--alternatives takes a single input and a single target language"
+        ));
+    };
+    let text = Input::parse(input).read()?;
+    let request = TranslationRequest::new(text, args.from.clone(), to.clone());
+    let alternatives = translator.alternatives(&request, count).await?;
+
+    let output = match &args.out {
+        Some(path)
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "json") =>
+        {
+            serde_json::to_string_pretty(&alternatives)? + "\n"
+        }
+        _ => alternatives
+            .iter()
+            .enumerate()
+            .map(|(index, alternative)| format!("{}. {}\n", index + 1, alternative.trim()))
+            .collect(),
+    };
+    if let Some(path) = &args.out {
+        trlt::write_atomic(path, &output)?;
+    }
+    if args.out.is_none() || args.tee {
+        print!("{}", output);
+    }
+    Ok(())
+}
+
+/// Translate the `--jsonl` batches, returning how many requests failed and how many there were.
+async fn translate_batch(
+    translator: &dyn Translator,
+    args: &TranslateArgs,
+) -> Result<(usize, usize)> {
+    let mut requests = Vec::new();
+    for input in trlt::expand_inputs(&args.input)? {
+        requests.extend(trlt::parse_batch(&input.read()?)?);
+    }
+
+    let results = trlt::translate_batch(
+        &requests,
+        translator,
+        &args.to[0],
+        args.from.as_deref(),
+        args.jobs,
+    )
+    .await;
+
+    let mut output = String::new();
+    for result in &results {
+        output.push_str(&serde_json::to_string(result)?);
+        output.push('\n');
+    }
+    if let Some(path) = &args.out {
+        trlt::write_atomic(path, &output)?;
+    }
+    if args.out.is_none() || args.tee {
+        print!("{}", output);
+    }
+
+    let failures = results
+        .iter()
+        .filter(|result| result.error.is_some())
+        .count();
+
+    Ok((failures, results.len()))
+}
+
+/// Refuse to overwrite any of `outputs` that already exists, unless `--force` is given or the
+/// user agrees to it on the terminal.
+fn check_overwrite<'a>(
+    args: &TranslateArgs,
+    outputs: impl IntoIterator<Item = &'a Path>,
+) -> Result<()> {
+    if args.force || args.incremental {
+        return Ok(());
+    }
+    let existing = outputs
+        .into_iter()
+        .filter(|path| path.exists())
+        .collect::<BTreeSet<_>>();
+    let (count, first) = match existing.first() {
+        Some(first) => (existing.len(), first.display()),
+        None => return Ok(()),
+    };
+    let (what, them) = match count {
+        1 => (format!("{} already exists", first), "it"),
+        _ => (
+            format!(
+                "{} and {} other output files already exist",
+                first,
+                count - 1
+            ),
+            "them",
+        ),
+    };
+
+    // Answers are read from stdin, which must not be an input.
+    let interactive = io::stdin().is_terminal()
+        && io::stderr().is_terminal()
+        && !args.input.iter().any(|input| input == "-");
+    if interactive {
+        eprint!("{}. Overwrite? [y/N] ", what);
+        io::stderr().flush()?;
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        if matches!(answer.trim(), "y" | "Y" | "yes") {
+            return Ok(());
+        }
+    }
+    Err(anyhow::anyhow!(
+        "{}; run again with --force to overwrite {}",
+        what,
+        them
+    ))
+}
+
+/// Resolve the command-line inputs into the list of translations to perform.
+fn plan_jobs(args: &TranslateArgs) -> Result<Vec<Job>> {
+    let multilingual = args.to.len() > 1;
+    let template = args
+        .name_template
+        .as_deref()
+        .or(multilingual.then_some(trlt::DEFAULT_NAME_TEMPLATE));
+    let mut jobs = Vec::new();
+
+    if args.recursive {
+        let out_dir = args.out.as_deref().unwrap_or(Path::new("."));
+        for root in &args.input {
+            let root = Path::new(root);
+            if !root.is_dir() {
+                return Err(anyhow::anyhow!("{} is not a directory", root.display()));
+            }
+            for path in trlt::walk_dir(root)? {
+                let relative = path.strip_prefix(root)?;
+                for to in &args.to {
+                    let out_dir = if multilingual {
+                        out_dir.join(to)
+                    } else {
+                        out_dir.to_path_buf()
+                    };
+                    jobs.push(Job {
+                        copy: !trlt::is_translatable(&path),
+                        input: Input::File(path.clone()),
+                        to: to.clone(),
+                        output: Some(if trlt::is_pdf(&path) {
+                            out_dir
+                                .join(relative)
+                                .with_extension(trlt::TEXT_LAYER_EXTENSION)
+                        } else {
+                            out_dir.join(relative)
+                        }),
+                    });
+                }
+            }
+        }
+        return Ok(jobs);
+    }
+
+    let mut inputs = trlt::expand_inputs(&args.input)?;
+    if args.clipboard {
+        inputs.insert(0, Input::Clipboard);
+    }
+    let batch = inputs.len() > 1;
+    for input in inputs {
+        // Stdin can only be read once, but may be translated into several languages. The
+        // clipboard is read once too, as the translations are copied to it.
+        let input = match input {
+            Input::Stdin | Input::Clipboard => Input::Text(input.read()?),
+            input => input,
+        };
+        for to in &args.to {
+            let android = args.mode_for(input.path()) == DocumentKind::Android;
+            let output =
+                match (input.path(), &args.out) {
+                    // Android resources go to the `values-xx` directory the app loads them from.
+                    (Some(path), None) if android => Some(trlt::android_output_path(path, to)),
+                    (Some(path), out) if batch => {
+                        Some(trlt::batch_output_path(path, to, out.as_deref(), template))
+                    }
+                    (_, _) if batch => None,
+                    (_, Some(out)) if multilingual => Some(out.with_file_name(
+                        trlt::output_file_name(out, to, template.unwrap_or_default()),
+                    )),
+                    // Documents such as DOCX cannot be printed, so they go next to their input.
+                    (Some(path), None) if args.mode_for(Some(path)).is_archive() => {
+                        Some(path.with_file_name(trlt::output_file_name(
+                            path,
+                            to,
+                            trlt::DEFAULT_NAME_TEMPLATE,
+                        )))
+                    }
+                    (_, out) => out.clone(),
+                };
+            jobs.push(Job {
+                input: input.clone(),
+                to: to.clone(),
+                output,
+                copy: false,
+            });
+        }
+    }
+
+    Ok(jobs)
+}
+
+/// Translate a single request, writing the result to `output` or stdout.
+async fn translate_one(
+    translator: &dyn Translator,
+    request: &TranslationRequest,
+    output: Option<&Path>,
+    print: Print,
+    label: Option<&str>,
+) -> Result<Translation> {
+    let stream = print == Print::Stream;
+    let translation = if stream {
+        let mut stdout = io::stdout();
+        let mut started = false;
+        let translation = translator
+            .translate_stream(request, &mut |chunk| {
+                if !started && !chunk.is_empty() {
+                    trlt::clear_progress();
+                    started = true;
+                }
+                print!("{}", chunk);
+                let _ = stdout.flush();
+            })
+            .await;
+        println!();
+        translation
+    } else {
+        translator.translate(request).await
+    }
+    .context("Failed to translate text")?;
+
+    let print = if stream { Print::Nothing } else { print };
+    write_translation(&translation.text, output, label, print)?;
+    Ok(translation)
+}
+
+/// Write a translation to `output`, if any, and to stdout unless it was already streamed there.
+fn write_translation(
+    text: &str,
+    output: Option<&Path>,
+    label: Option<&str>,
+    print: Print,
+) -> Result<()> {
+    if let Some(path) = output {
+        trlt::write_atomic(path, text)?;
+    }
+    if print != Print::Nothing {
+        trlt::suspend_progress(|| match label {
+            Some(label) => {
+                anstream::println!("{HEADING_STYLE}[{}]{HEADING_STYLE:#}\n{}\n", label, text)
+            }
+            None => println!("{}", text),
+        });
+    }
+    Ok(())
+}
+
+async fn detect(input: &str, offline: bool, provider: Option<ProviderKind>) -> Result<()> {
+    let text = Input::parse(input).read()?;
+
+    let detection = if offline {
+        trlt::detect_offline(&text)
+            .ok_or_else(|| anyhow::anyhow!("Unable to detect the language of the input"))?
+    } else {
+        let config = read_config()?;
+        let translator = trlt::translator_chain(&config, provider.unwrap_or(config.provider))?;
+        translator
+            .detect(&text)
+            .await
+            .context("Failed to detect the language")?
+    };
+
+    match detection.confidence {
+        Some(confidence) => println!(
+            "{} ({:.0}% confidence)",
+            detection.language,
+            confidence * 100.0
+        ),
+        None => println!("{}", detection.language),
+    }
+    Ok(())
+}
+
+const REPL_HELP: &str = "Commands:
+  :to LANG          translate to LANG
+  :from LANG|auto   translate from LANG, or detect the source language
+  :provider NAME    switch to another provider
+  :model NAME       switch to another model of the provider
+  :tone TONE|none   use a formal, informal, neutral, technical or casual register
+  :help             show this help
+  :quit             end the session";
+
+/// Translate each line read from stdin until it ends or `:quit` is typed.
+async fn repl(args: SessionArgs) -> Result<()> {
+    let (
+        mut config,
+        SessionSettings {
+            mut from,
+            mut to,
+            mut provider,
+            mut tone,
+        },
+    ) = session_config(args)?;
+    let mut translator = session_translator(&config, provider)?;
+    let history = TranslationHistory::open()?;
+
+    eprintln!("Type a line to translate it, or :help for commands.");
+    let mut stdin = io::stdin().lock();
+    let mut stdout = io::stdout();
+    loop {
+        eprint!(
+            "{}{}> ",
+            from.as_deref()
+                .map(|from| format!("{}→", from))
+                .unwrap_or_default(),
+            to
+        );
+        io::stderr().flush()?;
+        let mut line = String::new();
+        if stdin.read_line(&mut line)? == 0 {
+            eprintln!();
+            return Ok(());
+        }
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let Some(command) = line.strip_prefix(':') else {
+            let request = TranslationRequest {
+                tone,
+                ..TranslationRequest::new(line, from.clone(), to.clone())
+            };
+            let result = translator
+                .translate_stream(&request, &mut |chunk| {
+                    print!("{}", chunk);
+                    let _ = stdout.flush();
+                })
+                .await;
+            println!();
+            match result {
+                Ok(translation) => {
+                    if let Err(e) = history.record(None, &request, &translation, provider, &config)
+                    {
+                        eprintln!("Failed to record the translation in the history: {}", e);
+                    }
+                }
+                Err(e) => eprintln!("Failed to translate: {}", e),
+            }
+            continue;
+        };
+
+        let (command, value) = command.split_once(' ').unwrap_or((command, ""));
+        let value = value.trim();
+        let result = match (command, value) {
+            ("q" | "quit" | "exit", _) => return Ok(()),
+            ("help", _) => {
+                eprintln!("{}", REPL_HELP);
+                Ok(())
+            }
+            ("to", language) if !language.is_empty() => {
+                trlt::resolve_language(language).map(|language| to = language)
+            }
+            ("from", "auto") => {
+                from = None;
+                Ok(())
+            }
+            ("from", language) if !language.is_empty() => {
+                trlt::resolve_language(language).map(|language| from = Some(language))
+            }
+            ("tone", "none") => {
+                tone = None;
+                Ok(())
+            }
+            ("tone", name) if !name.is_empty() => Tone::from_str(name, true)
+                .map(|value| tone = Some(value))
+                .map_err(trlt::Error::Custom),
+            ("provider", name) if !name.is_empty() => ProviderKind::from_str(name, true)
+                .map_err(trlt::Error::Custom)
+                .and_then(|kind| {
+                    translator = session_translator(&config, kind)?;
+                    provider = kind;
+                    Ok(())
+                }),
+            ("model", name) if !name.is_empty() => {
+                let mut updated = config.clone();
+                updated
+                    .set_model(provider, name.to_string())
+                    .and_then(|()| {
+                        translator = session_translator(&updated, provider)?;
+                        config = updated;
+                        Ok(())
+                    })
+            }
+            _ => Err(trlt::Error::Custom(format!(
+                "Unknown command `:{}`. Type :help for commands.",
+                line.trim_start_matches(':')
+            ))),
+        };
+        if let Err(e) = result {
+            eprintln!("{}", e);
+        }
+    }
+}
+
+async fn tui(args: SessionArgs) -> Result<()> {
+    let (config, settings) = session_config(args)?;
+    trlt::run_tui(config, settings, session_translator).await?;
+    Ok(())
+}
+
+/// The language of a locale file named after it, e.g. `pt` for `pt.json`.
+fn language_of(path: &Path) -> Option<String> {
+    let stem = path.file_stem()?.to_str()?;
+    trlt::validate_language(stem).ok()?;
+    Some(stem.to_string())
+}
+
+async fn sync(
+    source: &Path,
+    target: &Path,
+    from: Option<String>,
+    to: Option<String>,
+    provider: Option<ProviderKind>,
+    model: Option<String>,
+    jobs: usize,
+) -> Result<()> {
+    let from = from.or_else(|| language_of(source));
+    let to = to.or_else(|| language_of(target)).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Unable to tell the language of {} from its name; use --to",
+            target.display()
+        )
+    })?;
+    let from = from.as_deref().map(trlt::resolve_language).transpose()?;
+    let to = trlt::resolve_language(&to)?;
+
+    let config = server_config(provider, model)?;
+    let translator = session_translator(&config, config.provider)?;
+    let existing = match fs::read_to_string(target) {
+        Ok(existing) => Some(existing),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e.into()),
+    };
+    let request = TranslationRequest::new(String::new(), from, to);
+    let (merged, count) = trlt::sync_json(
+        &fs::read_to_string(source)?,
+        existing.as_deref(),
+        translator.as_ref(),
+        &request,
+        jobs,
+    )
+    .await
+    .with_context(|| format!("Failed to sync {}", target.display()))?;
+
+    if count == 0 {
+        eprintln!("{} has no missing keys.", target.display());
+        return Ok(());
+    }
+    trlt::write_atomic(target, merged)?;
+    eprintln!(
+        "Translated {} missing keys into {}.",
+        count,
+        target.display()
+    );
+    Ok(())
+}
+
+async fn stdio(provider: Option<ProviderKind>, model: Option<String>) -> Result<()> {
+    let config = server_config(provider, model)?;
+    trlt::serve_stdio(config, session_translator).await?;
+    Ok(())
+}
+
+async fn serve(
+    address: std::net::SocketAddr,
+    grpc: Option<std::net::SocketAddr>,
+    provider: Option<ProviderKind>,
+    model: Option<String>,
+) -> Result<()> {
+    let config = server_config(provider, model)?;
+    trlt::serve(address, grpc, config, session_translator).await?;
+    Ok(())
+}
+
+/// The config of a server, whose default provider and model can be set on the command line.
+fn server_config(provider: Option<ProviderKind>, model: Option<String>) -> Result<Config> {
+    let mut config = read_config()?;
+    config.provider = provider.unwrap_or(config.provider);
+    if let Some(model) = model {
+        config.set_model(config.provider, model)?;
+    }
+    Ok(config)
+}
+
+/// The config and settings an interactive session starts with, with the languages resolved to
+/// their codes.
+fn session_config(args: SessionArgs) -> Result<(Config, SessionSettings)> {
+    let from = args
+        .from
+        .as_deref()
+        .map(trlt::resolve_language)
+        .transpose()?;
+    let mut config = read_config()?;
+    let to = match &args.to {
+        Some(to) => trlt::resolve_language(to)?,
+        None => trlt::default_target_language(config.to.first().map(String::as_str))?,
+    };
+    let provider = args.provider.unwrap_or(config.provider);
+    if let Some(model) = &args.model {
+        config.set_model(provider, model.clone())?;
+    }
+    let settings = SessionSettings {
+        from,
+        to,
+        provider,
+        tone: args.tone,
+    };
+    Ok((config, settings))
+}
+
+/// The translator used by interactive sessions, which caches translations and applies the
+/// glossary like the `translate` command does.
+fn session_translator(
+    config: &Config,
+    provider: ProviderKind,
+) -> trlt::Result<Box<dyn Translator>> {
+    let mut translator = trlt::translator_chain(config, provider)?;
+    translator = Box::new(PlaceholderTranslator::new(
+        translator,
+        trlt::DEFAULT_PLACEHOLDER_RETRIES,
+    ));
+    translator = Box::new(CachedTranslator::new(
+        translator,
+        Cache::open(),
+        provider,
+        config,
+    ));
+    let glossary = Glossary::load_for(config)?;
+    if !glossary.terms.is_empty() {
+        translator = Box::new(GlossaryTranslator::new(translator, glossary));
+    }
+    if config.system_prompt.is_some() {
+        translator = Box::new(PromptTranslator::new(
+            translator,
+            PromptOptions {
+                system_prompt: config.system_prompt.clone(),
+                ..Default::default()
+            },
+        ));
+    }
+    Ok(translator)
+}
+
+/// The language codes offered by the shell completions, described by their names.
+fn language_candidates() -> Vec<CompletionCandidate> {
+    trlt::LANGUAGES
+        .iter()
+        .map(|(code, name)| CompletionCandidate::new(code).help(Some((*name).into())))
+        .collect()
+}
+
+fn profile_candidates() -> Vec<CompletionCandidate> {
+    Config::profile_names()
+        .into_iter()
+        .map(CompletionCandidate::new)
+        .collect()
+}
+
+fn completions(shell: Shell) -> Result<()> {
+    let shells = Shells::builtins();
+    let completer = shells
+        .completer(&shell.to_string())
+        .ok_or_else(|| anyhow::anyhow!("{} completions are not supported", shell))?;
+    // The script runs trlt as it was run here, from any directory.
+    let mut program = PathBuf::from(std::env::args().next().unwrap_or_else(|| "trlt".into()));
+    if program.components().count() > 1 {
+        program = std::env::current_dir()?.join(program);
+    }
+    completer.write_registration(
+        "COMPLETE",
+        "trlt",
+        "trlt",
+        &program.to_string_lossy(),
+        &mut io::stdout(),
+    )?;
+    Ok(())
+}
+
+fn man(out: Option<PathBuf>) -> Result<()> {
+    match out {
+        Some(out) => {
+            fs::create_dir_all(&out)?;
+            clap_mangen::generate_to(Cli::command(), &out)?;
+            eprintln!("Wrote the man pages to {}.", out.display());
+        }
+        None => clap_mangen::Man::new(Cli::command()).render(&mut io::stdout())?,
+    }
+    Ok(())
+}
+
+fn languages() {
+    for (code, name) in trlt::LANGUAGES {
+        println!("{:<8} {}", code, name);
+    }
+}
+
+async fn models(provider: Option<ProviderKind>) -> Result<()> {
+    let config = read_config()?;
+    let kind = provider.unwrap_or(config.provider);
+    let models = trlt::translator(&config, kind)?
+        .models()
+        .await
+        .with_context(|| format!("Failed to list the {} models", kind))?;
+
+    let width = models.iter().map(|model| model.id.len()).max().unwrap_or(0);
+    for model in models {
+        let context = model
+            .context_window
+            .map(|tokens| format!("{}k context", tokens / 1000))
+            .unwrap_or_default();
+        let pricing = model
+            .pricing
+            .map(|pricing| {
+                format!(
+                    "${} in / ${} out per 1M tokens",
+                    pricing.input, pricing.output
+                )
+            })
+            .unwrap_or_default();
+        let line = format!(
+            "{:<width$}  {:<13} {}",
+            model.id,
+            context,
+            pricing,
+            width = width
+        );
+        println!("{}", line.trim_end());
+    }
+    Ok(())
+}
+
+fn usage_stats(by: UsageGrouping, days: u32) -> Result<()> {
+    let today = chrono::Utc::now().date_naive();
+    let since = today - chrono::Days::new(days.saturating_sub(1).into());
+    let since = since
+        .and_hms_opt(0, 0, 0)
+        .expect("midnight exists")
+        .and_utc();
+    let summaries = UsageLog::open()?.summary(by, since)?;
+    if summaries.is_empty() {
+        println!("No usage recorded in the last {} days.", days);
+        return Ok(());
+    }
+
+    let heading = format!("{:?}", by).to_uppercase();
+    let width = summaries
+        .iter()
+        .map(|summary| summary.key.len())
+        .chain([heading.len(), "TOTAL".len()])
+        .max()
+        .unwrap_or(0);
+    let row = |key: &str, requests: u64, prompt: u64, completion: u64, cost: f64| {
+        println!(
+            "{:<width$}  {:>8}  {:>13}  {:>17}  {:>10}",
+            key,
+            requests,
+            prompt,
+            completion,
+            format!("${:.4}", cost),
+            width = width
+        );
+    };
+    anstream::println!(
+        "{HEADING_STYLE}{:<width$}  {:>8}  {:>13}  {:>17}  {:>10}{HEADING_STYLE:#}",
+        heading,
+        "REQUESTS",
+        "PROMPT TOKENS",
+        "COMPLETION TOKENS",
+        "COST",
+        width = width
+    );
+    for summary in &summaries {
+        row(
+            &summary.key,
+            summary.requests,
+            summary.prompt_tokens,
+            summary.completion_tokens,
+            summary.cost,
+        );
+    }
+    if summaries.len() > 1 {
+        row(
+            "TOTAL",
+            summaries.iter().map(|summary| summary.requests).sum(),
+            summaries.iter().map(|summary| summary.prompt_tokens).sum(),
+            summaries
+                .iter()
+                .map(|summary| summary.completion_tokens)
+                .sum(),
+            summaries.iter().map(|summary| summary.cost).sum(),
+        );
+    }
+    Ok(())
+}
+
+/// The outcome of a check of `trlt doctor`.
+enum Check {
+    Passed(String),
+    /// What is wrong, and how to fix it.
+    Warning(String, String),
+    Failed(String, String),
+}
+
+async fn doctor() -> Result<()> {
+    let mut failures = 0;
+    let mut report = |name: &str, check: Check| {
+        let (style, mark, detail, fix) = match check {
+            Check::Passed(detail) => (INSERTED_STYLE, "ok", detail, None),
+            Check::Warning(detail, fix) => (WARNING_STYLE, "warning", detail, Some(fix)),
+            Check::Failed(detail, fix) => {
+                failures += 1;
+                (ERROR_STYLE, "failed", detail, Some(fix))
+            }
+        };
+        anstream::println!(
+            "{style}{:<7}{style:#} {HEADING_STYLE}{}:{HEADING_STYLE:#} {}",
+            mark,
+            name,
+            detail
+        );
+        if let Some(fix) = fix {
+            anstream::println!("        {DIMMED_STYLE}Fix: {}{DIMMED_STYLE:#}", fix);
+        }
+    };
+
+    let config = match read_config() {
+        Ok(config) => {
+            let path = match Config::config_path().exists() {
+                true => Config::config_path(),
+                false => Config::encrypted_path(),
+            };
+            let files = match Config::project_path() {
+                Some(project) => format!("{} and {}", path.display(), project.display()),
+                None => path.display().to_string(),
+            };
+            report("Config", Check::Passed(format!("read {}", files)));
+            Some(config)
+        }
+        Err(e) if matches!(e.downcast_ref(), Some(trlt::Error::MissingConfig)) => {
+            report(
+                "Config",
+                Check::Failed(
+                    "there is no config file".to_string(),
+                    "run `trlt init`, or set the TRLT_* environment variables".to_string(),
+                ),
+            );
+            None
+        }
+        Err(e) => {
+            report(
+                "Config",
+                Check::Failed(
+                    format!("{:#}", e),
+                    format!(
+                        "correct {} with `trlt config set`",
+                        Config::config_path().display()
+                    ),
+                ),
+            );
+            None
+        }
+    };
+
+    if let Some(config) = &config {
+        let provider = config.provider;
+        let translator = match trlt::translator(config, provider) {
+            Ok(_) if provider.requires_api_key() && config.api_keys_for(provider).is_empty() => {
+                report(
+                    "API key",
+                    Check::Failed(
+                        format!("there is no API key for {}", provider),
+                        "run `trlt config set api_key <KEY>`, or set TRLT_API_KEY".to_string(),
+                    ),
+                );
+                None
+            }
+            Ok(translator) => {
+                let detail = match provider.requires_api_key() {
+                    true => format!("set for {}", provider),
+                    false => format!("{} does not need one", provider),
+                };
+                report("API key", Check::Passed(detail));
+                Some(translator)
+            }
+            Err(trlt::Error::MissingApiKey(_)) => {
+                report(
+                    "API key",
+                    Check::Failed(
+                        format!("there is no API key for {}", provider),
+                        "run `trlt config set api_key <KEY>`, or set TRLT_API_KEY".to_string(),
+                    ),
+                );
+                None
+            }
+            Err(e) => {
+                report(
+                    "API key",
+                    Check::Failed(
+                        format!("{} cannot be set up: {}", provider, e),
+                        "add the missing settings with `trlt config set`".to_string(),
+                    ),
+                );
+                None
+            }
+        };
+
+        if let Some(translator) = translator {
+            match translator.models().await {
+                Ok(models) => {
+                    report("Network", Check::Passed(format!("{} answered", provider)));
+                    let check = match config.model_for(provider) {
+                        Some(model) if models.iter().any(|available| available.id == model) => {
+                            Check::Passed(format!("{} offers `{}`", provider, model))
+                        }
+                        Some(model) => Check::Warning(
+                            format!("{} does not list `{}`", provider, model),
+                            "pick one of `trlt models`, then run `trlt config set model <MODEL>`"
+                                .to_string(),
+                        ),
+                        None => Check::Passed(format!("{} does not let you choose one", provider)),
+                    };
+                    report("Model", check);
+                }
+                Err(trlt::Error::Custom(e)) => report(
+                    "Network",
+                    Check::Warning(
+                        format!("not checked: {}", e),
+                        "translate some text to check that the provider can be reached".to_string(),
+                    ),
+                ),
+                Err(e @ trlt::Error::Unauthorized(..)) => report(
+                    "Network",
+                    Check::Failed(
+                        format!("{} rejected the API key: {}", provider, e),
+                        "run `trlt config set api_key <KEY>` with a valid key".to_string(),
+                    ),
+                ),
+                Err(e) => report(
+                    "Network",
+                    Check::Failed(
+                        format!("{} could not be reached: {}", provider, e),
+                        "check the network connection, and the `proxy` and `base_url` settings"
+                            .to_string(),
+                    ),
+                ),
+            }
+        }
+    }
+
+    let clipboard = ClipboardContext::new().and_then(|mut clipboard| clipboard.get_contents());
+    let check = match clipboard {
+        Ok(_) => Check::Passed("available".to_string()),
+        Err(_)
+            if config
+                .as_ref()
+                .is_some_and(|config| config.clipboard == Some(false)) =>
+        {
+            Check::Passed("unavailable, and turned off in the config".to_string())
+        }
+        Err(e) => Check::Warning(
+            format!("unavailable: {}", e),
+            "run trlt in a graphical session, or run `trlt config set clipboard false`".to_string(),
+        ),
+    };
+    report("Clipboard", check);
+
+    let cache = Cache::open();
+    let check = match cache_health(&cache) {
+        Ok(stats) => Check::Passed(format!(
+            "{} entries, {:.1} KiB in {}",
+            stats.entries,
+            stats.size as f64 / 1024.0,
+            cache.dir().display()
+        )),
+        Err(e) => Check::Failed(
+            format!("{} cannot be used: {}", cache.dir().display(), e),
+            "check the permissions of the directory, or run `trlt cache clear`".to_string(),
+        ),
+    };
+    report("Cache", check);
+
+    if failures > 0 {
+        return Err(anyhow::anyhow!("{} checks failed", failures));
+    }
+    Ok(())
+}
+
+/// Check that entries can be written to the cache and read back.
+fn cache_health(cache: &Cache) -> io::Result<trlt::CacheStats> {
+    fs::create_dir_all(cache.dir())?;
+    let probe = cache.dir().join(".doctor");
+    fs::write(&probe, "ok")?;
+    let read = fs::read_to_string(&probe);
+    fs::remove_file(&probe)?;
+    read?;
+    cache.stats().map_err(|e| io::Error::other(e.to_string()))
+}
+
+fn cache(command: CacheCommand) -> Result<()> {
+    let cache = Cache::open();
+    match command {
+        CacheCommand::Clear => {
+            cache.clear()?;
+            println!("Cache cleared.");
+        }
+        CacheCommand::Stats => {
+            let stats = cache.stats()?;
+            println!("Location: {}", cache.dir().display());
+            println!("Entries:  {}", stats.entries);
+            println!("Size:     {:.1} KiB", stats.size as f64 / 1024.0);
+        }
+    }
+    Ok(())
+}
+
+fn memory(command: MemoryCommand) -> Result<()> {
+    let memory = TranslationMemory::open()?;
+    match command {
+        MemoryCommand::Import { path } => {
+            let count = memory.import_tmx(&path)?;
+            println!("Imported {} segment pairs from {}.", count, path.display());
+        }
+        MemoryCommand::Export { path } => {
+            let count = memory.export_tmx(&path)?;
+            println!("Exported {} segment pairs to {}.", count, path.display());
+        }
+        MemoryCommand::Stats => {
+            println!("Location: {}", TranslationMemory::default_path().display());
+            println!("Segments: {}", memory.len()?);
+        }
+        MemoryCommand::Clear => {
+            memory.clear()?;
+            println!("Translation memory cleared.");
+        }
+    }
+    Ok(())
+}
+
+/// How many characters of the source `trlt history list` shows.
+const HISTORY_PREVIEW: usize = 60;
+
+fn history(command: HistoryCommand) -> Result<()> {
+    let history = TranslationHistory::open()?;
+    match command {
+        HistoryCommand::List { limit, export } => {
+            list_history(&history.list(limit)?, export, "No translations recorded.")?;
+        }
+        HistoryCommand::Search {
+            query,
+            limit,
+            export,
+        } => {
+            list_history(
+                &history.search(&query, limit)?,
+                export,
+                "No translations match the search.",
+            )?;
+        }
+        HistoryCommand::Show { id } => {
+            let entry = history
+                .get(id)?
+                .ok_or_else(|| anyhow::anyhow!("There is no translation {} in the history", id))?;
+            anstream::println!(
+                "{HEADING_STYLE}Date:{HEADING_STYLE:#}     {}",
+                entry.created_at.with_timezone(&chrono::Local).to_rfc2822()
+            );
+            if let Some(name) = &entry.name {
+                anstream::println!("{HEADING_STYLE}Input:{HEADING_STYLE:#}    {}", name);
+            }
+            anstream::println!("{HEADING_STYLE}Pair:{HEADING_STYLE:#}     {}", entry.pair());
+            anstream::println!(
+                "{HEADING_STYLE}Provider:{HEADING_STYLE:#} {}",
+                entry.provider
+            );
+            if let Some(model) = &entry.model {
+                anstream::println!("{HEADING_STYLE}Model:{HEADING_STYLE:#}    {}", model);
+            }
+            anstream::println!(
+                "\n{}\n\n{INSERTED_STYLE}{}{INSERTED_STYLE:#}",
+                entry.source.trim_end(),
+                entry.result.trim_end()
+            );
+        }
+        HistoryCommand::Clear => {
+            history.clear()?;
+            println!("History cleared.");
+        }
+    }
+    Ok(())
+}
+
+/// Print a line per entry, or export the entries in full.
+fn list_history(
+    entries: &[HistoryEntry],
+    export: Option<HistoryFormat>,
+    empty: &str,
+) -> Result<()> {
+    if let Some(format) = export {
+        trlt::export_history(entries, format, io::stdout().lock())?;
+        return Ok(());
+    }
+    if entries.is_empty() {
+        println!("{}", empty);
+    }
+    for entry in entries {
+        anstream::println!(
+            "{HEADING_STYLE}{:>5}{HEADING_STYLE:#}  {DIMMED_STYLE}{}{DIMMED_STYLE:#}  {:<12}  {}",
+            entry.id,
+            entry
+                .created_at
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M"),
+            entry.pair(),
+            preview(entry)
+        );
+    }
+    Ok(())
+}
+
+/// The first line of the input name or source of `entry`, cut to fit on a line.
+fn preview(entry: &HistoryEntry) -> String {
+    let text = entry.name.as_deref().unwrap_or(&entry.source);
+    let line = text
+        .lines()
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or("");
+    let mut preview: String = line.trim().chars().take(HISTORY_PREVIEW).collect();
+    if preview.len() < line.trim().len() || text.trim().lines().nth(1).is_some() {
+        preview.push('…');
+    }
+    preview
+}
+
+fn glossary(command: GlossaryCommand) -> Result<()> {
+    let mut glossary = Glossary::load()?;
+    match command {
+        GlossaryCommand::Add {
+            source,
+            target,
+            from,
+            to,
+        } => {
+            glossary.add(GlossaryTerm {
+                source,
+                target,
+                from,
+                to,
+            });
+            glossary.save()?;
+        }
+        GlossaryCommand::Remove { source, to } => {
+            let removed = glossary.remove(&source, to.as_deref());
+            if removed == 0 {
+                return Err(anyhow::anyhow!("No glossary entry for {}", source));
+            }
+            glossary.save()?;
+        }
+        GlossaryCommand::List { from, to } => {
+            for term in &glossary.terms {
+                let matches = |filter: &Option<String>, value: Option<&str>| {
+                    filter.as_ref().is_none_or(|filter| {
+                        value.is_some_and(|value| value.eq_ignore_ascii_case(filter))
+                    })
+                };
+                if matches(&from, term.from.as_deref()) && matches(&to, Some(&term.to)) {
+                    println!(
+                        "{} -> {} ({}:{})",
+                        term.source,
+                        term.target,
+                        term.from.as_deref().unwrap_or("*"),
+                        term.to
+                    );
+                }
+            }
+        }
+        GlossaryCommand::Import { path, from, to } => {
+            let count = glossary.import_csv(&path, from.as_deref(), to.as_deref())?;
+            glossary.save()?;
+            println!("Imported {} terms from {}.", count, path.display());
+        }
+    }
+    Ok(())
+}
+
+fn config(command: ConfigCommand) -> Result<()> {
+    // Settings are edited in the profile when one is selected.
+    let profile = PROFILE.get().and_then(Option::as_deref);
+    let setting = |key: &str| match profile {
+        Some(profile) => format!("profile.{}.{}", profile, key),
+        None => key.to_string(),
+    };
+    match command {
+        ConfigCommand::Get { key } => match trlt::config_value(&read_config()?, &key)? {
+            Some(toml::Value::String(value)) => println!("{}", value),
+            Some(toml::Value::Table(table)) => print!("{}", toml::to_string_pretty(&table)?),
+            Some(value) => println!("{}", value),
+            None => return Err(anyhow::anyhow!("`{}` is not set", key)),
+        },
+        ConfigCommand::Set { key, value } => {
+            let mut file = ConfigFile::open()?;
+            file.set(&setting(&key), &value)?;
+            file.save()?;
+        }
+        ConfigCommand::Unset { key } => {
+            let mut file = ConfigFile::open()?;
+            if !file.unset(&setting(&key)) {
+                return Err(anyhow::anyhow!("`{}` is not set in the config file", key));
+            }
+            file.save()?;
+        }
+        ConfigCommand::Encrypt => {
+            trlt::encrypt_config()?;
+            println!(
+                "Config file encrypted in {}",
+                Config::encrypted_path().display()
+            );
+        }
+        ConfigCommand::Decrypt => {
+            trlt::decrypt_config()?;
+            println!(
+                "Config file decrypted in {}",
+                Config::config_path().display()
+            );
+        }
+    }
+    Ok(())
+}
+
+fn copy_to_clipboard(text: &str) {
+    if let Ok(mut ctx) = ClipboardContext::new() {
+        if let Err(e) = ctx.set_contents(text.to_string()) {
+            eprintln!("Failed to copy to clipboard: {:?}", e);
+        } else {
+            // On stderr, so that it does not end up with the translation when piped.
+            eprintln!("Output copied to clipboard.");
+        }
+    }
+}
@@ -0,0 +1,414 @@
+use std::{fs, path::Path, path::PathBuf, sync::Mutex};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use quick_xml::{escape::escape, events::Event, Reader};
+use rusqlite::{params, Connection, OptionalExtension};
+use tracing::{debug, warn};
+
+use crate::{
+    write_atomic, Error, OnChunk, Result, Task, Translation, TranslationRequest, Translator,
+};
+
+/// A source/target segment pair stored in the translation memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+    /// The source language, or an empty string when it is unknown.
+    pub source_lang: String,
+    pub target_lang: String,
+    pub source: String,
+    pub target: String,
+}
+
+/// What a translation depends on besides its source and languages. An exact match is only
+/// reused for a request with the same tone and hint, and made by the same model unless it was
+/// imported.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SegmentSettings {
+    pub tone: Option<String>,
+    pub hint: Option<String>,
+    pub model: Option<String>,
+}
+
+/// A persistent translation memory backed by SQLite.
+///
+/// Every translated segment is stored, and exact matches are reused instead of calling
+/// the provider again.
+pub struct TranslationMemory {
+    conn: Mutex<Connection>,
+}
+
+impl TranslationMemory {
+    pub fn new(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS segments (
+                id INTEGER PRIMARY KEY,
+                source_lang TEXT NOT NULL,
+                target_lang TEXT NOT NULL,
+                source TEXT NOT NULL,
+                target TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                UNIQUE (source_lang, target_lang, source)
+            );",
+        )?;
+        let has_settings: bool = conn.query_row(
+            "SELECT EXISTS (SELECT 1 FROM pragma_table_info('segments') WHERE name = 'tone')",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_settings {
+            conn.execute_batch(
+                "BEGIN;
+                 ALTER TABLE segments ADD COLUMN tone TEXT;
+                 ALTER TABLE segments ADD COLUMN hint TEXT;
+                 ALTER TABLE segments ADD COLUMN model TEXT;
+                 COMMIT;",
+            )?;
+        }
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// The translation memory in the user data directory, e.g. `$HOME/.local/share/trlt`.
+    pub fn open() -> Result<Self> {
+        Self::new(&Self::default_path())
+    }
+
+    pub fn default_path() -> PathBuf {
+        dirs::data_dir()
+            .expect("Failed to get data directory")
+            .join("trlt")
+            .join("memory.db")
+    }
+
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn.lock().expect("translation memory lock poisoned")
+    }
+
+    /// Find an exact match for `source` translated with `settings`. Without a source language,
+    /// any source language matches.
+    pub fn lookup(
+        &self,
+        source: &str,
+        from: Option<&str>,
+        to: &str,
+        settings: &SegmentSettings,
+    ) -> Result<Option<String>> {
+        let target = self
+            .conn()
+            .query_row(
+                "SELECT target FROM segments
+                 WHERE source = ?1 AND target_lang = ?2 AND (?3 IS NULL OR source_lang = ?3)
+                   AND tone IS ?4 AND hint IS ?5 AND (model IS NULL OR model IS ?6)
+                 ORDER BY id DESC LIMIT 1",
+                params![
+                    source,
+                    to,
+                    from,
+                    settings.tone,
+                    settings.hint,
+                    settings.model
+                ],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(target)
+    }
+
+    /// Store `segment`, translated with `settings`, replacing any translation of the same
+    /// source.
+    pub fn insert(&self, segment: &Segment, settings: &SegmentSettings) -> Result<()> {
+        self.conn().execute(
+            "INSERT INTO segments (source_lang, target_lang, source, target, created_at, tone,
+                                   hint, model)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT (source_lang, target_lang, source) DO UPDATE SET
+                 target = excluded.target, tone = excluded.tone, hint = excluded.hint,
+                 model = excluded.model",
+            params![
+                segment.source_lang,
+                segment.target_lang,
+                segment.source,
+                segment.target,
+                Utc::now().to_rfc3339(),
+                settings.tone,
+                settings.hint,
+                settings.model
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn segments(&self) -> Result<Vec<Segment>> {
+        let conn = self.conn();
+        let mut statement = conn
+            .prepare("SELECT source_lang, target_lang, source, target FROM segments ORDER BY id")?;
+        let segments = statement
+            .query_map([], |row| {
+                Ok(Segment {
+                    source_lang: row.get(0)?,
+                    target_lang: row.get(1)?,
+                    source: row.get(2)?,
+                    target: row.get(3)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(segments)
+    }
+
+    pub fn len(&self) -> Result<usize> {
+        let count: i64 = self
+            .conn()
+            .query_row("SELECT COUNT(*) FROM segments", [], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    pub fn is_empty(&self) -> Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        self.conn().execute("DELETE FROM segments", [])?;
+        Ok(())
+    }
+
+    /// Import the segment pairs of a TMX file, returning how many were imported.
+    pub fn import_tmx(&self, path: &Path) -> Result<usize> {
+        let contents = fs::read_to_string(path)?;
+        let segments = parse_tmx(&contents)?;
+        for segment in &segments {
+            self.insert(segment, &SegmentSettings::default())?;
+        }
+        Ok(segments.len())
+    }
+
+    /// Export every segment pair to a TMX 1.4 file, returning how many were exported.
+    pub fn export_tmx(&self, path: &Path) -> Result<usize> {
+        let segments = self.segments()?;
+        write_atomic(path, to_tmx(&segments))?;
+        Ok(segments.len())
+    }
+}
+
+fn xml_error(e: impl std::fmt::Display) -> Error {
+    Error::Custom(format!("Invalid TMX: {}", e))
+}
+
+/// Parse the translation units of a TMX document into source/target pairs.
+///
+/// The source of each unit is the variant in the header `srclang`, or the first variant
+/// when the header allows any language.
+pub fn parse_tmx(contents: &str) -> Result<Vec<Segment>> {
+    let mut reader = Reader::from_str(contents);
+    let mut segments = Vec::new();
+    let mut srclang: Option<String> = None;
+    let mut variants: Vec<(String, String)> = Vec::new();
+    let mut lang = String::new();
+    let mut seg: Option<String> = None;
+
+    loop {
+        match reader.read_event().map_err(xml_error)? {
+            Event::Start(e) | Event::Empty(e) => match e.local_name().as_ref() {
+                b"header" => {
+                    srclang = attribute(&e, b"srclang").filter(|lang| lang != "*all*");
+                }
+                b"tu" => variants.clear(),
+                b"tuv" => lang = attribute(&e, b"lang").unwrap_or_default(),
+                b"seg" => seg = Some(String::new()),
+                _ => {}
+            },
+            Event::Text(text) => {
+                if let Some(seg) = seg.as_mut() {
+                    seg.push_str(&text.unescape().map_err(xml_error)?);
+                }
+            }
+            Event::CData(text) => {
+                if let Some(seg) = seg.as_mut() {
+                    seg.push_str(&String::from_utf8_lossy(&text));
+                }
+            }
+            Event::End(e) => match e.local_name().as_ref() {
+                b"seg" => {
+                    if let Some(seg) = seg.take() {
+                        variants.push((lang.clone(), seg));
+                    }
+                }
+                b"tu" => {
+                    let source = srclang
+                        .as_ref()
+                        .and_then(|srclang| {
+                            variants
+                                .iter()
+                                .position(|(lang, _)| lang.eq_ignore_ascii_case(srclang))
+                        })
+                        .unwrap_or(0);
+                    let Some((source_lang, source_text)) = variants.get(source).cloned() else {
+                        continue;
+                    };
+                    for (index, (target_lang, target)) in variants.iter().enumerate() {
+                        if index != source {
+                            segments.push(Segment {
+                                source_lang: source_lang.clone(),
+                                target_lang: target_lang.clone(),
+                                source: source_text.clone(),
+                                target: target.clone(),
+                            });
+                        }
+                    }
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(segments)
+}
+
+/// The value of an attribute, matched by its local name (so `xml:lang` matches `lang`).
+fn attribute(element: &quick_xml::events::BytesStart, name: &[u8]) -> Option<String> {
+    element
+        .attributes()
+        .flatten()
+        .find(|attribute| attribute.key.local_name().as_ref() == name)
+        .and_then(|attribute| attribute.unescape_value().ok())
+        .map(|value| value.to_string())
+}
+
+/// Render segment pairs as a TMX 1.4 document.
+pub fn to_tmx(segments: &[Segment]) -> String {
+    let mut tmx = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<tmx version=\"1.4\">\n",
+        "  <header creationtool=\"trlt\" creationtoolversion=\"",
+        env!("CARGO_PKG_VERSION"),
+        "\" segtype=\"sentence\" o-tmf=\"trlt\" adminlang=\"en\" srclang=\"*all*\" datatype=\"plaintext\"/>\n",
+        "  <body>\n",
+    ));
+
+    for segment in segments {
+        tmx.push_str("    <tu>\n");
+        for (lang, text) in [
+            (&segment.source_lang, &segment.source),
+            (&segment.target_lang, &segment.target),
+        ] {
+            tmx.push_str(&format!(
+                "      <tuv xml:lang=\"{}\"><seg>{}</seg></tuv>\n",
+                escape(lang.as_str()),
+                escape(text.as_str())
+            ));
+        }
+        tmx.push_str("    </tu>\n");
+    }
+
+    tmx.push_str("  </body>\n</tmx>\n");
+    tmx
+}
+
+/// Translator that reuses exact matches from the translation memory and records every
+/// new translation in it.
+pub struct MemoryTranslator {
+    inner: Box<dyn Translator>,
+    memory: TranslationMemory,
+    model: Option<String>,
+}
+
+impl MemoryTranslator {
+    /// Reuse and record the translations made by `model`.
+    pub fn new(
+        inner: Box<dyn Translator>,
+        memory: TranslationMemory,
+        model: Option<String>,
+    ) -> Self {
+        Self {
+            inner,
+            memory,
+            model,
+        }
+    }
+
+    fn settings(&self, request: &TranslationRequest) -> SegmentSettings {
+        SegmentSettings {
+            tone: request.tone.map(|tone| tone.name().to_string()),
+            hint: request.hint.clone(),
+            model: self.model.clone(),
+        }
+    }
+
+    fn lookup(&self, request: &TranslationRequest) -> Option<Translation> {
+        let target = self
+            .memory
+            .lookup(
+                &request.text,
+                request.from.as_deref(),
+                &request.to,
+                &self.settings(request),
+            )
+            .unwrap_or_else(|e| {
+                warn!("Failed to read the translation memory: {}", e);
+                None
+            })?;
+        debug!(to = request.to, "Translation memory hit");
+        Some(Translation {
+            text: target,
+            ..Default::default()
+        })
+    }
+
+    fn record(&self, request: &TranslationRequest, translation: &Translation) {
+        let segment = Segment {
+            source_lang: request
+                .from
+                .clone()
+                .or_else(|| translation.detected_source.clone())
+                .unwrap_or_default(),
+            target_lang: request.to.clone(),
+            source: request.text.clone(),
+            target: translation.text.clone(),
+        };
+        if let Err(e) = self.memory.insert(&segment, &self.settings(request)) {
+            warn!("Failed to write to the translation memory: {}", e);
+        }
+    }
+}
+
+#[async_trait]
+impl Translator for MemoryTranslator {
+    async fn translate(&self, request: &TranslationRequest) -> Result<Translation> {
+        if request.task != Task::Translate {
+            return self.inner.translate(request).await;
+        }
+        if let Some(translation) = self.lookup(request) {
+            return Ok(translation);
+        }
+
+        let translation = self.inner.translate(request).await?;
+        self.record(request, &translation);
+        Ok(translation)
+    }
+
+    async fn translate_stream(
+        &self,
+        request: &TranslationRequest,
+        on_chunk: OnChunk<'_>,
+    ) -> Result<Translation> {
+        if request.task != Task::Translate {
+            return self.inner.translate_stream(request, on_chunk).await;
+        }
+        if let Some(translation) = self.lookup(request) {
+            on_chunk(&translation.text);
+            return Ok(translation);
+        }
+
+        let translation = self.inner.translate_stream(request, on_chunk).await?;
+        self.record(request, &translation);
+        Ok(translation)
+    }
+}
@@ -0,0 +1,42 @@
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+    process,
+};
+
+use crate::Result;
+
+/// Write `contents` to `path` atomically: see [`write_atomic_with`].
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    write_atomic_with(path, |file| Ok(file.write_all(contents.as_ref())?))
+}
+
+/// Write `path` with `write`, to a temporary file next to it that is only renamed into place
+/// once it is complete, so that an interrupted run or a failed translation never leaves a
+/// truncated file behind, nor clobbers the one that was there.
+pub fn write_atomic_with(path: &Path, write: impl FnOnce(&mut File) -> Result<()>) -> Result<()> {
+    let temp = temp_path(path);
+    let result = (|| {
+        let mut file = File::create(&temp)?;
+        write(&mut file)?;
+        file.sync_all()?;
+        // The file being replaced keeps its permissions, e.g. those of a config file.
+        if let Ok(metadata) = fs::metadata(path) {
+            fs::set_permissions(&temp, metadata.permissions())?;
+        }
+        fs::rename(&temp, path)?;
+        Ok(())
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// A hidden file in the same directory as `path`, so that renaming it does not cross file
+/// systems.
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{}.{}.tmp", name, process::id()))
+}
@@ -0,0 +1,272 @@
+use std::sync::LazyLock;
+
+use async_trait::async_trait;
+use regex::Regex;
+use tracing::warn;
+
+use crate::{Error, OnChunk, Result, Task, Translation, TranslationRequest, Translator};
+
+/// How many times a translation is retried when it loses placeholders.
+pub const DEFAULT_PLACEHOLDER_RETRIES: usize = 2;
+
+/// The instruction added to prompts whose text contains protected placeholders.
+pub(crate) const MARKER_INSTRUCTION: &str =
+    "Keep every marker like ⟦0⟧ exactly as it is, in the position that fits the translation.";
+
+static SIMPLE_PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        // `{{var}}` (Mustache, Handlebars, Angular)
+        r"\{\{[^{}]*\}\}",
+        // `%(name)s` (Python)
+        r"|%\([^)]+\)[-+0#]*\d*(?:\.\d+)?[diouxXeEfFgGcrs]",
+        // `%s`, `%1$s`, `%-5.2f`, `%lld`, `%@` (printf and Objective-C)
+        r"|%(?:\d+\$)?[-+0#']*(?:\d+|\*)?(?:\.(?:\d+|\*))?(?:hh|h|ll|l|L|z|j|t|q)?[diouxXeEfFgGaAcspn@%]",
+        // `${name}` and `$1`
+        r"|\$\{[^{}]+\}|\$\d+",
+    ))
+    .expect("placeholder pattern is valid")
+});
+
+static MARKER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"⟦(\d+)⟧").expect("marker pattern is valid"));
+
+/// Text whose placeholders were replaced by numbered markers such as `⟦0⟧`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Protected {
+    pub text: String,
+    pub placeholders: Vec<String>,
+    /// The number of markers the text already had, numbered before the new ones.
+    pub offset: usize,
+}
+
+/// Whether `text` contains protected placeholder markers.
+pub fn has_markers(text: &str) -> bool {
+    MARKER.is_match(text)
+}
+
+/// The end of the balanced `{...}` group starting at `start`, if any.
+fn balanced_brace_end(text: &str, start: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c) in text[start..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(start + i + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Whether a `{...}` group is a placeholder: `{name}`, `{0}`, or an ICU argument such as
+/// `{count, plural, one {# item} other {# items}}`.
+fn is_brace_placeholder(group: &str) -> bool {
+    let inner = &group[1..group.len() - 1];
+    let name = inner.split(',').next().unwrap_or_default().trim();
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '-' | '$' | ':'))
+        && (inner.trim() == name || inner.contains(','))
+}
+
+/// Replace the interpolation placeholders in `text` with numbered markers.
+///
+/// Supported placeholders are `{name}`, `{{var}}`, ICU arguments, printf-style and
+/// Python-style conversions, `${name}` and `$1`.
+pub fn protect(text: &str) -> Protected {
+    protect_by(text, |rest| {
+        let simple = SIMPLE_PLACEHOLDER
+            .find_iter(rest)
+            .find(|m| !is_percent_sign(rest, m.start(), m.end()))
+            .map(|m| (m.start(), m.end()));
+        let brace = rest.match_indices('{').find_map(|(start, _)| {
+            let end = balanced_brace_end(rest, start)?;
+            is_brace_placeholder(&rest[start..end]).then_some((start, end))
+        });
+
+        // Take whichever placeholder comes first; prefer the longer one on ties.
+        match (simple, brace) {
+            (Some(a), Some(b)) if b.0 < a.0 || (b.0 == a.0 && b.1 > a.1) => Some(b),
+            (Some(a), _) => Some(a),
+            (None, b) => b,
+        }
+    })
+}
+
+/// Whether the conversion at `start..end` of `text` is rather a percent sign in prose, such as
+/// `100%` or `%done`: one right after a number or right before a word.
+fn is_percent_sign(text: &str, start: usize, end: usize) -> bool {
+    text[start..].starts_with('%')
+        && (text[..start]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_ascii_digit())
+            || text[end..]
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_alphanumeric() || c == '_'))
+}
+
+/// Replace every match of `pattern` in `text` with numbered markers.
+pub fn protect_matches(text: &str, pattern: &Regex) -> Protected {
+    protect_by(text, |rest| {
+        pattern
+            .find_iter(rest)
+            .find(|m| !m.is_empty())
+            .map(|m| (m.start(), m.end()))
+    })
+}
+
+/// Replace the spans found by `next` with numbered markers. Markers already in `text` are
+/// kept, and new markers are numbered after them.
+fn protect_by(text: &str, next: impl Fn(&str) -> Option<(usize, usize)>) -> Protected {
+    let offset = MARKER
+        .captures_iter(text)
+        .filter_map(|captures| captures[1].parse::<usize>().ok())
+        .max()
+        .map_or(0, |max| max + 1);
+
+    let mut protected = String::with_capacity(text.len());
+    let mut placeholders = Vec::new();
+    let mut rest = text;
+
+    while let Some((start, end)) = next(rest) {
+        protected.push_str(&rest[..start]);
+        protected.push_str(&format!("⟦{}⟧", offset + placeholders.len()));
+        placeholders.push(rest[start..end].to_string());
+        rest = &rest[end..];
+    }
+    protected.push_str(rest);
+
+    Protected {
+        text: protected,
+        placeholders,
+        offset,
+    }
+}
+
+impl Protected {
+    /// Wrap text without placeholders.
+    pub fn plain(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            placeholders: Vec::new(),
+            offset: 0,
+        }
+    }
+
+    /// Put the original placeholders back into `translated`. Markers the text already had
+    /// before protection are checked but left in place.
+    ///
+    /// Fails when a placeholder is missing, duplicated, or unknown, listing the ones lost.
+    pub fn restore(&self, translated: &str) -> Result<String> {
+        let mut seen = vec![0; self.offset + self.placeholders.len()];
+        for captures in MARKER.captures_iter(translated) {
+            let index: usize = captures[1].parse().unwrap_or(usize::MAX);
+            match seen.get_mut(index) {
+                Some(count) => *count += 1,
+                None => {
+                    return Err(Error::Custom(format!(
+                        "Translation contains an unknown placeholder {}",
+                        &captures[0]
+                    )))
+                }
+            }
+        }
+
+        let lost = seen
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count != 1)
+            .map(|(index, _)| match index.checked_sub(self.offset) {
+                Some(index) => self.placeholders[index].clone(),
+                None => format!("⟦{}⟧", index),
+            })
+            .collect::<Vec<_>>();
+        if !lost.is_empty() {
+            return Err(Error::Custom(format!(
+                "Translation did not preserve the placeholders {}",
+                lost.join(", ")
+            )));
+        }
+
+        Ok(MARKER
+            .replace_all(translated, |captures: &regex::Captures| {
+                let index: usize = captures[1].parse().unwrap_or_default();
+                match index.checked_sub(self.offset) {
+                    Some(index) => self.placeholders[index].clone(),
+                    None => captures[0].to_string(),
+                }
+            })
+            .into_owned())
+    }
+}
+
+/// Translator that protects placeholders from the model, checks that all of them survive
+/// the translation, and retries when some are lost.
+pub struct PlaceholderTranslator {
+    inner: Box<dyn Translator>,
+    retries: usize,
+}
+
+impl PlaceholderTranslator {
+    pub fn new(inner: Box<dyn Translator>, retries: usize) -> Self {
+        Self { inner, retries }
+    }
+}
+
+#[async_trait]
+impl Translator for PlaceholderTranslator {
+    async fn translate(&self, request: &TranslationRequest) -> Result<Translation> {
+        let protected = protect(&request.text);
+        if protected.placeholders.is_empty() && protected.offset == 0
+            || request.task != Task::Translate
+        {
+            return self.inner.translate(request).await;
+        }
+
+        let protected_request = TranslationRequest {
+            text: protected.text.clone(),
+            ..request.clone()
+        };
+
+        let mut attempt = 0;
+        loop {
+            let translation = self.inner.translate(&protected_request).await?;
+            match protected.restore(&translation.text) {
+                Ok(text) => {
+                    return Ok(Translation {
+                        text,
+                        ..translation
+                    })
+                }
+                Err(e) if attempt < self.retries => {
+                    attempt += 1;
+                    warn!("{}. Retrying ({}/{})...", e, attempt, self.retries);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    async fn translate_stream(
+        &self,
+        request: &TranslationRequest,
+        on_chunk: OnChunk<'_>,
+    ) -> Result<Translation> {
+        let protected = protect(&request.text);
+        if protected.placeholders.is_empty() && protected.offset == 0 {
+            return self.inner.translate_stream(request, on_chunk).await;
+        }
+
+        // Placeholders can only be checked once the whole translation is known.
+        let translation = self.translate(request).await?;
+        on_chunk(&translation.text);
+        Ok(translation)
+    }
+}
@@ -0,0 +1,204 @@
+use std::{
+    io::{self, Write},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+
+use crate::{format_cost, Config, UsageMeter};
+
+/// The progress bar being drawn, which anything written to stderr must go around.
+static ACTIVE: Mutex<Option<ProgressBar>> = Mutex::new(None);
+
+/// Run `f`, which writes to stderr, with the progress bar being drawn, if any, cleared.
+pub fn suspend_progress<R>(f: impl FnOnce() -> R) -> R {
+    let bar = ACTIVE.lock().expect("progress lock poisoned").clone();
+    match bar {
+        Some(bar) => bar.suspend(f),
+        None => f(),
+    }
+}
+
+/// Stop drawing the progress bar, if any, clearing it.
+pub fn clear_progress() {
+    if let Some(bar) = ACTIVE.lock().expect("progress lock poisoned").take() {
+        bar.finish_and_clear();
+        // Later updates of the progress must not draw it again.
+        bar.set_draw_target(ProgressDrawTarget::hidden());
+    }
+}
+
+/// Stderr, written around the progress bar being drawn, for the logs.
+pub struct ProgressStderr;
+
+impl Write for ProgressStderr {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        suspend_progress(|| io::stderr().write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stderr().flush()
+    }
+}
+
+/// What the progress bar counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Unit {
+    /// Nothing is drawn.
+    Hidden,
+    /// The inputs of a batch.
+    Inputs,
+    /// The chunks of a single input, once it is split.
+    Chunks,
+    /// Nothing, with a spinner shown while waiting for a single translation.
+    Waiting,
+}
+
+#[derive(Debug)]
+struct State {
+    unit: Unit,
+    shown: bool,
+    chunks: usize,
+    chunks_done: usize,
+    failures: usize,
+}
+
+/// The progress of a run of several inputs, or of a long input split into chunks, drawn on
+/// stderr with the time left, the estimated cost so far and the inputs that failed. Single
+/// translations get a spinner with the time elapsed instead.
+///
+/// Nothing is drawn until one of the `start_*` methods is called, nor when stderr is not a
+/// terminal.
+pub struct Progress {
+    bar: ProgressBar,
+    state: Mutex<State>,
+    meter: Arc<UsageMeter>,
+    config: Config,
+}
+
+impl Progress {
+    pub fn new(meter: Arc<UsageMeter>, config: Config) -> Self {
+        Self {
+            bar: ProgressBar::hidden(),
+            state: Mutex::new(State {
+                unit: Unit::Hidden,
+                shown: false,
+                chunks: 0,
+                chunks_done: 0,
+                failures: 0,
+            }),
+            meter,
+            config,
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().expect("progress lock poisoned")
+    }
+
+    /// Count the `total` inputs of a batch.
+    pub fn start_inputs(&self, total: usize) {
+        self.state().unit = Unit::Inputs;
+        self.bar.set_length(total as u64);
+        self.show("inputs");
+    }
+
+    /// Count the chunks of a single input, showing the bar only if it gets split, and a
+    /// spinner until then if `spinner` is set.
+    pub fn start_chunks(&self, spinner: bool) {
+        self.state().unit = Unit::Chunks;
+        self.bar.set_length(0);
+        if spinner {
+            self.draw("{spinner} Translating... {elapsed}");
+        }
+    }
+
+    /// Show a spinner until [`clear_progress`] is called, e.g. once the translation starts
+    /// to stream in.
+    pub fn start_waiting(&self) {
+        self.state().unit = Unit::Waiting;
+        self.draw("{spinner} Translating... {elapsed}");
+    }
+
+    fn show(&self, unit: &str) {
+        self.state().shown = true;
+        self.draw(&format!(
+            "{{spinner}} [{{bar:30}}] {{pos}}/{{len}} {} ({{eta}} left){{msg}}",
+            unit
+        ));
+        self.refresh();
+    }
+
+    fn draw(&self, template: &str) {
+        let style = ProgressStyle::with_template(template)
+            .expect("the progress template is valid")
+            .progress_chars("=> ");
+        self.bar.set_style(style);
+        self.bar.set_draw_target(ProgressDrawTarget::stderr());
+        self.bar.enable_steady_tick(Duration::from_millis(100));
+        *ACTIVE.lock().expect("progress lock poisoned") = Some(self.bar.clone());
+    }
+
+    /// Count `count` more chunks to translate.
+    pub fn add_chunks(&self, count: usize) {
+        let mut state = self.state();
+        state.chunks += count;
+        if state.unit == Unit::Chunks {
+            self.bar.inc_length(count as u64);
+            let shown = state.shown;
+            drop(state);
+            if !shown && count > 1 {
+                self.show("chunks");
+            }
+        }
+    }
+
+    pub fn chunk_done(&self) {
+        let mut state = self.state();
+        state.chunks_done += 1;
+        if state.unit == Unit::Chunks {
+            self.bar.inc(1);
+        }
+        drop(state);
+        self.refresh();
+    }
+
+    pub fn input_done(&self, failed: bool) {
+        let mut state = self.state();
+        if failed {
+            state.failures += 1;
+        }
+        if state.unit == Unit::Inputs {
+            self.bar.inc(1);
+        }
+        drop(state);
+        self.refresh();
+    }
+
+    /// Update the details after the count: the chunks translated in a batch, the estimated
+    /// cost so far and the failures.
+    fn refresh(&self) {
+        let state = self.state();
+        let mut details = Vec::new();
+        if state.unit == Unit::Inputs {
+            details.push(format!("{}/{} chunks", state.chunks_done, state.chunks));
+        }
+        if let Some(cost) = self.meter.cost(&self.config).filter(|cost| *cost > 0.0) {
+            details.push(format_cost(cost));
+        }
+        if state.failures > 0 {
+            details.push(format!("{} failed", state.failures));
+        }
+        let message = match details.is_empty() {
+            true => String::new(),
+            false => format!(", {}", details.join(", ")),
+        };
+        self.bar.set_message(message);
+    }
+
+    /// Stop drawing the bar, clearing it.
+    pub fn finish(&self) {
+        clear_progress();
+    }
+}
@@ -0,0 +1,60 @@
+use async_trait::async_trait;
+
+use crate::{OnChunk, Result, Tone, Translation, TranslationRequest, Translator};
+
+/// Settings that shape the prompt of every request, set from the config file or the command line.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PromptOptions {
+    pub system_prompt: Option<String>,
+    pub template: Option<String>,
+    pub tone: Option<Tone>,
+    pub hint: Option<String>,
+}
+
+impl PromptOptions {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Translator that sends every request with the given prompt options. Options the request
+/// already sets are kept.
+pub struct PromptTranslator {
+    inner: Box<dyn Translator>,
+    options: PromptOptions,
+}
+
+impl PromptTranslator {
+    pub fn new(inner: Box<dyn Translator>, options: PromptOptions) -> Self {
+        Self { inner, options }
+    }
+
+    fn with_prompt(&self, request: &TranslationRequest) -> TranslationRequest {
+        let options = self.options.clone();
+        let request = request.clone();
+        TranslationRequest {
+            system_prompt: request.system_prompt.or(options.system_prompt),
+            template: request.template.or(options.template),
+            tone: request.tone.or(options.tone),
+            hint: request.hint.or(options.hint),
+            ..request
+        }
+    }
+}
+
+#[async_trait]
+impl Translator for PromptTranslator {
+    async fn translate(&self, request: &TranslationRequest) -> Result<Translation> {
+        self.inner.translate(&self.with_prompt(request)).await
+    }
+
+    async fn translate_stream(
+        &self,
+        request: &TranslationRequest,
+        on_chunk: OnChunk<'_>,
+    ) -> Result<Translation> {
+        self.inner
+            .translate_stream(&self.with_prompt(request), on_chunk)
+            .await
+    }
+}
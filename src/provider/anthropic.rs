@@ -0,0 +1,146 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    check_status, default_http_client, Error, GenerationParams, Model, Result, Translation,
+    TranslationRequest, Translator, Usage,
+};
+
+const MESSAGES_URL: &str = "https://api.anthropic.com/v1/messages";
+const MODELS_URL: &str = "https://api.anthropic.com/v1/models";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+const MAX_TOKENS: u32 = 8192;
+
+/// The default Claude model used when none is configured.
+pub const DEFAULT_ANTHROPIC_MODEL: &str = "claude-3-5-sonnet-latest";
+
+/// The `[anthropic]` section of the config file.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct AnthropicConfig {
+    /// The Anthropic API key. Falls back to the top-level `api_key`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    /// More API keys, used in turn with `api_key`, e.g. to spread a batch over several
+    /// accounts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub api_keys: Vec<String>,
+    /// The Claude model to use. Defaults to [`DEFAULT_ANTHROPIC_MODEL`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+/// Translator backed by the Anthropic Messages API.
+pub struct AnthropicTranslator {
+    client: reqwest::Client,
+    api_key: String,
+    model: String,
+    generation: GenerationParams,
+}
+
+impl AnthropicTranslator {
+    pub fn new(api_key: String, model: String) -> Self {
+        Self {
+            client: default_http_client(),
+            api_key,
+            model,
+            generation: GenerationParams::default(),
+        }
+    }
+
+    /// Use the given sampling settings instead of the provider's defaults.
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.generation = generation;
+        self
+    }
+
+    /// Send the requests with `client`, e.g. one with another timeout or a proxy.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+}
+
+#[async_trait]
+impl Translator for AnthropicTranslator {
+    async fn translate(&self, request: &TranslationRequest) -> Result<Translation> {
+        let mut body = json!({
+            "model": self.model,
+            "max_tokens": MAX_TOKENS,
+            "system": request.system(),
+            "messages": [{
+                "role": "user",
+                "content": request.prompt()
+            }]
+        });
+        if let Some(body) = body.as_object_mut() {
+            body.extend(self.generation.fields([
+                Some("temperature"),
+                Some("top_p"),
+                Some("max_tokens"),
+                None,
+            ]));
+        }
+
+        let response = self
+            .client
+            .post(MESSAGES_URL)
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(&body)
+            .send()
+            .await?;
+        let response = check_status(response).await?;
+
+        let response_json: serde_json::Value = response.json().await?;
+
+        if let Some(message) = response_json["error"]["message"].as_str() {
+            return Err(Error::Api(message.to_string()));
+        }
+
+        let text = response_json["content"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|block| block["text"].as_str())
+            .collect::<String>();
+
+        if text.is_empty() {
+            return Err(Error::Api("Empty response from API".to_string()));
+        }
+
+        Ok(Translation {
+            text,
+            usage: Usage::from_json(
+                &self.model,
+                &response_json["usage"],
+                "input_tokens",
+                "output_tokens",
+            ),
+            ..Default::default()
+        })
+    }
+
+    async fn models(&self) -> Result<Vec<Model>> {
+        let response = self
+            .client
+            .get(MODELS_URL)
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .send()
+            .await?;
+
+        let response_json: serde_json::Value = response.json().await?;
+
+        if let Some(message) = response_json["error"]["message"].as_str() {
+            return Err(Error::Api(message.to_string()));
+        }
+
+        Ok(response_json["data"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry["id"].as_str().map(Model::new))
+            .collect())
+    }
+}
@@ -0,0 +1,230 @@
+use std::env;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use hmac::{Hmac, KeyInit, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    check_status, default_http_client, Error, GenerationParams, Result, Translation,
+    TranslationRequest, Translator, Usage,
+};
+
+const SERVICE: &str = "bedrock";
+
+/// The default Bedrock model used when none is configured.
+pub const DEFAULT_BEDROCK_MODEL: &str = "anthropic.claude-3-5-sonnet-20240620-v1:0";
+
+/// The `[bedrock]` section of the config file.
+///
+/// Credentials fall back to the standard `AWS_*` environment variables.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct BedrockConfig {
+    /// The AWS region, e.g. `us-east-1`. Falls back to `AWS_REGION`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    /// The Bedrock model id. Defaults to [`DEFAULT_BEDROCK_MODEL`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_key_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_access_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_token: Option<String>,
+}
+
+/// Translator backed by the AWS Bedrock Converse API.
+pub struct BedrockTranslator {
+    client: reqwest::Client,
+    region: String,
+    model: String,
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+    generation: GenerationParams,
+}
+
+impl BedrockTranslator {
+    pub fn new(config: &BedrockConfig) -> Result<Self> {
+        let setting =
+            |value: &Option<String>, var: &str| value.clone().or_else(|| env::var(var).ok());
+        let missing = |name: &str| Error::Custom(format!("Missing AWS {} for Bedrock", name));
+
+        Ok(Self {
+            client: default_http_client(),
+            region: setting(&config.region, "AWS_REGION").ok_or_else(|| missing("region"))?,
+            model: config
+                .model
+                .clone()
+                .unwrap_or_else(|| DEFAULT_BEDROCK_MODEL.to_string()),
+            access_key_id: setting(&config.access_key_id, "AWS_ACCESS_KEY_ID")
+                .ok_or_else(|| missing("access key id"))?,
+            secret_access_key: setting(&config.secret_access_key, "AWS_SECRET_ACCESS_KEY")
+                .ok_or_else(|| missing("secret access key"))?,
+            session_token: setting(&config.session_token, "AWS_SESSION_TOKEN"),
+            generation: GenerationParams::default(),
+        })
+    }
+
+    /// Use the given sampling settings instead of the provider's defaults.
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.generation = generation;
+        self
+    }
+
+    /// Build the SigV4 `Authorization` header for a POST request.
+    fn authorization(&self, host: &str, path: &str, amz_date: &str, payload: &[u8]) -> String {
+        let date = &amz_date[..8];
+
+        let mut headers = vec![
+            ("content-type", "application/json".to_string()),
+            ("host", host.to_string()),
+            ("x-amz-date", amz_date.to_string()),
+        ];
+        if let Some(token) = &self.session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        let canonical_headers: String = headers
+            .iter()
+            .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+            .collect();
+        let signed_headers = headers
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(";");
+
+        // Non-S3 services expect every path segment to be URI-encoded twice.
+        let canonical_uri = path
+            .split('/')
+            .map(uri_encode)
+            .collect::<Vec<_>>()
+            .join("/");
+        let canonical_request = format!(
+            "POST\n{}\n\n{}\n{}\n{}",
+            canonical_uri,
+            canonical_headers,
+            signed_headers,
+            hex::encode(Sha256::digest(payload))
+        );
+
+        let scope = format!("{}/{}/{}/aws4_request", date, self.region, SERVICE);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let key = hmac_sha256(
+            format!("AWS4{}", self.secret_access_key).as_bytes(),
+            date.as_bytes(),
+        );
+        let key = hmac_sha256(&key, self.region.as_bytes());
+        let key = hmac_sha256(&key, SERVICE.as_bytes());
+        let key = hmac_sha256(&key, b"aws4_request");
+        let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.access_key_id, scope, signed_headers, signature
+        )
+    }
+
+    /// Send the requests with `client`, e.g. one with another timeout or a proxy.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Percent-encode everything except the unreserved characters, as required by SigV4.
+fn uri_encode(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+#[async_trait]
+impl Translator for BedrockTranslator {
+    async fn translate(&self, request: &TranslationRequest) -> Result<Translation> {
+        let host = format!("bedrock-runtime.{}.amazonaws.com", self.region);
+        let path = format!("/model/{}/converse", uri_encode(&self.model));
+        let payload = serde_json::to_vec(&json!({
+            "system": [{ "text": request.system() }],
+            "inferenceConfig": self.generation.fields([
+                Some("temperature"),
+                Some("topP"),
+                Some("maxTokens"),
+                None,
+            ]),
+            "messages": [{
+                "role": "user",
+                "content": [{ "text": request.prompt() }]
+            }]
+        }))
+        .map_err(|e| Error::Custom(e.to_string()))?;
+
+        let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let authorization = self.authorization(&host, &path, &amz_date, &payload);
+
+        let mut builder = self
+            .client
+            .post(format!("https://{}{}", host, path))
+            .header("content-type", "application/json")
+            .header("x-amz-date", &amz_date)
+            .header("authorization", authorization);
+        if let Some(token) = &self.session_token {
+            builder = builder.header("x-amz-security-token", token);
+        }
+
+        let response = check_status(builder.body(payload).send().await?).await?;
+        let status = response.status();
+        let response_json: serde_json::Value = response.json().await.unwrap_or_default();
+
+        if !status.is_success() {
+            let message = response_json["message"]
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| status.to_string());
+            return Err(Error::Api(message));
+        }
+
+        let text = response_json["output"]["message"]["content"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|block| block["text"].as_str())
+            .collect::<String>();
+
+        if text.is_empty() {
+            return Err(Error::Api("Empty response from API".to_string()));
+        }
+
+        Ok(Translation {
+            text,
+            usage: Usage::from_json(
+                &self.model,
+                &response_json["usage"],
+                "inputTokens",
+                "outputTokens",
+            ),
+            ..Default::default()
+        })
+    }
+}
@@ -0,0 +1,50 @@
+use async_trait::async_trait;
+use tokio::sync::Semaphore;
+
+use crate::{Model, OnChunk, Result, Translation, TranslationRequest, Translator};
+
+/// Translator that sends at most a given number of requests to the provider at a time, however
+/// many files, segments and chunks are translated concurrently above it, so that `--jobs` is
+/// the number of requests in flight rather than a limit per level.
+pub struct ConcurrencyLimitTranslator {
+    inner: Box<dyn Translator>,
+    semaphore: Semaphore,
+}
+
+impl ConcurrencyLimitTranslator {
+    pub fn new(inner: Box<dyn Translator>, jobs: usize) -> Self {
+        Self {
+            inner,
+            semaphore: Semaphore::new(jobs.max(1)),
+        }
+    }
+}
+
+#[async_trait]
+impl Translator for ConcurrencyLimitTranslator {
+    async fn translate(&self, request: &TranslationRequest) -> Result<Translation> {
+        let _permit = self
+            .semaphore
+            .acquire()
+            .await
+            .expect("semaphore is never closed");
+        self.inner.translate(request).await
+    }
+
+    async fn translate_stream(
+        &self,
+        request: &TranslationRequest,
+        on_chunk: OnChunk<'_>,
+    ) -> Result<Translation> {
+        let _permit = self
+            .semaphore
+            .acquire()
+            .await
+            .expect("semaphore is never closed");
+        self.inner.translate_stream(request, on_chunk).await
+    }
+
+    async fn models(&self) -> Result<Vec<Model>> {
+        self.inner.models().await
+    }
+}
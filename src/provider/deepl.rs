@@ -0,0 +1,117 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    check_status, default_http_client, Error, Result, Tone, Translation, TranslationRequest,
+    Translator,
+};
+
+const PRO_URL: &str = "https://api.deepl.com/v2/translate";
+const FREE_URL: &str = "https://api-free.deepl.com/v2/translate";
+
+/// The `[deepl]` section of the config file.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct DeepLConfig {
+    /// The DeepL authentication key. Falls back to the top-level `api_key`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    /// More API keys, used in turn with `api_key`, e.g. to spread a batch over several
+    /// accounts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub api_keys: Vec<String>,
+}
+
+/// Translator backed by the DeepL REST API.
+pub struct DeepLTranslator {
+    client: reqwest::Client,
+    api_key: String,
+}
+
+impl DeepLTranslator {
+    pub fn new(api_key: String) -> Self {
+        Self {
+            client: default_http_client(),
+            api_key,
+        }
+    }
+
+    /// Free plan keys end with `:fx` and must use a dedicated host.
+    fn url(&self) -> &'static str {
+        if self.api_key.ends_with(":fx") {
+            FREE_URL
+        } else {
+            PRO_URL
+        }
+    }
+
+    /// Send the requests with `client`, e.g. one with another timeout or a proxy.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+}
+
+#[async_trait]
+impl Translator for DeepLTranslator {
+    async fn translate(&self, request: &TranslationRequest) -> Result<Translation> {
+        let mut body = json!({
+            "text": [request.text],
+            // e.g. `PT-BR` for `pt_BR`, as DeepL only reads `-` as the region separator.
+            "target_lang": request.to.replace('_', "-").to_uppercase(),
+        });
+        if let Some(from) = &request.from {
+            // DeepL only accepts the base language (e.g. `EN`, not `EN-US`) as source.
+            let base = from.split(['-', '_']).next().unwrap_or(from);
+            body["source_lang"] = json!(base.to_uppercase());
+        }
+        let context = [request.hint.as_deref(), request.preceding.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        if !context.is_empty() {
+            body["context"] = json!(context.join("\n\n"));
+        }
+        // The `prefer_` values fall back to the default for languages without formality.
+        match request.tone {
+            Some(Tone::Formal) => body["formality"] = json!("prefer_more"),
+            Some(Tone::Informal | Tone::Casual) => body["formality"] = json!("prefer_less"),
+            _ => {}
+        }
+
+        let response = self
+            .client
+            .post(self.url())
+            .header("Authorization", format!("DeepL-Auth-Key {}", self.api_key))
+            .json(&body)
+            .send()
+            .await?;
+        let response = check_status(response).await?;
+
+        let status = response.status();
+        let response_json: serde_json::Value = response.json().await.unwrap_or_default();
+
+        if !status.is_success() {
+            let message = response_json["message"]
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| status.to_string());
+            return Err(Error::Api(message));
+        }
+
+        let translation = &response_json["translations"][0];
+        let text = translation["text"].as_str().unwrap_or_default().to_string();
+
+        if text.is_empty() {
+            return Err(Error::Api("Empty response from API".to_string()));
+        }
+
+        Ok(Translation {
+            text,
+            detected_source: translation["detected_source_language"]
+                .as_str()
+                .map(str::to_lowercase),
+            ..Default::default()
+        })
+    }
+}
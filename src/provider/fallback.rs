@@ -0,0 +1,91 @@
+use async_trait::async_trait;
+use tracing::warn;
+
+use crate::{
+    Config, Error, Model, OnChunk, ProviderKind, Result, Translation, TranslationRequest,
+    Translator,
+};
+
+/// Translator that tries each provider of a chain in order until one succeeds.
+pub struct FallbackTranslator {
+    chain: Vec<(ProviderKind, Box<dyn Translator>)>,
+}
+
+impl FallbackTranslator {
+    /// Build the translator of each provider of `chain` once, so that they keep their
+    /// connections, rate limits and API key rotation across requests. Fallback providers that
+    /// cannot be built, e.g. for lack of settings, are left out of the chain.
+    pub fn new(config: &Config, chain: Vec<ProviderKind>) -> Result<Self> {
+        let mut translators = Vec::with_capacity(chain.len());
+        for (index, kind) in chain.into_iter().enumerate() {
+            match crate::translator(config, kind) {
+                Ok(translator) => translators.push((kind, translator)),
+                Err(e) if index > 0 => warn!("Fallback provider {} is unavailable: {}", kind, e),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(Self { chain: translators })
+    }
+
+    async fn run(
+        &self,
+        request: &TranslationRequest,
+        mut on_chunk: Option<OnChunk<'_>>,
+    ) -> Result<Translation> {
+        let mut failures = Vec::new();
+
+        for (kind, translator) in &self.chain {
+            let mut streamed = false;
+            let result = match on_chunk.as_deref_mut() {
+                Some(on_chunk) => {
+                    translator
+                        .translate_stream(request, &mut |chunk| {
+                            streamed = true;
+                            on_chunk(chunk);
+                        })
+                        .await
+                }
+                None => translator.translate(request).await,
+            };
+
+            match result {
+                Ok(mut translation) => {
+                    translation.provider = Some(*kind);
+                    return Ok(translation);
+                }
+                // Part of the translation was already passed on, so another provider would
+                // repeat it.
+                Err(e) if streamed => return Err(e),
+                Err(e) => {
+                    warn!("{} failed: {}", kind, e);
+                    failures.push(format!("{}: {}", kind, e));
+                }
+            }
+        }
+
+        Err(Error::Api(format!(
+            "All providers failed ({})",
+            failures.join("; ")
+        )))
+    }
+}
+
+#[async_trait]
+impl Translator for FallbackTranslator {
+    async fn translate(&self, request: &TranslationRequest) -> Result<Translation> {
+        self.run(request, None).await
+    }
+
+    async fn translate_stream(
+        &self,
+        request: &TranslationRequest,
+        on_chunk: OnChunk<'_>,
+    ) -> Result<Translation> {
+        self.run(request, Some(on_chunk)).await
+    }
+
+    /// The models of the primary provider.
+    async fn models(&self) -> Result<Vec<Model>> {
+        self.chain[0].1.models().await
+    }
+}
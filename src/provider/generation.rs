@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// The `[generation]` section of the config file: sampling settings for language models.
+///
+/// Unset parameters are left to the provider's defaults. Providers without an equivalent
+/// setting, such as `seed` on Anthropic, ignore it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GenerationParams {
+    /// The sampling temperature. Lower values give more literal, repeatable translations.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    /// Nucleus sampling: only the tokens within this cumulative probability are considered.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    /// The maximum number of tokens generated for a single request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u32>,
+    /// The seed for deterministic sampling, where the provider supports it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+impl GenerationParams {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// The parameters that are set, under the names the provider gives to temperature, top_p,
+    /// max_tokens and seed, in that order. Parameters the provider does not support are `None`.
+    pub(crate) fn fields(&self, names: [Option<&str>; 4]) -> Map<String, Value> {
+        let [temperature, top_p, max_tokens, seed] = names;
+        [
+            (temperature, self.temperature.map(Value::from)),
+            (top_p, self.top_p.map(Value::from)),
+            (max_tokens, self.max_tokens.map(Value::from)),
+            (seed, self.seed.map(Value::from)),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| Some((name?.to_string(), value?)))
+        .collect()
+    }
+}
@@ -0,0 +1,205 @@
+use std::{
+    fs,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::Mutex;
+
+use crate::{
+    check_status, default_http_client, Error, Result, Translation, TranslationRequest, Translator,
+};
+
+const TRANSLATE_URL: &str = "https://translation.googleapis.com/v3";
+const SCOPE: &str = "https://www.googleapis.com/auth/cloud-translation";
+const DEFAULT_TOKEN_URI: &str = "https://oauth2.googleapis.com/token";
+
+/// The `[google]` section of the config file.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct GoogleConfig {
+    /// Path to the service account JSON key.
+    pub credentials: PathBuf,
+    /// The GCP project. Defaults to the `project_id` of the service account.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<String>,
+    /// The API location. Defaults to `global`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ServiceAccountKey {
+    client_email: String,
+    private_key: String,
+    project_id: Option<String>,
+    token_uri: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Claims<'a> {
+    iss: &'a str,
+    scope: &'a str,
+    aud: &'a str,
+    iat: u64,
+    exp: u64,
+}
+
+struct AccessToken {
+    token: String,
+    expires_at: SystemTime,
+}
+
+/// Translator backed by the Google Cloud Translation v3 API.
+pub struct GoogleTranslator {
+    client: reqwest::Client,
+    key: ServiceAccountKey,
+    project_id: String,
+    location: String,
+    token: Mutex<Option<AccessToken>>,
+}
+
+impl GoogleTranslator {
+    pub fn new(config: &GoogleConfig) -> Result<Self> {
+        let contents = fs::read_to_string(&config.credentials)?;
+        let key: ServiceAccountKey = serde_json::from_str(&contents).map_err(|e| {
+            Error::Custom(format!(
+                "Invalid service account key {}: {}",
+                config.credentials.display(),
+                e
+            ))
+        })?;
+        let project_id = config
+            .project_id
+            .clone()
+            .or_else(|| key.project_id.clone())
+            .ok_or_else(|| Error::Custom("Missing Google Cloud project id".to_string()))?;
+
+        Ok(Self {
+            client: default_http_client(),
+            key,
+            project_id,
+            location: config
+                .location
+                .clone()
+                .unwrap_or_else(|| "global".to_string()),
+            token: Mutex::new(None),
+        })
+    }
+
+    /// Exchange a signed JWT for an OAuth access token, reusing it until it expires.
+    async fn access_token(&self) -> Result<String> {
+        let mut cached = self.token.lock().await;
+        if let Some(token) = cached.as_ref() {
+            if token.expires_at > SystemTime::now() {
+                return Ok(token.token.clone());
+            }
+        }
+
+        let token_uri = self.key.token_uri.as_deref().unwrap_or(DEFAULT_TOKEN_URI);
+        let iat = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let claims = Claims {
+            iss: &self.key.client_email,
+            scope: SCOPE,
+            aud: token_uri,
+            iat,
+            exp: iat + 3600,
+        };
+        let encoding_key = EncodingKey::from_rsa_pem(self.key.private_key.as_bytes())
+            .map_err(|e| Error::Custom(format!("Invalid service account private key: {}", e)))?;
+        let assertion =
+            jsonwebtoken::encode(&Header::new(Algorithm::RS256), &claims, &encoding_key)
+                .map_err(|e| Error::Custom(format!("Failed to sign token request: {}", e)))?;
+
+        let response: serde_json::Value = self
+            .client
+            .post(token_uri)
+            .form(&[
+                ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+                ("assertion", &assertion),
+            ])
+            .send()
+            .await?
+            .json()
+            .await?;
+
+        let Some(token) = response["access_token"].as_str() else {
+            let message = response["error_description"]
+                .as_str()
+                .unwrap_or("Failed to obtain an access token");
+            return Err(Error::Api(message.to_string()));
+        };
+        let expires_in = response["expires_in"].as_u64().unwrap_or(3600);
+
+        *cached = Some(AccessToken {
+            token: token.to_string(),
+            // Refresh a minute early to avoid using a token right as it expires.
+            expires_at: SystemTime::now() + Duration::from_secs(expires_in.saturating_sub(60)),
+        });
+
+        Ok(token.to_string())
+    }
+
+    /// Send the requests with `client`, e.g. one with another timeout or a proxy.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+}
+
+#[async_trait]
+impl Translator for GoogleTranslator {
+    async fn translate(&self, request: &TranslationRequest) -> Result<Translation> {
+        let token = self.access_token().await?;
+        let url = format!(
+            "{}/projects/{}/locations/{}:translateText",
+            TRANSLATE_URL, self.project_id, self.location
+        );
+
+        let mut body = json!({
+            "contents": [request.text],
+            "targetLanguageCode": request.to,
+            "mimeType": "text/plain",
+        });
+        if let Some(from) = &request.from {
+            body["sourceLanguageCode"] = json!(from);
+        }
+
+        let response = self
+            .client
+            .post(url)
+            .bearer_auth(token)
+            .json(&body)
+            .send()
+            .await?;
+        let response_json: serde_json::Value = check_status(response).await?.json().await?;
+
+        if let Some(message) = response_json["error"]["message"].as_str() {
+            return Err(Error::Api(message.to_string()));
+        }
+
+        let translation = &response_json["translations"][0];
+        let text = translation["translatedText"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+
+        if text.is_empty() {
+            return Err(Error::Api("Empty response from API".to_string()));
+        }
+
+        Ok(Translation {
+            text,
+            detected_source: translation["detectedLanguageCode"]
+                .as_str()
+                .map(str::to_string),
+            ..Default::default()
+        })
+    }
+}
@@ -0,0 +1,80 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    check_status, default_http_client, Error, Result, Translation, TranslationRequest, Translator,
+};
+
+/// The `[libretranslate]` section of the config file.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct LibreTranslateConfig {
+    /// The URL of the LibreTranslate instance, e.g. `http://localhost:5000`.
+    pub url: String,
+    /// The API key, for instances that require one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+}
+
+/// Translator backed by a LibreTranslate instance.
+pub struct LibreTranslateTranslator {
+    client: reqwest::Client,
+    url: String,
+    api_key: Option<String>,
+}
+
+impl LibreTranslateTranslator {
+    pub fn new(config: &LibreTranslateConfig) -> Self {
+        Self {
+            client: default_http_client(),
+            url: format!("{}/translate", config.url.trim_end_matches('/')),
+            api_key: config.api_key.clone(),
+        }
+    }
+
+    /// Send the requests with `client`, e.g. one with another timeout or a proxy.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+}
+
+#[async_trait]
+impl Translator for LibreTranslateTranslator {
+    async fn translate(&self, request: &TranslationRequest) -> Result<Translation> {
+        let mut body = json!({
+            "q": request.text,
+            "source": request.from.as_deref().unwrap_or("auto"),
+            "target": request.to,
+            "format": "text",
+        });
+        if let Some(api_key) = &self.api_key {
+            body["api_key"] = json!(api_key);
+        }
+
+        let response = self.client.post(&self.url).json(&body).send().await?;
+        let response = check_status(response).await?;
+        let response_json: serde_json::Value = response.json().await?;
+
+        if let Some(message) = response_json["error"].as_str() {
+            return Err(Error::Api(message.to_string()));
+        }
+
+        let text = response_json["translatedText"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+
+        if text.is_empty() {
+            return Err(Error::Api("Empty response from API".to_string()));
+        }
+
+        Ok(Translation {
+            text,
+            detected_source: response_json["detectedLanguage"]["language"]
+                .as_str()
+                .map(str::to_string),
+            ..Default::default()
+        })
+    }
+}
@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+/// A model offered by a provider.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Model {
+    pub id: String,
+    /// The context window, in tokens, when the provider reports it or the model is well known.
+    pub context_window: Option<u64>,
+    /// The list price, when the provider reports it or the model is well known.
+    pub pricing: Option<ModelPricing>,
+}
+
+/// The price of a model, in US dollars per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPricing {
+    pub input: f64,
+    pub output: f64,
+}
+
+/// Context windows and list prices of well-known models, matched by the longest id prefix so
+/// dated snapshots such as `gpt-4o-2024-08-06` are covered too. These are hints only: prices
+/// change, so check the provider's pricing page before relying on them.
+const KNOWN_MODELS: &[(&str, u64, f64, f64)] = &[
+    ("gpt-4o", 128_000, 2.5, 10.0),
+    ("gpt-4o-mini", 128_000, 0.15, 0.6),
+    ("gpt-4.1", 1_047_576, 2.0, 8.0),
+    ("gpt-4.1-mini", 1_047_576, 0.4, 1.6),
+    ("gpt-4.1-nano", 1_047_576, 0.1, 0.4),
+    ("gpt-4-turbo", 128_000, 10.0, 30.0),
+    ("gpt-4", 8_192, 30.0, 60.0),
+    ("gpt-3.5-turbo", 16_385, 0.5, 1.5),
+    ("o1", 200_000, 15.0, 60.0),
+    ("o1-mini", 128_000, 1.1, 4.4),
+    ("o3-mini", 200_000, 1.1, 4.4),
+    ("claude-3-5-sonnet", 200_000, 3.0, 15.0),
+    ("claude-3-5-haiku", 200_000, 0.8, 4.0),
+    ("claude-3-opus", 200_000, 15.0, 75.0),
+    ("claude-3-haiku", 200_000, 0.25, 1.25),
+];
+
+impl Model {
+    /// A model with the hints known for its id, if any.
+    pub fn new(id: impl Into<String>) -> Self {
+        let id = id.into();
+        let known = KNOWN_MODELS
+            .iter()
+            .filter(|(prefix, ..)| id.starts_with(prefix))
+            .max_by_key(|(prefix, ..)| prefix.len());
+
+        Self {
+            context_window: known.map(|(_, context_window, ..)| *context_window),
+            pricing: known.map(|(_, _, input, output)| ModelPricing {
+                input: *input,
+                output: *output,
+            }),
+            id,
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    check_status, default_http_client, Error, GenerationParams, Model, Result, Translation,
+    TranslationRequest, Translator, Usage,
+};
+
+const DEFAULT_HOST: &str = "localhost";
+const DEFAULT_PORT: u16 = 11434;
+
+/// The default local model used when none is configured.
+pub const DEFAULT_OLLAMA_MODEL: &str = "llama3";
+
+/// The `[ollama]` section of the config file.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct OllamaConfig {
+    /// The host running the Ollama server. Defaults to `localhost`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    /// The port of the Ollama server. Defaults to `11434`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    /// The local model to use. Defaults to [`DEFAULT_OLLAMA_MODEL`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+impl OllamaConfig {
+    /// The base URL of the server, e.g. `http://localhost:11434`.
+    pub fn url(&self) -> String {
+        let host = self.host.as_deref().unwrap_or(DEFAULT_HOST);
+        let port = self.port.unwrap_or(DEFAULT_PORT);
+        if host.starts_with("http://") || host.starts_with("https://") {
+            format!("{}:{}", host.trim_end_matches('/'), port)
+        } else {
+            format!("http://{}:{}", host, port)
+        }
+    }
+}
+
+/// Translator backed by a local Ollama server.
+pub struct OllamaTranslator {
+    client: reqwest::Client,
+    url: String,
+    tags_url: String,
+    model: String,
+    generation: GenerationParams,
+}
+
+impl OllamaTranslator {
+    pub fn new(config: &OllamaConfig) -> Self {
+        Self {
+            client: default_http_client(),
+            url: format!("{}/api/chat", config.url()),
+            tags_url: format!("{}/api/tags", config.url()),
+            model: config
+                .model
+                .clone()
+                .unwrap_or_else(|| DEFAULT_OLLAMA_MODEL.to_string()),
+            generation: GenerationParams::default(),
+        }
+    }
+
+    /// Use the given sampling settings instead of the provider's defaults.
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.generation = generation;
+        self
+    }
+
+    /// Send the requests with `client`, e.g. one with another timeout or a proxy.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+}
+
+#[async_trait]
+impl Translator for OllamaTranslator {
+    async fn translate(&self, request: &TranslationRequest) -> Result<Translation> {
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&json!({
+                "model": self.model,
+                "stream": false,
+                "options": self.generation.fields([
+                    Some("temperature"),
+                    Some("top_p"),
+                    Some("num_predict"),
+                    Some("seed"),
+                ]),
+                "messages": [{
+                    "role": "system",
+                    "content": request.system()
+                }, {
+                    "role": "user",
+                    "content": request.prompt()
+                }]
+            }))
+            .send()
+            .await?;
+        let response = check_status(response).await?;
+
+        let response_json: serde_json::Value = response.json().await?;
+
+        if let Some(message) = response_json["error"].as_str() {
+            return Err(Error::Api(message.to_string()));
+        }
+
+        let text = response_json["message"]["content"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+
+        if text.is_empty() {
+            return Err(Error::Api("Empty response from API".to_string()));
+        }
+
+        Ok(Translation {
+            text,
+            usage: Usage::from_json(
+                &self.model,
+                &response_json,
+                "prompt_eval_count",
+                "eval_count",
+            ),
+            ..Default::default()
+        })
+    }
+
+    async fn models(&self) -> Result<Vec<Model>> {
+        let response = self.client.get(&self.tags_url).send().await?;
+        let response_json: serde_json::Value = response.json().await?;
+
+        if let Some(message) = response_json["error"].as_str() {
+            return Err(Error::Api(message.to_string()));
+        }
+
+        Ok(response_json["models"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry["name"].as_str().map(Model::new))
+            .collect())
+    }
+}
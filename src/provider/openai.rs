@@ -0,0 +1,306 @@
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::{
+    check_status, default_http_client, Config, Error, GenerationParams, Model, ModelPricing,
+    OnChunk, RateLimit, Result, Translation, TranslationRequest, Translator, Usage,
+};
+
+/// The base URL of the official OpenAI API.
+pub const DEFAULT_OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+/// The default OpenAI model used when none is configured.
+pub const DEFAULT_OPENAI_MODEL: &str = "gpt-4o-mini";
+const DEFAULT_AZURE_API_VERSION: &str = "2024-06-01";
+
+/// The `[azure]` section of the config file.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct AzureConfig {
+    /// The resource endpoint, e.g. `https://my-resource.openai.azure.com`.
+    pub endpoint: String,
+    /// The name of the model deployment.
+    pub deployment: String,
+    /// The `api-version` query parameter. Defaults to a recent stable version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_version: Option<String>,
+    /// The Azure API key. Falls back to the top-level `api_key`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    /// More API keys, used in turn with `api_key`, e.g. to spread a batch over several
+    /// accounts.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub api_keys: Vec<String>,
+}
+
+/// How the API key is sent to the server.
+enum Auth {
+    /// `Authorization: Bearer <key>`, used by OpenAI.
+    Bearer(String),
+    /// `api-key: <key>`, used by Azure OpenAI.
+    ApiKeyHeader(String),
+}
+
+/// Translator backed by the OpenAI chat completions API, or a compatible deployment.
+pub struct OpenAiTranslator {
+    client: reqwest::Client,
+    url: String,
+    /// The model list endpoint. Azure deployments are bound to a single model and have none.
+    models_url: Option<String>,
+    auth: Auth,
+    model: String,
+    generation: GenerationParams,
+    rate_limit: RateLimit,
+}
+
+impl OpenAiTranslator {
+    pub fn new(api_key: String, model: String) -> Self {
+        Self::with_base_url(DEFAULT_OPENAI_BASE_URL, api_key, model)
+    }
+
+    /// Build a translator for any OpenAI-compatible server, e.g. OpenRouter, LM Studio or vLLM.
+    pub fn with_base_url(base_url: &str, api_key: String, model: String) -> Self {
+        let base_url = base_url.trim_end_matches('/');
+        Self {
+            client: default_http_client(),
+            url: format!("{}/chat/completions", base_url),
+            models_url: Some(format!("{}/models", base_url)),
+            auth: Auth::Bearer(api_key),
+            model,
+            generation: GenerationParams::default(),
+            rate_limit: RateLimit::default(),
+        }
+    }
+
+    pub fn from_config(config: &Config) -> Self {
+        Self::with_base_url(
+            config
+                .base_url
+                .as_deref()
+                .unwrap_or(DEFAULT_OPENAI_BASE_URL),
+            config.api_key.clone(),
+            config.model.clone(),
+        )
+        .with_generation(config.generation)
+    }
+
+    /// Build a translator for an Azure OpenAI deployment.
+    pub fn azure(azure: &AzureConfig, api_key: String, model: String) -> Self {
+        let url = format!(
+            "{}/openai/deployments/{}/chat/completions?api-version={}",
+            azure.endpoint.trim_end_matches('/'),
+            azure.deployment,
+            azure
+                .api_version
+                .as_deref()
+                .unwrap_or(DEFAULT_AZURE_API_VERSION)
+        );
+
+        Self {
+            client: default_http_client(),
+            url,
+            models_url: None,
+            auth: Auth::ApiKeyHeader(api_key),
+            model,
+            generation: GenerationParams::default(),
+            rate_limit: RateLimit::default(),
+        }
+    }
+
+    /// Use the given sampling settings instead of the provider's defaults.
+    pub fn with_generation(mut self, generation: GenerationParams) -> Self {
+        self.generation = generation;
+        self
+    }
+
+    fn authorize(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.auth {
+            Auth::Bearer(api_key) => builder.header("Authorization", format!("Bearer {}", api_key)),
+            Auth::ApiKeyHeader(api_key) => builder.header("api-key", api_key),
+        }
+    }
+
+    async fn send(&self, request: &TranslationRequest, stream: bool) -> Result<reqwest::Response> {
+        let mut body = json!({
+            "model": self.model,
+            "stream": stream,
+            "messages": [{
+                "role": "system",
+                "content": request.system()
+            }, {
+                "role": "user",
+                "content": request.prompt()
+            }]
+        });
+        if let Some(body) = body.as_object_mut() {
+            body.extend(self.generation.fields([
+                Some("temperature"),
+                Some("top_p"),
+                Some("max_tokens"),
+                Some("seed"),
+            ]));
+            // Streams only report the tokens used when asked to, in a last event.
+            if stream {
+                body.insert(
+                    "stream_options".to_string(),
+                    json!({ "include_usage": true }),
+                );
+            }
+        }
+
+        self.rate_limit.wait().await;
+        let response = self
+            .authorize(self.client.post(&self.url))
+            .json(&body)
+            .send()
+            .await?;
+        self.rate_limit.update(&response);
+
+        check_status(response).await
+    }
+
+    /// Send the requests with `client`, e.g. one with another timeout or a proxy.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+}
+
+#[async_trait]
+impl Translator for OpenAiTranslator {
+    async fn translate(&self, request: &TranslationRequest) -> Result<Translation> {
+        let response = self.send(request, false).await?;
+        let response_json: serde_json::Value = response.json().await?;
+
+        if let Some(message) = response_json["error"]["message"].as_str() {
+            return Err(Error::Api(message.to_string()));
+        }
+
+        let text = response_json["choices"][0]["message"]["content"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+
+        if text.is_empty() {
+            return Err(Error::Api("Empty response from API".to_string()));
+        }
+
+        Ok(Translation {
+            text,
+            usage: Usage::from_json(
+                &self.model,
+                &response_json["usage"],
+                "prompt_tokens",
+                "completion_tokens",
+            ),
+            ..Default::default()
+        })
+    }
+
+    async fn translate_stream(
+        &self,
+        request: &TranslationRequest,
+        on_chunk: OnChunk<'_>,
+    ) -> Result<Translation> {
+        let response = self.send(request, true).await?;
+
+        if !response.status().is_success() {
+            let response_json: serde_json::Value = response.json().await?;
+            let message = response_json["error"]["message"]
+                .as_str()
+                .unwrap_or("Request failed");
+            return Err(Error::Api(message.to_string()));
+        }
+
+        let mut text = String::new();
+        let mut usage = None;
+        let mut buffer = Vec::new();
+        let mut stream = response.bytes_stream();
+
+        // Server-sent events: one `data: <json>` line per event, ending with `data: [DONE]`.
+        'events: while let Some(bytes) = stream.next().await {
+            buffer.extend_from_slice(&bytes?);
+
+            while let Some(newline) = buffer.iter().position(|byte| *byte == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=newline).collect();
+                let line = String::from_utf8_lossy(&line);
+                let line = line.trim();
+
+                let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+                    continue;
+                };
+                if data == "[DONE]" {
+                    break 'events;
+                }
+
+                let event: serde_json::Value = serde_json::from_str(data)
+                    .map_err(|e| Error::Api(format!("Invalid stream event: {}", e)))?;
+                if let Some(message) = event["error"]["message"].as_str() {
+                    return Err(Error::Api(message.to_string()));
+                }
+                if let Some(delta) = event["choices"][0]["delta"]["content"].as_str() {
+                    on_chunk(delta);
+                    text.push_str(delta);
+                }
+                usage = usage.or_else(|| {
+                    Usage::from_json(
+                        &self.model,
+                        &event["usage"],
+                        "prompt_tokens",
+                        "completion_tokens",
+                    )
+                });
+            }
+        }
+
+        if text.is_empty() {
+            return Err(Error::Api("Empty response from API".to_string()));
+        }
+
+        Ok(Translation {
+            text,
+            usage,
+            ..Default::default()
+        })
+    }
+
+    async fn models(&self) -> Result<Vec<Model>> {
+        let Some(url) = &self.models_url else {
+            return Ok(vec![Model::new(self.model.clone())]);
+        };
+
+        let response = self.authorize(self.client.get(url)).send().await?;
+        let response_json: serde_json::Value = response.json().await?;
+
+        if let Some(message) = response_json["error"]["message"].as_str() {
+            return Err(Error::Api(message.to_string()));
+        }
+
+        let mut models = response_json["data"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                let mut model = Model::new(entry["id"].as_str()?);
+                // OpenRouter and some other compatible servers describe their models in full.
+                if let Some(context_window) = entry["context_length"].as_u64() {
+                    model.context_window = Some(context_window);
+                }
+                let price = |key: &str| {
+                    let price = &entry["pricing"][key];
+                    price
+                        .as_f64()
+                        .or_else(|| price.as_str()?.parse().ok())
+                        .map(|per_token: f64| per_token * 1_000_000.0)
+                };
+                if let (Some(input), Some(output)) = (price("prompt"), price("completion")) {
+                    model.pricing = Some(ModelPricing { input, output });
+                }
+                Some(model)
+            })
+            .collect::<Vec<_>>();
+        models.sort_by(|a, b| a.id.cmp(&b.id));
+
+        Ok(models)
+    }
+}
@@ -0,0 +1,266 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use reqwest::header::HeaderMap;
+use tracing::{debug, info, warn};
+
+use crate::{Error, Model, OnChunk, Result, Translation, TranslationRequest, Translator};
+
+/// How many times a request is sent before giving up, unless configured otherwise.
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+/// The delay before the first retry, doubled for every further retry.
+const BASE_DELAY: Duration = Duration::from_millis(500);
+/// The longest delay between two attempts.
+const MAX_DELAY: Duration = Duration::from_secs(30);
+/// The longest the server can make requests wait, however long it asks for.
+const MAX_PAUSE: Duration = Duration::from_secs(300);
+
+/// Fail with [`Error::Transient`] when the server answered with a status worth retrying: too
+/// many requests or a server error, and with [`Error::Unauthorized`] when it rejected the
+/// credentials. Any other response is returned for the provider to read.
+pub(crate) async fn check_status(response: reqwest::Response) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.as_u16() == 401 || status.as_u16() == 403 {
+        let message = response.text().await.unwrap_or_default();
+        return Err(Error::Unauthorized(status.as_u16(), message));
+    }
+    if status.as_u16() == 429 || status.is_server_error() {
+        let wait = retry_after(response.headers());
+        let message = response.text().await.unwrap_or_default();
+        return Err(Error::Transient(status.as_u16(), message, wait));
+    }
+    Ok(response)
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name)?.to_str().ok().map(str::trim)
+}
+
+/// How long the server asks to wait before the next request, from its `Retry-After` header
+/// (in seconds or as a date) or, failing that, from the reset times of OpenAI's rate limits.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    if let Some(value) = header(headers, "retry-after") {
+        if let Ok(seconds) = value.parse::<f64>() {
+            return Duration::try_from_secs_f64(seconds).ok();
+        }
+        if let Ok(date) = DateTime::parse_from_rfc2822(value) {
+            return (date.with_timezone(&Utc) - Utc::now()).to_std().ok();
+        }
+    }
+    ["x-ratelimit-reset-requests", "x-ratelimit-reset-tokens"]
+        .into_iter()
+        .filter_map(|name| parse_reset(header(headers, name)?))
+        .max()
+}
+
+/// Parse the reset times of OpenAI's rate limits, e.g. `1s`, `6m0s` or `120ms`.
+fn parse_reset(value: &str) -> Option<Duration> {
+    let mut total = Duration::ZERO;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let number: f64 = rest[..number_end].parse().ok()?;
+        rest = &rest[number_end..];
+        let unit_end = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let seconds = match &rest[..unit_end] {
+            "ms" => number / 1000.0,
+            "s" => number,
+            "m" => number * 60.0,
+            "h" => number * 3600.0,
+            _ => return None,
+        };
+        total += Duration::try_from_secs_f64(seconds).ok()?;
+        rest = &rest[unit_end..];
+    }
+    Some(total)
+}
+
+/// Holds back every request to a provider while its rate limit is used up, as reported by
+/// the `x-ratelimit-*` headers of OpenAI-compatible APIs, so that concurrent requests wait
+/// for the limit to reset instead of failing one after the other.
+#[derive(Default)]
+pub(crate) struct RateLimit {
+    paused_until: Mutex<Option<Instant>>,
+}
+
+impl RateLimit {
+    /// Wait until the rate limit has reset, if it was used up.
+    pub(crate) async fn wait(&self) {
+        let paused_until = *self.paused_until.lock().expect("rate limit lock poisoned");
+        if let Some(paused_until) = paused_until {
+            debug!(
+                wait_ms = paused_until
+                    .saturating_duration_since(Instant::now())
+                    .as_millis() as u64,
+                "Waiting for the rate limit to reset"
+            );
+            tokio::time::sleep_until(paused_until.into()).await;
+        }
+    }
+
+    /// Pause the following requests when `response` says the rate limit is used up.
+    pub(crate) fn update(&self, response: &reqwest::Response) {
+        let headers = response.headers();
+        let limited = response.status().as_u16() == 429;
+        let exhausted = limited
+            || [
+                "x-ratelimit-remaining-requests",
+                "x-ratelimit-remaining-tokens",
+            ]
+            .into_iter()
+            .any(|name| header(headers, name) == Some("0"));
+        let Some(wait) = exhausted.then(|| retry_after(headers)).flatten() else {
+            return;
+        };
+
+        let until = Instant::now() + wait.min(MAX_PAUSE);
+        let mut paused_until = self.paused_until.lock().expect("rate limit lock poisoned");
+        if paused_until.is_none_or(|paused_until| paused_until < until) {
+            // A request that was turned away already says so when it is retried.
+            if !limited && paused_until.is_none_or(|paused_until| paused_until <= Instant::now()) {
+                warn!(
+                    "Rate limit reached; pausing requests for {:.1}s.",
+                    wait.as_secs_f64()
+                );
+            }
+            *paused_until = Some(until);
+        }
+    }
+}
+
+impl Error {
+    /// Whether the failure is likely to go away when the request is sent again, such as a
+    /// rate limit, a server error or a dropped connection.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Error::Transient(..) => true,
+            Error::RequestFailed(e) => e.is_connect() || e.is_timeout() || e.is_request(),
+            _ => false,
+        }
+    }
+}
+
+/// Translator that sends a request again when it fails with a transient error, waiting a
+/// little longer before each attempt.
+pub struct RetryTranslator {
+    inner: Box<dyn Translator>,
+    max_attempts: u32,
+}
+
+impl RetryTranslator {
+    pub fn new(inner: Box<dyn Translator>, max_attempts: u32) -> Self {
+        Self {
+            inner,
+            max_attempts: max_attempts.max(1),
+        }
+    }
+
+    /// The delay before the given retry, counting from 1: the one the server asked for, or
+    /// exponential with a random part so that concurrent requests do not all retry at once.
+    fn delay(error: &Error, retry: u32) -> Duration {
+        if let Error::Transient(_, _, Some(wait)) = error {
+            return (*wait).min(MAX_PAUSE);
+        }
+        let delay = BASE_DELAY
+            .saturating_mul(2u32.saturating_pow(retry - 1))
+            .min(MAX_DELAY);
+        delay / 2 + delay.mul_f64(rand::thread_rng().gen_range(0.0..0.5))
+    }
+
+    /// Wait before the next attempt if `error` is transient and attempts are left.
+    async fn should_retry(&self, error: &Error, attempt: u32) -> bool {
+        if !error.is_transient() || attempt >= self.max_attempts {
+            return false;
+        }
+        let delay = Self::delay(error, attempt);
+        warn!(
+            "{}; retrying in {:.1}s (attempt {} of {}).",
+            error,
+            delay.as_secs_f64(),
+            attempt + 1,
+            self.max_attempts
+        );
+        tokio::time::sleep(delay).await;
+        true
+    }
+}
+
+/// Log how long an attempt at `request` took and how it ended.
+fn log_attempt(
+    request: &TranslationRequest,
+    result: &Result<Translation>,
+    started: Instant,
+    attempt: u32,
+) {
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    let bytes = request.text.len();
+    match result {
+        Ok(translation) => info!(
+            attempt,
+            bytes,
+            elapsed_ms,
+            prompt_tokens = translation.usage.as_ref().map(|usage| usage.prompt_tokens),
+            completion_tokens = translation
+                .usage
+                .as_ref()
+                .map(|usage| usage.completion_tokens),
+            "Request succeeded"
+        ),
+        Err(e) => info!(attempt, bytes, elapsed_ms, error = %e, "Request failed"),
+    }
+}
+
+#[async_trait]
+impl Translator for RetryTranslator {
+    async fn translate(&self, request: &TranslationRequest) -> Result<Translation> {
+        let mut attempt = 1;
+        loop {
+            let started = Instant::now();
+            let result = self.inner.translate(request).await;
+            log_attempt(request, &result, started, attempt);
+            match result {
+                Err(e) if self.should_retry(&e, attempt).await => attempt += 1,
+                result => return result,
+            }
+        }
+    }
+
+    async fn translate_stream(
+        &self,
+        request: &TranslationRequest,
+        on_chunk: OnChunk<'_>,
+    ) -> Result<Translation> {
+        let mut attempt = 1;
+        loop {
+            let mut streamed = false;
+            let started = Instant::now();
+            let result = self
+                .inner
+                .translate_stream(request, &mut |chunk| {
+                    streamed = true;
+                    on_chunk(chunk);
+                })
+                .await;
+            log_attempt(request, &result, started, attempt);
+            match result {
+                // Part of the translation was already passed on, so it cannot be sent again.
+                Err(e) if !streamed && self.should_retry(&e, attempt).await => attempt += 1,
+                result => return result,
+            }
+        }
+    }
+
+    async fn models(&self) -> Result<Vec<Model>> {
+        self.inner.models().await
+    }
+}
@@ -0,0 +1,80 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use async_trait::async_trait;
+use tracing::debug;
+
+use crate::{Error, Model, OnChunk, Result, Translation, TranslationRequest, Translator};
+
+/// Translator that sends each request with the next of several API keys of a provider, and
+/// with the following ones when a key hits its rate limit, so that a batch gets the combined
+/// throughput of all of them.
+pub struct KeyRotationTranslator {
+    /// The translator of each key.
+    translators: Vec<Box<dyn Translator>>,
+    next: AtomicUsize,
+}
+
+impl KeyRotationTranslator {
+    pub fn new(translators: Vec<Box<dyn Translator>>) -> Self {
+        assert!(!translators.is_empty(), "at least one API key is needed");
+        Self {
+            translators,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// The translators to try for the next request, starting with the next key in turn.
+    fn turn(&self) -> impl Iterator<Item = (usize, &dyn Translator)> {
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        let count = self.translators.len();
+        (0..count).map(move |offset| {
+            let index = (start + offset) % count;
+            (index, self.translators[index].as_ref())
+        })
+    }
+}
+
+/// Whether the key was turned away for exceeding its rate limit, so another key may do.
+fn is_rate_limited(error: &Error) -> bool {
+    matches!(error, Error::Transient(429, ..))
+}
+
+#[async_trait]
+impl Translator for KeyRotationTranslator {
+    async fn translate(&self, request: &TranslationRequest) -> Result<Translation> {
+        let mut last = None;
+        for (index, translator) in self.turn() {
+            match translator.translate(request).await {
+                Err(e) if is_rate_limited(&e) => {
+                    debug!(key = index, "Rate limited; trying the next API key");
+                    last = Some(e);
+                }
+                result => return result,
+            }
+        }
+        Err(last.expect("at least one API key was tried"))
+    }
+
+    async fn translate_stream(
+        &self,
+        request: &TranslationRequest,
+        on_chunk: OnChunk<'_>,
+    ) -> Result<Translation> {
+        let mut last = None;
+        for (index, translator) in self.turn() {
+            // A rate limit turns the request away before any of it is streamed.
+            match translator.translate_stream(request, on_chunk).await {
+                Err(e) if is_rate_limited(&e) => {
+                    debug!(key = index, "Rate limited; trying the next API key");
+                    last = Some(e);
+                }
+                result => return result,
+            }
+        }
+        Err(last.expect("at least one API key was tried"))
+    }
+
+    async fn models(&self) -> Result<Vec<Model>> {
+        self.translators[0].models().await
+    }
+}
@@ -0,0 +1,87 @@
+use futures_util::future::try_join_all;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Semaphore;
+
+use crate::{Error, Result, Task, TranslationRequest, Translator};
+
+/// Segments scoring this or less are flagged for review.
+pub const DEFAULT_SCORE_THRESHOLD: u8 = 3;
+
+/// A language model's estimate of the quality of a translated segment.
+#[derive(Debug, Clone, Serialize)]
+pub struct QualityScore {
+    pub source: String,
+    pub translation: String,
+    /// From 1 (unusable) to 5 (perfect).
+    pub score: u8,
+    /// A short justification of the score.
+    pub reason: String,
+}
+
+#[derive(Deserialize)]
+struct ScoreReply {
+    score: f64,
+    #[serde(default)]
+    reason: String,
+}
+
+/// Ask `scorer` to rate each `(source, translation)` pair from `from` into `to`, running at
+/// most `jobs` requests at a time.
+pub async fn score_translations(
+    pairs: &[(String, String)],
+    scorer: &dyn Translator,
+    from: Option<&str>,
+    to: &str,
+    jobs: usize,
+) -> Result<Vec<QualityScore>> {
+    let semaphore = Semaphore::new(jobs.max(1));
+    try_join_all(pairs.iter().map(|(source, translation)| {
+        let semaphore = &semaphore;
+        async move {
+            let _permit = semaphore
+                .acquire()
+                .await
+                .expect("semaphore is never closed");
+            let request = TranslationRequest {
+                task: Task::Score,
+                ..TranslationRequest::new(
+                    format!("Source:\n{}\n\nTranslation:\n{}", source, translation),
+                    from.map(str::to_string),
+                    to,
+                )
+            };
+            let reply = scorer.translate(&request).await?.text;
+            let (score, reason) = parse_score(&reply)?;
+
+            Ok(QualityScore {
+                source: source.clone(),
+                translation: translation.clone(),
+                score,
+                reason,
+            })
+        }
+    }))
+    .await
+}
+
+/// Read the score and reason from a model's reply, which should be a JSON object but may be
+/// wrapped in a code fence or be a bare number followed by the reason.
+fn parse_score(reply: &str) -> Result<(u8, String)> {
+    let reply = reply.trim();
+    let json = reply
+        .find('{')
+        .zip(reply.rfind('}'))
+        .and_then(|(start, end)| serde_json::from_str::<ScoreReply>(&reply[start..=end]).ok());
+    if let Some(ScoreReply { score, reason }) = json {
+        return Ok((score.round().clamp(1.0, 5.0) as u8, reason));
+    }
+
+    let digit = reply
+        .char_indices()
+        .find(|(_, c)| matches!(c, '1'..='5'))
+        .ok_or_else(|| Error::Api(format!("Unable to read a quality score from: {}", reply)))?;
+    let reason = reply[digit.0 + 1..]
+        .trim_start_matches(|c: char| !c.is_alphanumeric())
+        .to_string();
+    Ok((digit.1 as u8 - b'0', reason))
+}
@@ -0,0 +1,171 @@
+use std::{
+    env, fs,
+    io::{self, BufRead, Write},
+    process,
+};
+
+use crate::{
+    Document, Error, Result, TranslationRequest, Translator, HEADING_STYLE, INSERTED_STYLE,
+};
+
+/// How many candidates are requested when a segment is retried.
+const RETRY_ALTERNATIVES: usize = 4;
+
+/// What the reviewer decided for a segment.
+enum Decision {
+    Accept,
+    Edit,
+    Retry,
+    Skip,
+    AcceptAll,
+    Quit,
+}
+
+/// Walk through each translated segment of `document`, asking on the terminal whether to
+/// accept, edit, retry or skip it. Skipped segments, and those left when quitting, keep their
+/// source text, so only accepted segments reach the output.
+pub async fn review_segments(
+    document: &Document,
+    mut translations: Vec<String>,
+    translator: &dyn Translator,
+    request: &TranslationRequest,
+) -> Result<Vec<String>> {
+    let segments = document.segments().collect::<Vec<_>>();
+    let total = segments
+        .iter()
+        .filter(|segment| segment.needs_translation())
+        .count();
+    let mut stdin = io::stdin().lock();
+    let mut reviewed = 0;
+    let mut accept_all = false;
+    let mut quit = false;
+
+    for (segment, translation) in segments.iter().zip(translations.iter_mut()) {
+        if !segment.needs_translation() {
+            continue;
+        }
+        if quit {
+            *translation = segment.source.text.clone();
+            continue;
+        }
+        reviewed += 1;
+        if accept_all {
+            continue;
+        }
+
+        let mut candidates = Vec::new();
+        loop {
+            anstream::eprintln!(
+                "\n{HEADING_STYLE}[{}/{}]{HEADING_STYLE:#} {}",
+                reviewed,
+                total,
+                segment.original().trim()
+            );
+            anstream::eprintln!(
+                "  -> {INSERTED_STYLE}{}{INSERTED_STYLE:#}",
+                segment
+                    .source
+                    .restore(translation)
+                    .unwrap_or_else(|_| translation.clone())
+                    .trim()
+            );
+
+            match ask(&mut stdin)? {
+                Decision::Accept => break,
+                Decision::AcceptAll => {
+                    accept_all = true;
+                    break;
+                }
+                Decision::Skip => {
+                    *translation = segment.source.text.clone();
+                    break;
+                }
+                Decision::Quit => {
+                    *translation = segment.source.text.clone();
+                    quit = true;
+                    break;
+                }
+                Decision::Edit => {
+                    let edited = edit(&mut stdin, translation)?;
+                    match segment.source.restore(&edited) {
+                        Ok(_) => *translation = edited,
+                        Err(e) => eprintln!("{}. Keep the ⟦n⟧ markers as they are.", e),
+                    }
+                }
+                Decision::Retry => {
+                    if candidates.is_empty() {
+                        let segment_request = TranslationRequest {
+                            text: segment.source.text.clone(),
+                            ..request.clone()
+                        };
+                        candidates = translator
+                            .alternatives(&segment_request, RETRY_ALTERNATIVES)
+                            .await?;
+                        candidates.retain(|candidate| {
+                            candidate != translation && segment.source.restore(candidate).is_ok()
+                        });
+                    }
+                    if candidates.is_empty() {
+                        eprintln!("No other translation was found.");
+                    } else {
+                        let candidate = candidates.remove(0);
+                        candidates.push(std::mem::replace(translation, candidate));
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(translations)
+}
+
+fn ask(stdin: &mut impl BufRead) -> Result<Decision> {
+    loop {
+        eprint!("[a]ccept, [e]dit, [r]etry, [s]kip, accept [A]ll, [q]uit? ");
+        io::stderr().flush()?;
+        let mut answer = String::new();
+        if stdin.read_line(&mut answer)? == 0 {
+            return Ok(Decision::Quit);
+        }
+        match answer.trim() {
+            "a" | "" => return Ok(Decision::Accept),
+            "e" => return Ok(Decision::Edit),
+            "r" => return Ok(Decision::Retry),
+            "s" => return Ok(Decision::Skip),
+            "A" => return Ok(Decision::AcceptAll),
+            "q" => return Ok(Decision::Quit),
+            _ => {}
+        }
+    }
+}
+
+/// Let the reviewer rewrite `translation` in `$VISUAL` or `$EDITOR`, or on a single line when
+/// neither is set.
+fn edit(stdin: &mut impl BufRead, translation: &str) -> Result<String> {
+    let Some(editor) = env::var_os("VISUAL").or_else(|| env::var_os("EDITOR")) else {
+        eprint!("New translation (empty to keep): ");
+        io::stderr().flush()?;
+        let mut line = String::new();
+        stdin.read_line(&mut line)?;
+        let line = line.trim_end_matches(['\n', '\r']);
+        return Ok(if line.is_empty() {
+            translation.to_string()
+        } else {
+            line.to_string()
+        });
+    };
+
+    let path = env::temp_dir().join(format!("trlt-review-{}.txt", process::id()));
+    fs::write(&path, translation)?;
+    let status = process::Command::new(&editor).arg(&path).status()?;
+    let edited = fs::read_to_string(&path);
+    let _ = fs::remove_file(&path);
+    if !status.success() {
+        return Err(Error::Custom(format!(
+            "{} exited with {}",
+            editor.to_string_lossy(),
+            status
+        )));
+    }
+    Ok(edited?.trim_end_matches('\n').to_string())
+}
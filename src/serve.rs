@@ -0,0 +1,181 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use serde::Deserialize;
+
+use crate::{
+    Config, Detection, Error, ProviderKind, Result, Tone, Translation, TranslationRequest,
+    Translator, TranslatorFactory,
+};
+
+/// The address `trlt serve` listens on by default. Only local clients can reach it.
+pub const DEFAULT_SERVE_ADDRESS: &str = "127.0.0.1:8787";
+
+/// The body of a `POST /translate` request.
+#[derive(Debug, Deserialize)]
+pub(crate) struct TranslateBody {
+    pub(crate) text: String,
+    #[serde(default)]
+    pub(crate) from: Option<String>,
+    pub(crate) to: String,
+    /// Overrides the provider the server was started with.
+    #[serde(default)]
+    pub(crate) provider: Option<ProviderKind>,
+    #[serde(default)]
+    pub(crate) tone: Option<Tone>,
+    /// Where the text is used, to disambiguate short strings.
+    #[serde(default)]
+    pub(crate) context: Option<String>,
+}
+
+/// The body of a `POST /detect` request.
+#[derive(Debug, Deserialize)]
+pub(crate) struct DetectBody {
+    pub(crate) text: String,
+    /// Detect the language locally instead of asking the provider.
+    #[serde(default)]
+    pub(crate) offline: bool,
+    #[serde(default)]
+    pub(crate) provider: Option<ProviderKind>,
+}
+
+/// What the HTTP, gRPC and stdio servers share: the config and a translator per provider.
+pub(crate) struct ServerState {
+    config: Config,
+    factory: TranslatorFactory,
+    /// One translator per provider, built on first use and shared by every request, so they
+    /// reuse their connection pools.
+    translators: Mutex<HashMap<ProviderKind, Arc<dyn Translator>>>,
+}
+
+impl ServerState {
+    pub(crate) fn new(config: Config, factory: TranslatorFactory) -> Self {
+        Self {
+            config,
+            factory,
+            translators: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn translator(&self, provider: Option<ProviderKind>) -> Result<Arc<dyn Translator>> {
+        let provider = provider.unwrap_or(self.config.provider);
+        let mut translators = self.translators.lock().expect("translators lock poisoned");
+        if let Some(translator) = translators.get(&provider) {
+            return Ok(translator.clone());
+        }
+        let translator: Arc<dyn Translator> = Arc::from((self.factory)(&self.config, provider)?);
+        translators.insert(provider, translator.clone());
+        Ok(translator)
+    }
+
+    /// Resolve the languages of `body` to their codes and pick the translator for it.
+    pub(crate) fn prepare(
+        &self,
+        body: TranslateBody,
+    ) -> Result<(Arc<dyn Translator>, TranslationRequest)> {
+        let from = body
+            .from
+            .as_deref()
+            .map(crate::resolve_language)
+            .transpose()?;
+        let to = crate::resolve_language(&body.to)?;
+        let translator = self.translator(body.provider)?;
+        let request = TranslationRequest {
+            tone: body.tone,
+            hint: body.context,
+            ..TranslationRequest::new(body.text, from, to)
+        };
+        Ok((translator, request))
+    }
+
+    pub(crate) async fn translate(&self, body: TranslateBody) -> Result<Translation> {
+        let (translator, request) = self.prepare(body)?;
+        translator.translate(&request).await
+    }
+
+    pub(crate) async fn detect(&self, body: DetectBody) -> Result<Detection> {
+        if body.offline {
+            return crate::detect_offline(&body.text).ok_or_else(|| {
+                Error::Custom("Unable to detect the language of the text".to_string())
+            });
+        }
+        self.translator(body.provider)?.detect(&body.text).await
+    }
+}
+
+/// An error sent back as `{"error": "..."}`.
+struct ApiError(StatusCode, String);
+
+impl From<Error> for ApiError {
+    fn from(error: Error) -> Self {
+        let status = match error {
+            Error::Custom(_) | Error::MissingApiKey(_) => StatusCode::BAD_REQUEST,
+            _ => StatusCode::BAD_GATEWAY,
+        };
+        ApiError(status, error.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+/// Serve `POST /translate` and `POST /detect` on `address`, and the gRPC service on `grpc` when
+/// given, until the process is interrupted. Translators are built with `factory` and kept for
+/// the lifetime of the server.
+pub async fn serve(
+    address: SocketAddr,
+    grpc: Option<SocketAddr>,
+    config: Config,
+    factory: TranslatorFactory,
+) -> Result<()> {
+    let state = Arc::new(ServerState::new(config, factory));
+    // Build the default translator up front so configuration errors show at startup.
+    state.translator(None)?;
+
+    let app = Router::new()
+        .route("/translate", post(translate))
+        .route("/detect", post(detect))
+        .with_state(state.clone());
+
+    let listener = tokio::net::TcpListener::bind(address).await?;
+    eprintln!("Listening on http://{}", listener.local_addr()?);
+    let http = async {
+        axum::serve(listener, app)
+            .with_graceful_shutdown(async {
+                tokio::signal::ctrl_c().await.ok();
+            })
+            .await?;
+        Ok(())
+    };
+    match grpc {
+        Some(grpc) => tokio::try_join!(http, crate::serve_grpc(grpc, state)).map(|_| ()),
+        None => http.await,
+    }
+}
+
+async fn translate(
+    State(state): State<Arc<ServerState>>,
+    Json(body): Json<TranslateBody>,
+) -> std::result::Result<Json<Translation>, ApiError> {
+    Ok(Json(state.translate(body).await?))
+}
+
+async fn detect(
+    State(state): State<Arc<ServerState>>,
+    Json(body): Json<DetectBody>,
+) -> std::result::Result<Json<Detection>, ApiError> {
+    Ok(Json(state.detect(body).await?))
+}
@@ -0,0 +1,161 @@
+use std::{collections::HashMap, sync::Arc};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    sync::{mpsc, oneshot},
+    task::JoinHandle,
+};
+
+use crate::{Config, Error, Result, ServerState, TranslatorFactory};
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// A translation or detection failed, e.g. because the provider rejected it.
+const REQUEST_FAILED: i64 = -32000;
+/// The request was cancelled by the client. The code is the one the Language Server Protocol
+/// uses, which editors already know about.
+const REQUEST_CANCELLED: i64 = -32800;
+
+#[derive(Debug, Deserialize)]
+struct RpcRequest {
+    /// Notifications have no id and get no response.
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+struct RpcError(i64, String);
+
+impl From<Error> for RpcError {
+    fn from(error: Error) -> Self {
+        RpcError(REQUEST_FAILED, error.to_string())
+    }
+}
+
+fn response(id: Value, result: std::result::Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(RpcError(code, message)) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": code, "message": message }
+        }),
+    }
+}
+
+fn params<T: serde::de::DeserializeOwned>(params: Value) -> std::result::Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|e| RpcError(INVALID_PARAMS, e.to_string()))
+}
+
+async fn call(
+    state: &ServerState,
+    method: &str,
+    params_value: Value,
+) -> std::result::Result<Value, RpcError> {
+    let result = match method {
+        "translate" => serde_json::to_value(state.translate(params(params_value)?).await?),
+        "detect" => serde_json::to_value(state.detect(params(params_value)?).await?),
+        _ => {
+            return Err(RpcError(
+                METHOD_NOT_FOUND,
+                format!("Unknown method `{}`", method),
+            ))
+        }
+    };
+    result.map_err(|e| RpcError(REQUEST_FAILED, e.to_string()))
+}
+
+/// A request that is still running, with the means to cancel it.
+struct Pending {
+    cancel: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+/// Speak newline-delimited JSON-RPC 2.0 on stdin and stdout until stdin is closed.
+///
+/// The `translate` and `detect` methods take the same parameters as the HTTP endpoints of
+/// [`serve`](crate::serve) and run concurrently, so responses may come out of order. `cancel`
+/// takes the `id` of a running request, which is then answered with a cancellation error.
+pub async fn serve_stdio(config: Config, factory: TranslatorFactory) -> Result<()> {
+    let state = Arc::new(ServerState::new(config, factory));
+    state.translator(None)?;
+
+    // Responses go through one writer so that lines from concurrent requests never interleave.
+    let (sender, mut responses) = mpsc::unbounded_channel::<Value>();
+    let writer = tokio::spawn(async move {
+        let mut stdout = tokio::io::stdout();
+        while let Some(response) = responses.recv().await {
+            let line = format!("{}\n", response);
+            if stdout.write_all(line.as_bytes()).await.is_err() || stdout.flush().await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut pending: HashMap<String, Pending> = HashMap::new();
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        pending.retain(|_, request| !request.task.is_finished());
+
+        let request: RpcRequest = match serde_json::from_str(&line) {
+            Ok(request) => request,
+            Err(e) => {
+                let error = RpcError(PARSE_ERROR, e.to_string());
+                sender.send(response(Value::Null, Err(error))).ok();
+                continue;
+            }
+        };
+
+        match request.method.as_str() {
+            "cancel" => {
+                let target = request.params["id"].to_string();
+                // Cancelling fails when the request has already been answered.
+                let cancelled = pending
+                    .remove(&target)
+                    .is_some_and(|request| request.cancel.send(()).is_ok());
+                if let Some(id) = request.id {
+                    sender.send(response(id, Ok(json!(cancelled)))).ok();
+                }
+            }
+            _ => {
+                let (cancel, cancelled) = oneshot::channel();
+                let key = request.id.as_ref().map(Value::to_string);
+                let state = state.clone();
+                let sender = sender.clone();
+                let task = tokio::spawn(async move {
+                    let result = tokio::select! {
+                        // A request that was cancelled is never answered with its result.
+                        biased;
+                        // A dropped sender is not a cancellation.
+                        Ok(()) = cancelled => {
+                            Err(RpcError(REQUEST_CANCELLED, "Request cancelled".to_string()))
+                        }
+                        result = call(&state, &request.method, request.params) => result,
+                    };
+                    if let Some(id) = request.id {
+                        sender.send(response(id, result)).ok();
+                    }
+                });
+                if let Some(key) = key {
+                    pending.insert(key, Pending { cancel, task });
+                }
+            }
+        }
+    }
+
+    // Answer the requests that are still running before exiting.
+    for (_, request) in pending {
+        request.task.await.ok();
+    }
+    drop(sender);
+    writer.await.ok();
+    Ok(())
+}
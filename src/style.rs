@@ -0,0 +1,40 @@
+use anstyle::{AnsiColor, Color, Style};
+use clap::ValueEnum;
+
+/// When to color the output of the command line.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ColorMode {
+    /// Color output written to a terminal, unless `NO_COLOR` is set.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorMode {
+    /// Use this mode for everything printed with the `anstream` macros from now on.
+    pub fn apply(self) {
+        anstream::ColorChoice::write_global(match self {
+            ColorMode::Auto => anstream::ColorChoice::Auto,
+            ColorMode::Always => anstream::ColorChoice::Always,
+            ColorMode::Never => anstream::ColorChoice::Never,
+        });
+    }
+}
+
+const fn color(color: AnsiColor) -> Style {
+    Style::new().fg_color(Some(Color::Ansi(color)))
+}
+
+/// The style of headings and labels.
+pub const HEADING_STYLE: Style = Style::new().bold();
+/// The style of what needs attention, such as low scores.
+pub const WARNING_STYLE: Style = color(AnsiColor::Yellow).bold();
+/// The style of errors.
+pub const ERROR_STYLE: Style = color(AnsiColor::Red).bold();
+/// The style of text that a comparison adds, such as a translation.
+pub const INSERTED_STYLE: Style = color(AnsiColor::Green);
+/// The style of text that a comparison removes, such as a diverging back-translation.
+pub const DELETED_STYLE: Style = color(AnsiColor::Red);
+/// The style of secondary details.
+pub const DIMMED_STYLE: Style = Style::new().dimmed();
@@ -0,0 +1,156 @@
+use futures_util::future::try_join_all;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::Semaphore;
+
+use crate::{Error, Result, TranslationRequest, Translator};
+
+/// The string values of `source` that are missing or empty in `target`, as JSON pointers
+/// (e.g. `/home/title`) with their source text.
+pub fn missing_keys(source: &Value, target: &Value) -> Vec<(String, String)> {
+    let mut missing = Vec::new();
+    collect_missing(source, Some(target), String::new(), &mut missing);
+    missing
+}
+
+fn collect_missing(
+    source: &Value,
+    target: Option<&Value>,
+    pointer: String,
+    missing: &mut Vec<(String, String)>,
+) {
+    match source {
+        Value::String(text) if !text.trim().is_empty() => {
+            let untranslated = match target {
+                None | Some(Value::Null) => true,
+                Some(Value::String(translation)) => translation.trim().is_empty(),
+                Some(_) => false,
+            };
+            if untranslated {
+                missing.push((pointer, text.clone()));
+            }
+        }
+        Value::Object(fields) => {
+            for (key, value) in fields {
+                let escaped = key.replace('~', "~0").replace('/', "~1");
+                let target = target.and_then(|target| target.get(key));
+                collect_missing(value, target, format!("{}/{}", pointer, escaped), missing);
+            }
+        }
+        Value::Array(items) => {
+            for (index, value) in items.iter().enumerate() {
+                let target = target.and_then(|target| target.get(index));
+                collect_missing(value, target, format!("{}/{}", pointer, index), missing);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Add the keys and array items of `source` that `target` lacks, and the values that are empty
+/// in `target`, leaving every other value of `target` as it is.
+fn fill_missing(source: &Value, target: &mut Value) {
+    match (source, target) {
+        (Value::Object(source), Value::Object(target)) => {
+            for (key, value) in source {
+                match target.get_mut(key) {
+                    Some(Value::Null) | None => {
+                        target.insert(key.clone(), value.clone());
+                    }
+                    Some(existing) => fill_missing(value, existing),
+                }
+            }
+        }
+        (Value::Array(source), Value::Array(target)) => {
+            for (value, existing) in source.iter().zip(target.iter_mut()) {
+                fill_missing(value, existing);
+            }
+            target.extend(source.iter().skip(target.len()).cloned());
+        }
+        (Value::String(_), target @ Value::String(_))
+            if target.as_str().is_some_and(|text| text.trim().is_empty()) =>
+        {
+            *target = source.clone();
+        }
+        _ => {}
+    }
+}
+
+/// Translate the string values of the JSON locale file `source` that are missing or empty in
+/// `target` (an empty document when it does not exist yet), and merge them into it.
+///
+/// Values already in `target` are kept as they are. Keys keep the order of the files, and the
+/// result uses the indentation of `target`. Returns the merged file and the number of values
+/// translated.
+pub async fn sync_json(
+    source: &str,
+    target: Option<&str>,
+    translator: &dyn Translator,
+    request: &TranslationRequest,
+    jobs: usize,
+) -> Result<(String, usize)> {
+    let parse = |input: &str| {
+        serde_json::from_str::<Value>(input)
+            .map_err(|e| Error::Custom(format!("Invalid JSON: {}", e)))
+    };
+    let source_value = parse(source)?;
+    let mut target_value = match target {
+        Some(target) if !target.trim().is_empty() => parse(target)?,
+        _ => Value::Object(Default::default()),
+    };
+
+    let missing = missing_keys(&source_value, &target_value);
+    let semaphore = Semaphore::new(jobs.max(1));
+    let translations = try_join_all(missing.iter().map(|(_, text)| async {
+        let _permit = semaphore
+            .acquire()
+            .await
+            .expect("semaphore is never closed");
+        let key_request = TranslationRequest {
+            text: text.clone(),
+            ..request.clone()
+        };
+        translator
+            .translate(&key_request)
+            .await
+            .map(|translation| translation.text)
+    }))
+    .await?;
+
+    fill_missing(&source_value, &mut target_value);
+    let mut merged = 0;
+    for ((pointer, _), translation) in missing.iter().zip(translations) {
+        // Keys whose parent is not an object or array in `target` cannot be merged.
+        if let Some(value) = target_value.pointer_mut(pointer) {
+            *value = Value::String(translation);
+            merged += 1;
+        }
+    }
+
+    let layout = target
+        .filter(|target| !target.trim().is_empty())
+        .unwrap_or(source);
+    Ok((render_json(&target_value, layout)?, merged))
+}
+
+/// Pretty-print `value` with the indentation and final newline of `layout`.
+fn render_json(value: &Value, layout: &str) -> Result<String> {
+    let indent = layout
+        .lines()
+        .find_map(|line| {
+            let indent = &line[..line.len() - line.trim_start().len()];
+            (!indent.is_empty() && !line.trim().is_empty()).then_some(indent)
+        })
+        .unwrap_or("  ");
+    let mut output = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+    let mut serializer = serde_json::Serializer::with_formatter(&mut output, formatter);
+    value
+        .serialize(&mut serializer)
+        .map_err(|e| Error::Custom(format!("Unable to write JSON: {}", e)))?;
+    let mut output = String::from_utf8(output).expect("serde_json writes UTF-8");
+    if layout.ends_with('\n') {
+        output.push('\n');
+    }
+    Ok(output)
+}
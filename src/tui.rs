@@ -0,0 +1,602 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use clap::ValueEnum;
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    widgets::{Block, List, ListItem, ListState, Paragraph, Wrap},
+    DefaultTerminal, Frame,
+};
+use tokio::sync::mpsc;
+
+use crate::{Config, ProviderKind, Result, Tone, Translation, TranslationRequest, Translator};
+
+/// How long the source must stay unchanged before it is translated again.
+const RETRANSLATE_DELAY: Duration = Duration::from_millis(800);
+
+/// Builds the translator for a provider, so the interface can switch providers and models.
+pub type TranslatorFactory = fn(&Config, ProviderKind) -> Result<Box<dyn Translator>>;
+
+/// The settings a session starts with. They can all be changed from the settings pane.
+pub struct SessionSettings {
+    pub from: Option<String>,
+    pub to: String,
+    pub provider: ProviderKind,
+    pub tone: Option<Tone>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Focus {
+    Source,
+    History,
+    Settings,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Setting {
+    From,
+    To,
+    Provider,
+    Model,
+    Tone,
+}
+
+const SETTINGS: [Setting; 5] = [
+    Setting::From,
+    Setting::To,
+    Setting::Provider,
+    Setting::Model,
+    Setting::Tone,
+];
+
+struct HistoryEntry {
+    source: String,
+    translation: String,
+    to: String,
+}
+
+/// The translation that was last asked for, to tell whether the source needs translating again.
+#[derive(PartialEq)]
+struct Snapshot {
+    text: String,
+    from: Option<String>,
+    to: String,
+    tone: Option<Tone>,
+    provider: ProviderKind,
+    model: Option<String>,
+}
+
+struct App {
+    config: Config,
+    settings: SessionSettings,
+    factory: TranslatorFactory,
+    translator: Arc<dyn Translator>,
+    source: String,
+    /// The byte offset of the cursor in `source`.
+    cursor: usize,
+    translation: String,
+    last_edit: Option<Instant>,
+    requested: Option<Snapshot>,
+    /// The id of the latest translation asked for; older results are dropped.
+    request_id: u64,
+    translating: bool,
+    history: Vec<HistoryEntry>,
+    history_state: ListState,
+    focus: Focus,
+    setting: usize,
+    /// The value being typed for a setting.
+    input: Option<String>,
+    status: String,
+    sender: mpsc::UnboundedSender<(u64, String, Result<Translation>)>,
+    results: mpsc::UnboundedReceiver<(u64, String, Result<Translation>)>,
+}
+
+/// Run the full-screen interface until the user quits. The source pane is translated again
+/// whenever it stops changing, and the settings pane switches languages, provider, model and
+/// tone on the fly.
+pub async fn run_tui(
+    config: Config,
+    settings: SessionSettings,
+    factory: TranslatorFactory,
+) -> Result<()> {
+    let translator = Arc::from(factory(&config, settings.provider)?);
+    let (sender, results) = mpsc::unbounded_channel();
+    let mut app = App {
+        config,
+        settings,
+        factory,
+        translator,
+        source: String::new(),
+        cursor: 0,
+        translation: String::new(),
+        last_edit: None,
+        requested: None,
+        request_id: 0,
+        translating: false,
+        history: Vec::new(),
+        history_state: ListState::default(),
+        focus: Focus::Source,
+        setting: 0,
+        input: None,
+        status: String::new(),
+        sender,
+        results,
+    };
+
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+impl App {
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            while let Ok((id, source, result)) = self.results.try_recv() {
+                self.finish(id, source, result);
+            }
+            if event::poll(Duration::from_millis(50))? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press && !self.key(key) {
+                        return Ok(());
+                    }
+                }
+            }
+            if self
+                .last_edit
+                .is_some_and(|edit| edit.elapsed() >= RETRANSLATE_DELAY)
+            {
+                self.last_edit = None;
+                self.translate(false);
+            }
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            text: self.source.trim().to_string(),
+            from: self.settings.from.clone(),
+            to: self.settings.to.clone(),
+            tone: self.settings.tone,
+            provider: self.settings.provider,
+            model: self.config.model_for(self.settings.provider),
+        }
+    }
+
+    /// Translate the source pane in the background, unless it was already translated with the
+    /// same settings and `force` is not set.
+    fn translate(&mut self, force: bool) {
+        let snapshot = self.snapshot();
+        if snapshot.text.is_empty() || (!force && self.requested.as_ref() == Some(&snapshot)) {
+            return;
+        }
+
+        self.request_id += 1;
+        self.translating = true;
+        self.status = "Translating...".to_string();
+        let request = TranslationRequest {
+            tone: snapshot.tone,
+            ..TranslationRequest::new(
+                snapshot.text.clone(),
+                snapshot.from.clone(),
+                snapshot.to.clone(),
+            )
+        };
+        let (id, translator, sender) = (
+            self.request_id,
+            self.translator.clone(),
+            self.sender.clone(),
+        );
+        tokio::spawn(async move {
+            let result = translator.translate(&request).await;
+            let _ = sender.send((id, request.text, result));
+        });
+        self.requested = Some(snapshot);
+    }
+
+    fn finish(&mut self, id: u64, source: String, result: Result<Translation>) {
+        if id != self.request_id {
+            return;
+        }
+        self.translating = false;
+        match result {
+            Ok(translation) => {
+                self.status.clear();
+                self.translation = translation.text.trim().to_string();
+                let entry = HistoryEntry {
+                    source,
+                    translation: self.translation.clone(),
+                    to: self.requested.as_ref().map_or_else(
+                        || self.settings.to.clone(),
+                        |requested| requested.to.clone(),
+                    ),
+                };
+                let repeated = self
+                    .history
+                    .first()
+                    .is_some_and(|last| last.source == entry.source && last.to == entry.to);
+                if repeated {
+                    self.history[0] = entry;
+                } else {
+                    self.history.insert(0, entry);
+                }
+            }
+            Err(e) => {
+                self.requested = None;
+                self.status = format!("Failed to translate: {}", e);
+            }
+        }
+    }
+
+    /// Handle a key press, returning `false` when the user quits.
+    fn key(&mut self, key: KeyEvent) -> bool {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('c' | 'q') if control => return false,
+            KeyCode::Char('r') if control => self.translate(true),
+            KeyCode::Esc if self.input.is_some() => self.input = None,
+            KeyCode::Esc => return false,
+            KeyCode::Tab if self.input.is_none() => {
+                self.focus = match self.focus {
+                    Focus::Source => Focus::History,
+                    Focus::History => Focus::Settings,
+                    Focus::Settings => Focus::Source,
+                };
+            }
+            _ => match self.focus {
+                Focus::Source => self.edit_source(key),
+                Focus::History => self.browse_history(key),
+                Focus::Settings => self.change_setting(key),
+            },
+        }
+        true
+    }
+
+    fn edit_source(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('l') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.source.clear();
+                self.cursor = 0;
+                self.translation.clear();
+                self.requested = None;
+                return;
+            }
+            KeyCode::Char(c) => {
+                self.source.insert(self.cursor, c);
+                self.cursor += c.len_utf8();
+            }
+            KeyCode::Enter => {
+                self.source.insert(self.cursor, '\n');
+                self.cursor += 1;
+            }
+            KeyCode::Backspace => match self.source[..self.cursor].chars().next_back() {
+                Some(c) => {
+                    self.cursor -= c.len_utf8();
+                    self.source.remove(self.cursor);
+                }
+                None => return,
+            },
+            KeyCode::Delete if self.cursor < self.source.len() => {
+                self.source.remove(self.cursor);
+            }
+            KeyCode::Left => {
+                if let Some(c) = self.source[..self.cursor].chars().next_back() {
+                    self.cursor -= c.len_utf8();
+                }
+                return;
+            }
+            KeyCode::Right => {
+                if let Some(c) = self.source[self.cursor..].chars().next() {
+                    self.cursor += c.len_utf8();
+                }
+                return;
+            }
+            KeyCode::Home => {
+                self.cursor = self.source[..self.cursor].rfind('\n').map_or(0, |i| i + 1);
+                return;
+            }
+            KeyCode::End => {
+                self.cursor += self.source[self.cursor..]
+                    .find('\n')
+                    .unwrap_or(self.source.len() - self.cursor);
+                return;
+            }
+            _ => return,
+        }
+        self.last_edit = Some(Instant::now());
+    }
+
+    fn browse_history(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Up => self.history_state.select_previous(),
+            KeyCode::Down => self.history_state.select_next(),
+            KeyCode::Enter => {
+                let Some(entry) = self
+                    .history_state
+                    .selected()
+                    .and_then(|index| self.history.get(index))
+                else {
+                    return;
+                };
+                self.source = entry.source.clone();
+                self.cursor = self.source.len();
+                self.translation = entry.translation.clone();
+                self.settings.to = entry.to.clone();
+                self.requested = Some(self.snapshot());
+                self.focus = Focus::Source;
+            }
+            _ => {}
+        }
+    }
+
+    fn change_setting(&mut self, key: KeyEvent) {
+        let setting = SETTINGS[self.setting];
+        if let Some(input) = &mut self.input {
+            match key.code {
+                KeyCode::Char(c) => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Enter => {
+                    let value = self.input.take().unwrap_or_default();
+                    self.apply(setting, value.trim());
+                }
+                _ => {}
+            }
+            return;
+        }
+
+        match key.code {
+            KeyCode::Up => self.setting = self.setting.saturating_sub(1),
+            KeyCode::Down => self.setting = (self.setting + 1).min(SETTINGS.len() - 1),
+            KeyCode::Left | KeyCode::Right | KeyCode::Enter
+                if matches!(setting, Setting::Provider | Setting::Tone) =>
+            {
+                let step = if key.code == KeyCode::Left { -1 } else { 1 };
+                self.cycle(setting, step);
+            }
+            KeyCode::Enter => {
+                self.input = Some(match setting {
+                    Setting::From => self.settings.from.clone().unwrap_or_default(),
+                    Setting::To => self.settings.to.clone(),
+                    _ => self
+                        .config
+                        .model_for(self.settings.provider)
+                        .unwrap_or_default(),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// Apply a value typed for a text setting.
+    fn apply(&mut self, setting: Setting, value: &str) {
+        let result = match setting {
+            Setting::From if value.is_empty() || value == "auto" => {
+                self.settings.from = None;
+                Ok(())
+            }
+            Setting::From => {
+                crate::resolve_language(value).map(|language| self.settings.from = Some(language))
+            }
+            Setting::To => {
+                crate::resolve_language(value).map(|language| self.settings.to = language)
+            }
+            _ => {
+                let mut config = self.config.clone();
+                config
+                    .set_model(self.settings.provider, value.to_string())
+                    .and_then(|()| {
+                        self.translator =
+                            Arc::from((self.factory)(&config, self.settings.provider)?);
+                        self.config = config;
+                        Ok(())
+                    })
+            }
+        };
+        match result {
+            Ok(()) => self.settings_changed(),
+            Err(e) => self.status = e.to_string(),
+        }
+    }
+
+    /// Move a choice setting to the previous or next value.
+    fn cycle(&mut self, setting: Setting, step: isize) {
+        let next =
+            |index: usize, len: usize| (index as isize + step).rem_euclid(len as isize) as usize;
+        if setting == Setting::Tone {
+            // The first choice is no tone at all.
+            let tones = Tone::value_variants();
+            let index = self
+                .settings
+                .tone
+                .and_then(|tone| tones.iter().position(|t| *t == tone))
+                .map_or(0, |index| index + 1);
+            let index = next(index, tones.len() + 1);
+            self.settings.tone = index.checked_sub(1).map(|index| tones[index]);
+            return self.settings_changed();
+        }
+
+        // Providers that are not configured are skipped.
+        let providers = ProviderKind::value_variants();
+        let mut index = providers
+            .iter()
+            .position(|provider| *provider == self.settings.provider)
+            .unwrap_or_default();
+        for _ in 1..providers.len() {
+            index = next(index, providers.len());
+            if let Ok(translator) = (self.factory)(&self.config, providers[index]) {
+                self.translator = Arc::from(translator);
+                self.settings.provider = providers[index];
+                return self.settings_changed();
+            }
+        }
+        self.status = "No other provider is configured".to_string();
+    }
+
+    fn settings_changed(&mut self) {
+        self.status.clear();
+        self.translate(false);
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Min(3), Constraint::Length(1)]).areas(frame.area());
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(65), Constraint::Percentage(35)])
+                .areas(main);
+        let [source_area, translation_area] =
+            Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(left);
+        let [history_area, settings_area] =
+            Layout::vertical([Constraint::Min(3), Constraint::Length(7)]).areas(right);
+
+        let width = source_area.width.saturating_sub(2).max(1) as usize;
+        let (lines, cursor) = hard_wrap(&self.source, self.cursor, width);
+        frame.render_widget(
+            Paragraph::new(lines.join("\n")).block(self.block(" Source ", Focus::Source)),
+            source_area,
+        );
+        if self.focus == Focus::Source {
+            frame.set_cursor_position((
+                source_area.x + 1 + cursor.0 as u16,
+                source_area.y + 1 + cursor.1 as u16,
+            ));
+        }
+
+        let title = format!(
+            " Translation ({}{}) ",
+            self.settings
+                .from
+                .as_deref()
+                .map(|from| format!("{} → ", from))
+                .unwrap_or_default(),
+            self.settings.to
+        );
+        frame.render_widget(
+            Paragraph::new(self.translation.as_str())
+                .wrap(Wrap { trim: false })
+                .block(Block::bordered().title(title)),
+            translation_area,
+        );
+
+        let history = self
+            .history
+            .iter()
+            .map(|entry| {
+                ListItem::new(format!(
+                    "[{}] {}",
+                    entry.to,
+                    entry.source.lines().next().unwrap_or_default()
+                ))
+            })
+            .collect::<Vec<_>>();
+        frame.render_stateful_widget(
+            List::new(history)
+                .block(self.block(" History ", Focus::History))
+                .highlight_style(Style::new().add_modifier(Modifier::REVERSED)),
+            history_area,
+            &mut self.history_state,
+        );
+
+        let settings = SETTINGS
+            .iter()
+            .enumerate()
+            .map(|(index, setting)| {
+                let value = match (setting, &self.input) {
+                    (_, Some(input)) if index == self.setting => format!("{}▏", input),
+                    (Setting::From, _) => self.settings.from.clone().unwrap_or("auto".to_string()),
+                    (Setting::To, _) => self.settings.to.clone(),
+                    (Setting::Provider, _) => self.settings.provider.to_string(),
+                    (Setting::Model, _) => self
+                        .config
+                        .model_for(self.settings.provider)
+                        .unwrap_or("-".to_string()),
+                    (Setting::Tone, _) => self
+                        .settings
+                        .tone
+                        .and_then(|tone| tone.to_possible_value())
+                        .map_or("none".to_string(), |tone| tone.get_name().to_string()),
+                };
+                let name = match setting {
+                    Setting::From => "From",
+                    Setting::To => "To",
+                    Setting::Provider => "Provider",
+                    Setting::Model => "Model",
+                    Setting::Tone => "Tone",
+                };
+                ListItem::new(format!("{:<9}{}", name, value))
+            })
+            .collect::<Vec<_>>();
+        let mut settings_state = ListState::default()
+            .with_selected((self.focus == Focus::Settings).then_some(self.setting));
+        frame.render_stateful_widget(
+            List::new(settings)
+                .block(self.block(" Settings ", Focus::Settings))
+                .highlight_style(Style::new().add_modifier(Modifier::REVERSED)),
+            settings_area,
+            &mut settings_state,
+        );
+
+        let help = match self.focus {
+            Focus::Source => "Ctrl+R translate · Ctrl+L clear",
+            Focus::History => "↑↓ select · Enter restore",
+            Focus::Settings => "↑↓ select · Enter edit · ←→ change",
+        };
+        let status_text = if self.status.is_empty() {
+            format!("Tab switch pane · {} · Esc quit", help)
+        } else {
+            self.status.clone()
+        };
+        let style = if self.translating || self.status.is_empty() {
+            Style::new().fg(Color::DarkGray)
+        } else {
+            Style::new().fg(Color::Red)
+        };
+        frame.render_widget(Paragraph::new(status_text).style(style), status);
+    }
+
+    fn block(&self, title: &'static str, focus: Focus) -> Block<'static> {
+        let block = Block::bordered().title(title);
+        if self.focus == focus {
+            block.border_style(Style::new().fg(Color::Cyan))
+        } else {
+            block
+        }
+    }
+}
+
+/// Break `text` into lines of at most `width` characters, giving the column and row of the
+/// byte offset `cursor`.
+fn hard_wrap(text: &str, cursor: usize, width: usize) -> (Vec<String>, (usize, usize)) {
+    let mut lines = vec![String::new()];
+    let mut position = (0, 0);
+
+    let chars = text.char_indices().map(|(offset, c)| (offset, Some(c)));
+    for (offset, c) in chars.chain([(text.len(), None)]) {
+        if lines
+            .last()
+            .is_some_and(|line| line.chars().count() == width)
+        {
+            lines.push(String::new());
+        }
+        if offset == cursor {
+            position = (
+                lines.last().map_or(0, |line| line.chars().count()),
+                lines.len() - 1,
+            );
+        }
+        match c {
+            Some('\n') => lines.push(String::new()),
+            Some(c) => lines.last_mut().expect("there is always a line").push(c),
+            None => {}
+        }
+    }
+
+    (lines, position)
+}
@@ -0,0 +1,339 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Utc};
+use clap::ValueEnum;
+use rusqlite::{params, Connection};
+
+use crate::{
+    Config, Model, OnChunk, ProviderKind, Result, Translation, TranslationRequest, Translator,
+    Usage,
+};
+
+/// The tokens used with a model for a language pair, and the number of requests that used them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageEntry {
+    pub usage: Usage,
+    pub provider: ProviderKind,
+    pub from: Option<String>,
+    pub to: String,
+    pub requests: usize,
+}
+
+/// The tokens used by the requests of a run, added up by model, provider and language pair.
+#[derive(Debug, Default)]
+pub struct UsageMeter {
+    entries: Mutex<Vec<UsageEntry>>,
+}
+
+impl UsageMeter {
+    pub fn record(&self, usage: &Usage, provider: ProviderKind, request: &TranslationRequest) {
+        let mut entries = self.entries.lock().expect("usage lock poisoned");
+        let entry = entries.iter().position(|entry| {
+            entry.usage.model == usage.model
+                && entry.provider == provider
+                && entry.from == request.from
+                && entry.to == request.to
+        });
+        let entry = match entry {
+            Some(index) => &mut entries[index],
+            None => {
+                entries.push(UsageEntry {
+                    usage: Usage {
+                        model: usage.model.clone(),
+                        ..Default::default()
+                    },
+                    provider,
+                    from: request.from.clone(),
+                    to: request.to.clone(),
+                    requests: 0,
+                });
+                entries.last_mut().expect("an entry was just added")
+            }
+        };
+        entry.requests += 1;
+        entry.usage.prompt_tokens += usage.prompt_tokens;
+        entry.usage.completion_tokens += usage.completion_tokens;
+    }
+
+    pub fn entries(&self) -> Vec<UsageEntry> {
+        self.entries.lock().expect("usage lock poisoned").clone()
+    }
+
+    /// The number of requests that reported the tokens they used.
+    pub fn requests(&self) -> usize {
+        self.entries().iter().map(|entry| entry.requests).sum()
+    }
+
+    /// The tokens used so far, by model.
+    pub fn totals(&self) -> Vec<Usage> {
+        let mut models = BTreeMap::<String, Usage>::new();
+        for entry in self.entries() {
+            let total = models
+                .entry(entry.usage.model.clone())
+                .or_insert_with(|| Usage {
+                    model: entry.usage.model.clone(),
+                    ..Default::default()
+                });
+            total.prompt_tokens += entry.usage.prompt_tokens;
+            total.completion_tokens += entry.usage.completion_tokens;
+        }
+        models.into_values().collect()
+    }
+
+    /// The estimated cost of the tokens used so far, in US dollars, or `None` when the price of
+    /// one of the models is unknown.
+    pub fn cost(&self, config: &Config) -> Option<f64> {
+        self.totals()
+            .iter()
+            .map(|usage| usage_cost(usage, config))
+            .sum()
+    }
+
+    /// A line telling the tokens used so far and their estimated cost, or `None` when no request
+    /// reported any, e.g. because every translation came from the cache.
+    pub fn summary(&self, config: &Config) -> Option<String> {
+        let totals = self.totals();
+        if totals.is_empty() {
+            return None;
+        }
+        let prompt: u64 = totals.iter().map(|usage| usage.prompt_tokens).sum();
+        let completion: u64 = totals.iter().map(|usage| usage.completion_tokens).sum();
+        let cost = match self.cost(config) {
+            Some(cost) => format!(", {}", format_cost(cost)),
+            None => String::new(),
+        };
+        Some(format!(
+            "Used {} prompt and {} completion tokens{}.",
+            prompt, completion, cost
+        ))
+    }
+}
+
+/// The estimated cost of `usage`, in US dollars, or `None` when the price of its model is
+/// unknown.
+pub fn usage_cost(usage: &Usage, config: &Config) -> Option<f64> {
+    let pricing = config.pricing_for(&usage.model)?;
+    Some(
+        (usage.prompt_tokens as f64 * pricing.input
+            + usage.completion_tokens as f64 * pricing.output)
+            / 1_000_000.0,
+    )
+}
+
+/// An estimated cost in US dollars, e.g. `about $0.0123`.
+pub fn format_cost(cost: f64) -> String {
+    if cost > 0.0 && cost < 0.0001 {
+        "under $0.0001".to_string()
+    } else {
+        format!("about ${:.4}", cost)
+    }
+}
+
+/// How [`UsageLog::summary`] groups the usage.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum UsageGrouping {
+    #[default]
+    Day,
+    Model,
+    Provider,
+    /// The source and target languages.
+    Pair,
+}
+
+/// The usage of a group of runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageSummary {
+    /// The day, model, provider or language pair of the group.
+    pub key: String,
+    pub requests: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    /// The estimated cost, counting nothing for the models of unknown price.
+    pub cost: f64,
+}
+
+/// Changes to the usage log schema, applied in order. `PRAGMA user_version` tells how many are
+/// applied.
+const MIGRATIONS: &[&str] = &["ALTER TABLE usage ADD COLUMN provider TEXT;
+     ALTER TABLE usage ADD COLUMN source_lang TEXT;
+     ALTER TABLE usage ADD COLUMN target_lang TEXT;"];
+
+/// A persistent record of the tokens used by each run and their estimated cost, backed by
+/// SQLite.
+pub struct UsageLog {
+    conn: Mutex<Connection>,
+}
+
+impl UsageLog {
+    pub fn new(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS usage (
+                id INTEGER PRIMARY KEY,
+                created_at TEXT NOT NULL,
+                model TEXT NOT NULL,
+                requests INTEGER NOT NULL,
+                prompt_tokens INTEGER NOT NULL,
+                completion_tokens INTEGER NOT NULL,
+                cost REAL
+            );",
+        )?;
+        let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        for (index, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+            conn.execute_batch(&format!(
+                "BEGIN; {} PRAGMA user_version = {}; COMMIT;",
+                migration,
+                index + 1
+            ))?;
+        }
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// The usage log in the user data directory, e.g. `$HOME/.local/share/trlt`.
+    pub fn open() -> Result<Self> {
+        Self::new(&Self::default_path())
+    }
+
+    pub fn default_path() -> PathBuf {
+        dirs::data_dir()
+            .expect("Failed to get data directory")
+            .join("trlt")
+            .join("usage.db")
+    }
+
+    fn conn(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.conn.lock().expect("usage log lock poisoned")
+    }
+
+    /// Record the tokens a run used.
+    pub fn record(&self, meter: &UsageMeter, config: &Config) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        let conn = self.conn();
+        for entry in meter.entries() {
+            conn.execute(
+                "INSERT INTO usage (created_at, model, provider, source_lang, target_lang,
+                                    requests, prompt_tokens, completion_tokens, cost)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    now,
+                    entry.usage.model,
+                    entry.provider.to_string(),
+                    entry.from,
+                    entry.to,
+                    entry.requests as i64,
+                    entry.usage.prompt_tokens as i64,
+                    entry.usage.completion_tokens as i64,
+                    usage_cost(&entry.usage, config)
+                ],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// The estimated cost of the runs of the current month. Runs with models of unknown
+    /// price count for nothing.
+    pub fn month_cost(&self) -> Result<f64> {
+        let today = Utc::now();
+        let month = format!("{:04}-{:02}", today.year(), today.month());
+        let cost: Option<f64> = self.conn().query_row(
+            "SELECT SUM(cost) FROM usage WHERE substr(created_at, 1, 7) = ?1",
+            params![month],
+            |row| row.get(0),
+        )?;
+        Ok(cost.unwrap_or_default())
+    }
+
+    /// The usage of the runs since `since`, grouped as asked, in the order of the groups.
+    pub fn summary(
+        &self,
+        grouping: UsageGrouping,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<UsageSummary>> {
+        let key = match grouping {
+            UsageGrouping::Day => "substr(created_at, 1, 10)",
+            UsageGrouping::Model => "model",
+            UsageGrouping::Provider => "COALESCE(provider, 'unknown')",
+            UsageGrouping::Pair => {
+                "COALESCE(COALESCE(source_lang, 'auto') || ' -> ' || target_lang, 'unknown')"
+            }
+        };
+        let conn = self.conn();
+        let mut statement = conn.prepare(&format!(
+            "SELECT {key}, SUM(requests), SUM(prompt_tokens), SUM(completion_tokens),
+                    COALESCE(SUM(cost), 0)
+             FROM usage WHERE created_at >= ?1 GROUP BY 1 ORDER BY 1",
+        ))?;
+        let summaries = statement
+            .query_map(params![since.to_rfc3339()], |row| {
+                Ok(UsageSummary {
+                    key: row.get(0)?,
+                    requests: row.get::<_, i64>(1)? as u64,
+                    prompt_tokens: row.get::<_, i64>(2)? as u64,
+                    completion_tokens: row.get::<_, i64>(3)? as u64,
+                    cost: row.get(4)?,
+                })
+            })?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(summaries)
+    }
+}
+
+/// Translator that records the tokens used by the translations of the inner translator.
+pub struct UsageTranslator {
+    inner: Box<dyn Translator>,
+    meter: Arc<UsageMeter>,
+    /// The provider of the translations that do not tell theirs.
+    provider: ProviderKind,
+}
+
+impl UsageTranslator {
+    pub fn new(inner: Box<dyn Translator>, meter: Arc<UsageMeter>, provider: ProviderKind) -> Self {
+        Self {
+            inner,
+            meter,
+            provider,
+        }
+    }
+
+    fn record(&self, request: &TranslationRequest, translation: &Translation) {
+        if let Some(usage) = &translation.usage {
+            let provider = translation.provider.unwrap_or(self.provider);
+            self.meter.record(usage, provider, request);
+        }
+    }
+}
+
+#[async_trait]
+impl Translator for UsageTranslator {
+    async fn translate(&self, request: &TranslationRequest) -> Result<Translation> {
+        let translation = self.inner.translate(request).await?;
+        self.record(request, &translation);
+        Ok(translation)
+    }
+
+    async fn translate_stream(
+        &self,
+        request: &TranslationRequest,
+        on_chunk: OnChunk<'_>,
+    ) -> Result<Translation> {
+        let translation = self.inner.translate_stream(request, on_chunk).await?;
+        self.record(request, &translation);
+        Ok(translation)
+    }
+
+    async fn models(&self) -> Result<Vec<Model>> {
+        self.inner.models().await
+    }
+}
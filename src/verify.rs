@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use futures_util::future::try_join_all;
+use serde::Serialize;
+use tokio::sync::Semaphore;
+
+use crate::{Result, TranslationRequest, Translator};
+
+/// How similar a back-translation must be to its source, between 0 and 1, not to be flagged.
+pub const DEFAULT_VERIFY_THRESHOLD: f64 = 0.5;
+
+/// A segment checked by translating its translation back into the source language.
+#[derive(Debug, Clone, Serialize)]
+pub struct Verification {
+    pub source: String,
+    pub translation: String,
+    pub back_translation: String,
+    /// How similar the back-translation is to the source, between 0 and 1.
+    pub similarity: f64,
+}
+
+impl Verification {
+    /// Whether the back-translation strays too far from the source, which hints at a
+    /// mistranslation.
+    pub fn diverges(&self, threshold: f64) -> bool {
+        self.similarity < threshold
+    }
+}
+
+/// Translate each `(source, translation)` pair back from `to` into `from`, running at most
+/// `jobs` translations at a time, and measure how close each back-translation is to its source.
+pub async fn back_translate(
+    pairs: Vec<(String, String)>,
+    translator: &dyn Translator,
+    from: &str,
+    to: &str,
+    jobs: usize,
+) -> Result<Vec<Verification>> {
+    let semaphore = Semaphore::new(jobs.max(1));
+    try_join_all(pairs.into_iter().map(|(source, translation)| {
+        let semaphore = &semaphore;
+        async move {
+            let _permit = semaphore
+                .acquire()
+                .await
+                .expect("semaphore is never closed");
+            let request = TranslationRequest::new(translation.clone(), Some(to.to_string()), from);
+            let back_translation = translator.translate(&request).await?.text;
+
+            Ok(Verification {
+                similarity: similarity(&source, &back_translation),
+                source,
+                translation,
+                back_translation,
+            })
+        }
+    }))
+    .await
+}
+
+/// Pair up the paragraphs of a plain text and its translation, or the whole texts when their
+/// paragraphs do not line up.
+pub fn paragraph_pairs(source: &str, translation: &str) -> Vec<(String, String)> {
+    let paragraphs = |text: &str| {
+        text.split("\n\n")
+            .map(str::trim)
+            .filter(|paragraph| !paragraph.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    };
+    let (sources, translations) = (paragraphs(source), paragraphs(translation));
+
+    if sources.len() == translations.len() {
+        sources.into_iter().zip(translations).collect()
+    } else {
+        vec![(source.trim().to_string(), translation.trim().to_string())]
+    }
+}
+
+/// The Sørensen–Dice coefficient of the character bigrams of two texts, ignoring case,
+/// punctuation and spacing, so it works for scripts written without spaces too.
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let bigrams = |text: &str| {
+        let chars = text
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect::<Vec<_>>();
+        let mut bigrams = HashMap::new();
+        for pair in chars.windows(2) {
+            *bigrams.entry((pair[0], pair[1])).or_insert(0) += 1;
+        }
+        bigrams
+    };
+    let (a, b) = (bigrams(a), bigrams(b));
+
+    let total = a.values().sum::<usize>() + b.values().sum::<usize>();
+    if total == 0 {
+        return 1.0;
+    }
+    let shared = a
+        .iter()
+        .map(|(bigram, count)| (*count).min(b.get(bigram).copied().unwrap_or(0)))
+        .sum::<usize>();
+    2.0 * shared as f64 / total as f64
+}